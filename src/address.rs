@@ -1,7 +1,12 @@
+use crate::boundaries::Boundary;
+use crate::geocode::ReverseGeocode;
+use crate::matching::{CompareRecord, CompareRecords, CompareStatus};
+use crate::parcels::Parcels;
 use crate::table;
+use crate::utils::distance;
 use address::prelude::{
-    Address, AddressStatus, MatchRecord, MatchRecords, MatchStatus, SpatialAddress,
-    SpatialAddresses,
+    Address, AddressStatus, MatchRecord, MatchRecords, SpatialAddress, SpatialAddresses,
+    StreetNamePostType, StreetNamePreDirectional, SubaddressType,
 };
 use aid::error::Bandage;
 use derive_more::{Deref, DerefMut};
@@ -47,6 +52,13 @@ impl AddressColumns {
         }
         values
     }
+
+    /// Returns true if the user may edit the column.  The label is built from the other columns,
+    /// so it changes with them, and the status changes only through the lifecycle actions of the
+    /// editor, which record the reason and dates.
+    pub fn editable(&self) -> bool {
+        !matches!(self, Self::Label | Self::Status)
+    }
 }
 
 impl fmt::Display for AddressColumns {
@@ -147,6 +159,37 @@ impl AddressPoint {
         }
     }
 
+    /// The typed [`table::Cell`] of `column`, so numbers and zip codes sort numerically, and
+    /// enumerated values sort by their place in the enumeration of the `address` crate.  Missing
+    /// optional values are [`table::Cell::Empty`].
+    pub fn cell(&self, column: &AddressColumns) -> table::Cell {
+        use table::Cell;
+        match *column {
+            AddressColumns::Number => Cell::Integer(self.address.number()),
+            AddressColumns::Zip => Cell::Integer(self.address.zip()),
+            AddressColumns::Status => {
+                let status = self.address.status();
+                Cell::Enum(status.to_string(), status as usize)
+            }
+            AddressColumns::Directional => match self.address.directional() {
+                Some(value) => Cell::Enum(value.to_string(), value as usize),
+                None => Cell::Empty,
+            },
+            AddressColumns::StreetType => match self.address.street_type() {
+                Some(value) => Cell::Enum(value.abbreviate().to_string(), value as usize),
+                None => Cell::Empty,
+            },
+            AddressColumns::SubaddressType => match self.address.subaddress_type() {
+                Some(value) => Cell::Enum(value.to_string(), value as usize),
+                None => Cell::Empty,
+            },
+            AddressColumns::SubaddressId => Cell::optional(self.address.subaddress_id()),
+            AddressColumns::Label | AddressColumns::StreetName => {
+                Cell::Text(self.column::<String>(column))
+            }
+        }
+    }
+
     pub fn columns(&self) -> Vec<String> {
         let mut values = Vec::new();
         for column in AddressColumns::iter() {
@@ -154,6 +197,130 @@ impl AddressPoint {
         }
         values
     }
+
+    /// Sets the value of `column` to `value`, after checking it against the enumerated values
+    /// accepted by the `address` crate.  An empty value clears optional fields.
+    pub fn set_column(&mut self, column: &AddressColumns, value: &str) -> aid::prelude::Clean<()> {
+        let value = value.trim();
+        let invalid = |name: &str| Bandage::Hint(format!("{value} is not a valid {name}."));
+        match column {
+            AddressColumns::Label => {
+                return Err(Bandage::Hint("The label cannot be edited.".to_string()))
+            }
+            AddressColumns::Number => {
+                let number = value
+                    .parse::<i64>()
+                    .map_err(|_| invalid("address number"))?;
+                if number < 0 {
+                    return Err(invalid("address number"));
+                }
+                *self.address.number_mut() = number;
+            }
+            AddressColumns::Directional => {
+                *self.address.directional_mut() = match value {
+                    "" => None,
+                    _ => Some(
+                        StreetNamePreDirectional::match_mixed(value)
+                            .ok_or_else(|| invalid("directional prefix"))?,
+                    ),
+                };
+            }
+            AddressColumns::StreetName => {
+                if value.is_empty() {
+                    return Err(Bandage::Hint(
+                        "The street name cannot be empty.".to_string(),
+                    ));
+                }
+                *self.address.street_name_mut() = value.to_uppercase();
+            }
+            AddressColumns::StreetType => {
+                *self.address.street_type_mut() = match value {
+                    "" => None,
+                    _ => Some(
+                        StreetNamePostType::match_mixed(value)
+                            .ok_or_else(|| invalid("street type"))?,
+                    ),
+                };
+            }
+            AddressColumns::SubaddressType => {
+                *self.address.subaddress_type_mut() = match value {
+                    "" => None,
+                    _ => Some(
+                        SubaddressType::match_mixed(value)
+                            .ok_or_else(|| invalid("subaddress type"))?,
+                    ),
+                };
+            }
+            AddressColumns::SubaddressId => {
+                *self.address.subaddress_id_mut() = match value {
+                    "" => None,
+                    _ => Some(value.to_uppercase()),
+                };
+            }
+            AddressColumns::Zip => {
+                let zip = value.parse::<i64>().map_err(|_| invalid("zip code"))?;
+                if !(10000..=99999).contains(&zip) {
+                    return Err(invalid("zip code"));
+                }
+                *self.address.zip_mut() = zip;
+            }
+            AddressColumns::Status => {
+                *self.address.status_mut() =
+                    parse_status(value).ok_or_else(|| invalid("status"))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds an address for unit tests from its number, street name, zip code and location.
+    #[cfg(test)]
+    pub fn sample(
+        number: i64,
+        street: &str,
+        zip: i64,
+        latitude: f64,
+        longitude: f64,
+    ) -> SpatialAddress {
+        let mut point = Self::default();
+        let columns = [
+            (AddressColumns::Number, number.to_string()),
+            (AddressColumns::StreetName, street.to_string()),
+            (AddressColumns::Zip, zip.to_string()),
+        ];
+        for (column, value) in columns.iter() {
+            point.set_column(column, value).unwrap();
+        }
+        point.address.latitude = latitude;
+        point.address.longitude = longitude;
+        point.address
+    }
+
+    /// Builds an address for unit tests at an offset of `north` and `east` from a shared origin in
+    /// Grants Pass.  Offsets are in thousandths of a degree, roughly 110 m north and 80 m east.
+    #[cfg(test)]
+    pub fn sample_at(number: i64, street: &str, zip: i64, north: f64, east: f64) -> SpatialAddress {
+        Self::sample(
+            number,
+            street,
+            zip,
+            42.44 + north * 0.001,
+            -123.33 + east * 0.001,
+        )
+    }
+}
+
+/// Parses an address status from its display name, ignoring case.
+pub fn parse_status(value: &str) -> Option<AddressStatus> {
+    [
+        AddressStatus::Current,
+        AddressStatus::Other,
+        AddressStatus::Pending,
+        AddressStatus::Temporary,
+        AddressStatus::Retired,
+        AddressStatus::Virtual,
+    ]
+    .into_iter()
+    .find(|v| v.to_string().to_lowercase() == value.to_lowercase())
 }
 
 impl From<&SpatialAddress> for AddressPoint {
@@ -231,8 +398,8 @@ impl Feature for AddressPoint {
 }
 
 impl table::Columnar for AddressPoint {
-    fn values(&self) -> Vec<String> {
-        self.columns()
+    fn cells(&self) -> Vec<table::Cell> {
+        AddressColumns::iter().map(|v| self.cell(&v)).collect()
     }
 
     fn id(&self) -> uuid::Uuid {
@@ -244,89 +411,39 @@ impl table::Columnar for AddressPoint {
 pub struct AddressPoints(Vec<AddressPoint>);
 
 impl AddressPoints {
-    pub fn sort_by_col(&mut self, column_index: usize, reverse: bool) {
-        // Parse the index to an address column.
-        if let Ok(column) = AddressColumns::try_from(column_index) {
-            // Match against the column type and sort.
-            match column {
-                AddressColumns::Label => {
-                    if reverse {
-                        self.sort_by(|a, b| b.address.label().cmp(&a.address.label()));
-                    } else {
-                        self.sort_by(|a, b| a.address.label().cmp(&b.address.label()));
-                    }
-                }
-                AddressColumns::Number => {
-                    if reverse {
-                        self.sort_by(|a, b| b.address.number().cmp(&a.address.number()));
-                    } else {
-                        self.sort_by(|a, b| a.address.number().cmp(&b.address.number()));
-                    }
-                }
-                AddressColumns::Directional => {
-                    if reverse {
-                        self.sort_by(|a, b| b.address.directional().cmp(&a.address.directional()));
-                    } else {
-                        self.sort_by(|a, b| a.address.directional().cmp(&b.address.directional()));
-                    }
-                }
-                AddressColumns::StreetName => {
-                    if reverse {
-                        self.sort_by(|a, b| b.address.street_name().cmp(&a.address.street_name()));
-                    } else {
-                        self.sort_by(|a, b| a.address.street_name().cmp(&b.address.street_name()));
-                    }
-                }
-                AddressColumns::StreetType => {
-                    if reverse {
-                        self.sort_by(|a, b| b.address.street_type().cmp(&a.address.street_type()));
-                    } else {
-                        self.sort_by(|a, b| a.address.street_type().cmp(&b.address.street_type()));
-                    }
-                }
-                AddressColumns::SubaddressType => {
-                    if reverse {
-                        self.sort_by(|a, b| {
-                            b.address
-                                .subaddress_type()
-                                .cmp(&a.address.subaddress_type())
-                        });
-                    } else {
-                        self.sort_by(|a, b| {
-                            a.address
-                                .subaddress_type()
-                                .cmp(&b.address.subaddress_type())
-                        });
-                    }
-                }
-                AddressColumns::SubaddressId => {
-                    if reverse {
-                        self.sort_by(|a, b| {
-                            b.address.subaddress_id().cmp(&a.address.subaddress_id())
-                        });
-                    } else {
-                        self.sort_by(|a, b| {
-                            a.address.subaddress_id().cmp(&b.address.subaddress_id())
-                        });
-                    }
-                }
-                AddressColumns::Zip => {
-                    if reverse {
-                        self.sort_by(|a, b| b.address.zip().cmp(&a.address.zip()));
-                    } else {
-                        self.sort_by(|a, b| a.address.zip().cmp(&b.address.zip()));
-                    }
-                }
-                AddressColumns::Status => {
-                    if reverse {
-                        self.sort_by(|a, b| b.address.status().cmp(&a.address.status()));
-                    } else {
-                        self.sort_by(|a, b| a.address.status().cmp(&b.address.status()));
-                    }
-                }
-            }
-        }
+    /// Returns the `count` address points nearest to `latitude` and `longitude`, with their
+    /// distance in meters, nearest first.
+    pub fn nearest(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        count: usize,
+    ) -> Vec<(&AddressPoint, f64)> {
+        let mut points = self
+            .iter()
+            .map(|v| {
+                let meters = distance(latitude, longitude, v.address.latitude, v.address.longitude);
+                (v, meters)
+            })
+            .collect::<Vec<(&AddressPoint, f64)>>();
+        points.sort_by(|a, b| a.1.total_cmp(&b.1));
+        points.truncate(count);
+        points
     }
+
+    /// Describes the location at `latitude` and `longitude` by the `count` nearest address points,
+    /// the containing parcel in `parcels` and the containing members of `boundaries`.
+    pub fn reverse(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        count: usize,
+        parcels: Option<&Parcels>,
+        boundaries: &[Boundary],
+    ) -> ReverseGeocode {
+        ReverseGeocode::locate(self, latitude, longitude, count, parcels, boundaries)
+    }
+
     pub fn save<P: AsRef<std::path::Path>>(&self, path: P) -> aid::prelude::Clean<()> {
         tracing::info!("Serializing to binary.");
         address::prelude::save(self, path)
@@ -338,6 +455,19 @@ impl AddressPoints {
         let addresses: AddressPoints = bincode::deserialize(&vec[..])?;
         Ok(addresses)
     }
+
+    pub fn get_mut(&mut self, id: &uuid::Uuid) -> Option<&mut AddressPoint> {
+        self.iter_mut().find(|v| v.id == *id)
+    }
+
+    /// The addresses of the points, as a new set of spatial addresses.
+    pub fn addresses(&self) -> SpatialAddresses {
+        let records = self
+            .iter()
+            .map(|v| v.address.clone())
+            .collect::<Vec<SpatialAddress>>();
+        SpatialAddresses::from(&records[..])
+    }
 }
 
 impl table::Tabular<AddressPoint> for AddressPoints {
@@ -349,8 +479,15 @@ impl table::Tabular<AddressPoint> for AddressPoints {
         self.to_vec()
     }
 
-    fn sort_by_col(&mut self, column_index: usize, reverse: bool) {
-        self.sort_by_col(column_index, reverse);
+    fn set_rows(&mut self, rows: Vec<AddressPoint>) {
+        self.0 = rows;
+    }
+
+    fn editable(column_index: usize) -> bool {
+        match AddressColumns::try_from(column_index) {
+            Ok(column) => column.editable(),
+            Err(_) => false,
+        }
     }
 }
 
@@ -403,6 +540,7 @@ impl Symbol<AddressPoint> for AddressSymbol {
 #[derive(Debug, Clone, PartialEq)]
 pub struct MatchPoint {
     record: MatchRecord,
+    status: CompareStatus,
     geo_point: GeoPoint2d,
 }
 
@@ -437,8 +575,21 @@ impl From<&MatchRecord> for MatchPoint {
             galileo_types::geo::GeoPoint::lat(record),
             galileo_types::geo::GeoPoint::lon(record),
         );
+        let status = CompareStatus::from(&record.match_status);
         let record = record.clone();
-        Self { record, geo_point }
+        Self {
+            record,
+            status,
+            geo_point,
+        }
+    }
+}
+
+impl From<&CompareRecord> for MatchPoint {
+    fn from(record: &CompareRecord) -> Self {
+        let mut point = Self::from(&record.record);
+        point.status = record.status;
+        point
     }
 }
 
@@ -457,6 +608,16 @@ impl From<&MatchRecords> for MatchPoints {
     }
 }
 
+impl From<&CompareRecords> for MatchPoints {
+    fn from(records: &CompareRecords) -> Self {
+        let records = records
+            .iter()
+            .map(|r| r.into())
+            .collect::<Vec<MatchPoint>>();
+        Self { records }
+    }
+}
+
 pub struct MatchSymbol {}
 
 impl Symbol<MatchPoint> for MatchSymbol {
@@ -475,10 +636,11 @@ impl Symbol<MatchPoint> for MatchSymbol {
         let Geom::Point(point) = geometry else {
             return primitives;
         };
-        let color = match &feature.record.match_status {
-            MatchStatus::Matching => Color::BLUE,
-            MatchStatus::Divergent => Color::from_hex("#dbc200"),
-            MatchStatus::Missing => Color::from_hex("#ad0000"),
+        let color = match &feature.status {
+            CompareStatus::Matching => Color::BLUE,
+            CompareStatus::Divergent => Color::from_hex("#dbc200"),
+            CompareStatus::Probable => Color::from_hex("#db6e00"),
+            CompareStatus::Missing => Color::from_hex("#ad0000"),
         };
         primitives.push(RenderPrimitive::new_point_ref(
             point,
@@ -1,15 +1,23 @@
 use crate::controls::{act, command};
-use crate::state::{self, lens};
+use crate::project;
+use crate::state;
 use crate::tab;
 use aid::prelude::Clean;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use winit::{event, event_loop, window};
 
+/// How often to redraw while an operation runs in the background, so its results appear when it
+/// finishes.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
 /// Top level application state.
 pub struct App {
     window: Arc<window::Window>,
     state: state::State,
     exit: bool,
+    /// Periodically saves the workspace to a recovery file.
+    autosave: project::Autosave,
 }
 
 impl App {
@@ -22,12 +30,22 @@ impl App {
             .build(&event_loop)?;
         let window = Arc::new(window);
         let mut state = state::State::new(Arc::clone(&window)).await;
-        if let Ok(lens) = lens::Lens::load("data/state.data") {
-            state.lens = lens.clone();
-            state.tab = tab::TabState::new(lens.clone());
-            // state.tab = egui_dock::DockState::new(vec![tab::Tab::new(lens)]);
-        } else {
-            tracing::info!("Could not read state from storage.");
+        // Reopen the most recent project that can still be read.
+        for path in project::RecentProjects::local().to_vec() {
+            match project::Project::open(&path) {
+                Ok(project) => {
+                    if let Some((_, lens)) = project.tabs.iter_all_tabs().next() {
+                        state.lens = lens.clone();
+                    }
+                    state.tab = tab::TabState::from_project(project, &path);
+                    break;
+                }
+                Err(e) => tracing::info!("Could not read {}: {}", path.display(), e.to_string()),
+            }
+        }
+        // A recovery file left behind means the last session did not exit cleanly.
+        if let Some(project) = project::Autosave::recover() {
+            state.tab.offer_recovery(project);
         }
 
         Ok((
@@ -35,6 +53,7 @@ impl App {
                 window,
                 state,
                 exit: false,
+                autosave: Default::default(),
             },
             event_loop,
         ))
@@ -42,14 +61,31 @@ impl App {
 
     pub async fn run(mut self, event_loop: event_loop::EventLoop<()>) -> Clean<()> {
         let _ = event_loop.run(move |event, ewlt| {
-            ewlt.set_control_flow(event_loop::ControlFlow::Wait);
             if self.exit {
                 ewlt.exit()
             }
 
             match event {
+                event::Event::NewEvents(event::StartCause::ResumeTimeReached { .. }) => {
+                    // Redraw to collect the results of background work.
+                    if self.state.tab.busy() {
+                        self.window.request_redraw();
+                    }
+                }
                 event::Event::AboutToWait => {
                     self.state.about_to_wait();
+                    self.close();
+                    self.autosave();
+                    // Wake for the next autosave even when no events arrive, unless autosaves are
+                    // paused while the restore prompt is open.
+                    let flow = if self.state.tab.busy() {
+                        event_loop::ControlFlow::WaitUntil(Instant::now() + POLL_INTERVAL)
+                    } else if self.state.tab.recovery_pending() {
+                        event_loop::ControlFlow::Wait
+                    } else {
+                        event_loop::ControlFlow::WaitUntil(self.autosave.next())
+                    };
+                    ewlt.set_control_flow(flow);
                 }
                 event::Event::WindowEvent {
                     ref event,
@@ -167,13 +203,48 @@ impl App {
 
     pub fn close_requested(&mut self) {
         tracing::info!("Close requested.");
-        let state = self.state();
-        if state.lens.save("data/state.data").is_ok() {
-            tracing::info!("State saved from ref.");
-        } else {
-            tracing::info!("Unable to save state to file.");
+        // Ask before overwriting an open project; the prompt shows on the next frame.
+        self.state.tab.request_close();
+        self.window.request_redraw();
+    }
+
+    /// Exits once the user has answered the close prompt.
+    pub fn close(&mut self) {
+        match self.state.tab.closing() {
+            tab::Closing::Save => {
+                // Save the tabs on display, rather than the unused `lens` field of the state.
+                match self.state.tab.save() {
+                    Ok(_) => {
+                        tracing::info!("Project saved.");
+                        // The session is safe in the project, so the recovery file is no longer needed.
+                        self.autosave.clear();
+                    }
+                    // Keep the recovery file, so the session is offered again on the next start.
+                    Err(e) => tracing::warn!("Unable to save project: {}", e.to_string()),
+                }
+                self.exit = true;
+            }
+            tab::Closing::Discard => {
+                tracing::info!("Closing without saving.");
+                self.autosave.clear();
+                self.exit = true;
+            }
+            tab::Closing::Open | tab::Closing::Asking => {}
+        }
+    }
+
+    /// Saves the workspace to the recovery file if an autosave is due.
+    pub fn autosave(&mut self) {
+        if !self.autosave.elapsed() {
+            return;
+        }
+        // Keep the previous session on disk until the user decides whether to restore it, and
+        // let a slow write finish before starting the next.
+        if self.state.tab.recovery_pending() || self.autosave.busy() {
+            self.autosave.defer();
+            return;
         }
-        self.exit = true;
+        self.autosave.save(self.state.tab.tabs());
     }
 
     pub fn state(&self) -> &state::State {
@@ -0,0 +1,212 @@
+//! The `audit` module keeps an append-only log of data edits, imports, comparison runs and
+//! exports.  Entries are appended to a csv file on the local machine as they happen, and the
+//! file is never rewritten, so the log holds a complete record of work done in ams.
+use crate::table::{Cell, Columnar, Filtration, Tabular};
+use crate::utils::{from_csv, user_name};
+use aid::prelude::{Bandage, Clean};
+use chrono::{DateTime, Local, Utc};
+use derive_more::{Deref, DerefMut};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// Location of the audit log on the local machine.
+pub const AUDIT_PATH: &str = "data/audit.csv";
+
+/// The `AuditKind` enum describes the kind of action recorded in an [`AuditEntry`].
+#[derive(
+    Debug,
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    EnumIter,
+    Deserialize,
+    Serialize,
+)]
+pub enum AuditKind {
+    #[default]
+    Edit,
+    Import,
+    Compare,
+    Export,
+    Status,
+}
+
+impl std::fmt::Display for AuditKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Edit => write!(f, "Edit"),
+            Self::Import => write!(f, "Import"),
+            Self::Compare => write!(f, "Compare"),
+            Self::Export => write!(f, "Export"),
+            Self::Status => write!(f, "Status"),
+        }
+    }
+}
+
+/// The `AuditEntry` struct records a single action.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct AuditEntry {
+    pub id: uuid::Uuid,
+    pub time: DateTime<Utc>,
+    pub user: String,
+    pub kind: AuditKind,
+    /// The `dataset` field holds the id of the dataset acted on, if any.
+    pub dataset: Option<uuid::Uuid>,
+    /// The `summary` field describes the action.
+    pub summary: String,
+}
+
+impl AuditEntry {
+    pub fn new(kind: AuditKind, dataset: Option<uuid::Uuid>, summary: &str) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4(),
+            time: Utc::now(),
+            user: user_name(),
+            kind,
+            dataset,
+            summary: summary.to_string(),
+        }
+    }
+
+    /// Appends the entry to the log at `path`, writing the header row if the file is new.
+    pub fn append<P: AsRef<Path>>(&self, path: P) -> Clean<()> {
+        let new = !path.as_ref().exists();
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        let mut wtr = csv::WriterBuilder::new().has_headers(new).from_writer(file);
+        wtr.serialize(self)
+            .map_err(|e| Bandage::Hint(e.to_string()))?;
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+/// Records an action of kind `kind` in the audit log at [`AUDIT_PATH`].  Failure to write the log
+/// is reported but does not interrupt the action.
+pub fn record(kind: AuditKind, dataset: Option<uuid::Uuid>, summary: &str) {
+    let entry = AuditEntry::new(kind, dataset, summary);
+    if let Err(e) = entry.append(AUDIT_PATH) {
+        tracing::warn!("Could not write audit log: {}", e.to_string());
+    }
+}
+
+#[derive(EnumIter, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AuditColumns {
+    Time,
+    User,
+    Kind,
+    Dataset,
+    Summary,
+}
+
+impl AuditColumns {
+    pub fn value(&self, entry: &AuditEntry) -> String {
+        match self {
+            Self::Time => entry
+                .time
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+            Self::User => entry.user.clone(),
+            Self::Kind => entry.kind.to_string(),
+            Self::Dataset => match entry.dataset {
+                Some(id) => id.to_string(),
+                None => "".to_string(),
+            },
+            Self::Summary => entry.summary.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for AuditColumns {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Time => write!(f, "Time"),
+            Self::User => write!(f, "User"),
+            Self::Kind => write!(f, "Action"),
+            Self::Dataset => write!(f, "Dataset"),
+            Self::Summary => write!(f, "Summary"),
+        }
+    }
+}
+
+impl Columnar for AuditEntry {
+    fn cells(&self) -> Vec<Cell> {
+        AuditColumns::iter()
+            .map(|v| Cell::Text(v.value(self)))
+            .collect::<Vec<Cell>>()
+    }
+
+    fn id(&self) -> uuid::Uuid {
+        self.id
+    }
+}
+
+/// The `AuditLog` struct holds the entries of the audit log, oldest first.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize, Deref, DerefMut)]
+pub struct AuditLog(Vec<AuditEntry>);
+
+impl AuditLog {
+    pub fn load<P: AsRef<Path>>(path: P) -> Clean<Self> {
+        Ok(Self(from_csv(path)?))
+    }
+
+    /// Loads the log at [`AUDIT_PATH`], or an empty log if none exists.
+    pub fn local() -> Self {
+        match Self::load(AUDIT_PATH) {
+            Ok(log) => log,
+            Err(_) => {
+                tracing::info!("No audit log found.");
+                Self::default()
+            }
+        }
+    }
+
+    /// Copies the entries to a csv file at `path`.
+    pub fn to_csv<P: AsRef<Path>>(&self, path: P) -> Clean<()> {
+        let mut wtr = csv::Writer::from_path(path).map_err(|e| Bandage::Hint(e.to_string()))?;
+        for entry in self.iter() {
+            wtr.serialize(entry)
+                .map_err(|e| Bandage::Hint(e.to_string()))?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+impl Tabular<AuditEntry> for AuditLog {
+    fn headers() -> Vec<String> {
+        AuditColumns::iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>()
+    }
+
+    fn rows(&self) -> Vec<AuditEntry> {
+        self.to_vec()
+    }
+
+    fn set_rows(&mut self, rows: Vec<AuditEntry>) {
+        self.0 = rows;
+    }
+}
+
+impl Filtration<AuditLog, String> for AuditLog {
+    /// Filters entries to the kind named `filter`, or to entries for the dataset with id `filter`.
+    fn filter(&mut self, filter: &String) -> Self {
+        let mut entries = self.to_vec();
+        entries.retain(|v| {
+            v.kind.to_string() == *filter
+                || v.dataset.map(|id| id.to_string()) == Some(filter.clone())
+        });
+        Self(entries)
+    }
+}
@@ -0,0 +1,322 @@
+//! The `batch` module geocodes lists of addresses without coordinates, such as the spreadsheets
+//! sent by utility billing and business licensing.  Each row of a [`BatchInput`] is matched against
+//! a loaded dataset with a [`Geocoder`], producing a [`BatchRecord`] with the location, matched
+//! address and its record id, score and [`MatchKind`].  Numbers missing from the data fall back to
+//! a location interpolated between their neighbors on the same street.
+use crate::geocode::{CandidatePoint, CandidatePoints, Geocoder, Interpolation, Query};
+use crate::table::{Cell, Columnar, Filtration, Tabular};
+use aid::prelude::{Bandage, Clean};
+use derive_more::{Deref, DerefMut};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// Minimum score of a fuzzy match.  An exact number and subaddress leave the street name room for
+/// a typo, while a partial number on the right street falls short.
+pub const FUZZY_THRESHOLD: f64 = 0.85;
+
+/// The `MatchKind` enum describes how a row of a batch was matched to the address data.
+#[derive(EnumIter, Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum MatchKind {
+    /// The number, street and subaddress all match an address.
+    Exact,
+    /// The best candidate differs from the input, but scores above [`FUZZY_THRESHOLD`].
+    Fuzzy,
+    /// No address matches, so the location is interpolated between the neighbors of the number
+    /// on the same street.
+    Interpolated,
+    #[default]
+    Unmatched,
+}
+
+impl MatchKind {
+    /// Classifies a candidate by its `score`.
+    pub fn from_score(score: f64) -> Self {
+        if score >= 1.0 - 1e-9 {
+            Self::Exact
+        } else if score >= FUZZY_THRESHOLD {
+            Self::Fuzzy
+        } else {
+            Self::Unmatched
+        }
+    }
+}
+
+impl fmt::Display for MatchKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Exact => write!(f, "exact"),
+            Self::Fuzzy => write!(f, "fuzzy"),
+            Self::Interpolated => write!(f, "interpolated"),
+            Self::Unmatched => write!(f, "unmatched"),
+        }
+    }
+}
+
+/// The `BatchInput` struct holds a csv file of addresses to geocode, as read.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct BatchInput {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl BatchInput {
+    /// Reads the csv file at `path`.  The first row holds the headers.
+    pub fn from_csv<P: AsRef<Path>>(path: P) -> Clean<Self> {
+        let mut rdr = csv::Reader::from_path(path).map_err(|e| Bandage::Hint(e.to_string()))?;
+        let headers = rdr
+            .headers()
+            .map_err(|e| Bandage::Hint(e.to_string()))?
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>();
+        let mut rows = Vec::new();
+        let mut dropped = 0;
+        for result in rdr.records() {
+            match result {
+                Ok(record) => rows.push(record.iter().map(|v| v.to_string()).collect()),
+                Err(e) => {
+                    tracing::info!("Dropping: {:#?}.", e.to_string());
+                    dropped += 1;
+                }
+            }
+        }
+        if dropped > 0 {
+            tracing::info!("{} records dropped.", dropped);
+        }
+        Ok(Self { headers, rows })
+    }
+
+    /// Guesses the column holding the address, preferring a header named like "address".
+    pub fn address_column(&self) -> usize {
+        self.headers
+            .iter()
+            .position(|v| v.to_lowercase().contains("address"))
+            .unwrap_or_default()
+    }
+
+    /// Geocodes the text in `column` of each row against `geocoder`.
+    pub fn geocode(&self, column: usize, geocoder: &Geocoder) -> BatchRecords {
+        let records = self
+            .rows
+            .par_iter()
+            .map(|row| {
+                let text = row.get(column).cloned().unwrap_or_default();
+                BatchRecord::geocode(&text, row, geocoder)
+            })
+            .collect::<Vec<BatchRecord>>();
+        tracing::info!("Geocoded {} records.", records.len());
+        BatchRecords(records)
+    }
+}
+
+/// The `BatchRecord` struct is a row of a [`BatchInput`] with the result of geocoding it.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct BatchRecord {
+    /// The `id` field holds a [`uuid::Uuid`] for use by the [`crate::table::TableView`].
+    pub id: uuid::Uuid,
+    /// The `input` field holds the address text as read.
+    pub input: String,
+    /// The `fields` field holds the row as read, for export alongside the result.
+    pub fields: Vec<String>,
+    /// The `matched` field holds the label of the matched address, which links the row to the
+    /// address record.
+    pub matched: Option<String>,
+    /// The `record` field holds the id of the matched address in its dataset, as assigned by
+    /// [`Dataset::record_id`](crate::catalog::Dataset::record_id).
+    pub record: Option<uuid::Uuid>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub score: f64,
+    pub kind: MatchKind,
+    /// The `interpolation` field holds the neighbors and estimated error of an interpolated
+    /// location.
+    pub interpolation: Option<Interpolation>,
+}
+
+impl BatchRecord {
+    /// Geocodes `text` against `geocoder`, keeping the best candidate if it scores as a match.
+    /// Otherwise the location is interpolated along the street, if possible.
+    pub fn geocode(text: &str, fields: &[String], geocoder: &Geocoder) -> Self {
+        let mut record = Self {
+            id: uuid::Uuid::new_v4(),
+            input: text.to_string(),
+            fields: fields.to_vec(),
+            ..Default::default()
+        };
+        if text.trim().is_empty() {
+            return record;
+        }
+        let query = Query::parse(text, geocoder.lookup());
+        if let Some(candidate) = geocoder.candidates(&query, 1).first() {
+            record.score = candidate.score;
+            record.kind = MatchKind::from_score(candidate.score);
+            if record.kind != MatchKind::Unmatched {
+                record.matched = Some(candidate.label.clone());
+                record.record = Some(candidate.record);
+                record.latitude = Some(candidate.latitude);
+                record.longitude = Some(candidate.longitude);
+                return record;
+            }
+        }
+        if let Some(estimate) = geocoder.interpolate(&query) {
+            record.kind = MatchKind::Interpolated;
+            record.latitude = Some(estimate.latitude);
+            record.longitude = Some(estimate.longitude);
+            record.interpolation = Some(estimate);
+        }
+        record
+    }
+}
+
+#[derive(EnumIter, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BatchColumns {
+    Input,
+    Matched,
+    Kind,
+    Score,
+    Latitude,
+    Longitude,
+    Error,
+}
+
+impl BatchColumns {
+    pub fn value(&self, record: &BatchRecord) -> Cell {
+        match self {
+            Self::Input => Cell::Text(record.input.clone()),
+            Self::Matched => match (&record.matched, &record.interpolation) {
+                (Some(label), _) => Cell::Text(label.clone()),
+                (None, Some(estimate)) => {
+                    Cell::Text(format!("Between {} and {}", estimate.low, estimate.high))
+                }
+                (None, None) => Cell::Empty,
+            },
+            Self::Kind => Cell::variant(&record.kind),
+            Self::Score => Cell::Float(record.score, 2),
+            Self::Latitude => record.latitude.map_or(Cell::Empty, Cell::Coordinate),
+            Self::Longitude => record.longitude.map_or(Cell::Empty, Cell::Coordinate),
+            Self::Error => record
+                .interpolation
+                .as_ref()
+                .map_or(Cell::Empty, |v| Cell::Float(v.error, 0)),
+        }
+    }
+}
+
+impl fmt::Display for BatchColumns {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Input => write!(f, "Input"),
+            Self::Matched => write!(f, "Matched Address"),
+            Self::Kind => write!(f, "Match Type"),
+            Self::Score => write!(f, "Score"),
+            Self::Latitude => write!(f, "Latitude"),
+            Self::Longitude => write!(f, "Longitude"),
+            Self::Error => write!(f, "Error (m)"),
+        }
+    }
+}
+
+impl Columnar for BatchRecord {
+    fn cells(&self) -> Vec<Cell> {
+        BatchColumns::iter()
+            .map(|v| v.value(self))
+            .collect::<Vec<Cell>>()
+    }
+
+    fn id(&self) -> uuid::Uuid {
+        self.id
+    }
+}
+
+/// The `BatchRecords` struct is a wrapper around a vector of type [`BatchRecord`].
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize, Deref, DerefMut)]
+pub struct BatchRecords(Vec<BatchRecord>);
+
+impl BatchRecords {
+    /// Counts the records matched as `kind`.
+    pub fn count(&self, kind: MatchKind) -> usize {
+        self.iter().filter(|v| v.kind == kind).count()
+    }
+
+    /// Writes the records to a csv file at `path`, with the columns of the input named in
+    /// `headers` followed by the geocoding results.
+    pub fn to_csv<P: AsRef<Path>>(&self, path: P, headers: &[String]) -> Clean<()> {
+        let mut wtr = csv::Writer::from_path(path).map_err(|e| Bandage::Hint(e.to_string()))?;
+        let mut row = headers.to_vec();
+        row.extend(
+            [
+                "latitude",
+                "longitude",
+                "matched_address",
+                "matched_id",
+                "score",
+                "match_type",
+                "error_m",
+            ]
+            .iter()
+            .map(|v| v.to_string()),
+        );
+        wtr.write_record(&row)
+            .map_err(|e| Bandage::Hint(e.to_string()))?;
+        for record in self.iter() {
+            let mut row = record.fields.clone();
+            row.resize(headers.len(), String::new());
+            row.extend([
+                BatchColumns::Latitude.value(record).to_string(),
+                BatchColumns::Longitude.value(record).to_string(),
+                record.matched.clone().unwrap_or_default(),
+                record.record.map(|v| v.to_string()).unwrap_or_default(),
+                BatchColumns::Score.value(record).to_string(),
+                BatchColumns::Kind.value(record).to_string(),
+                BatchColumns::Error.value(record).to_string(),
+            ]);
+            wtr.write_record(&row)
+                .map_err(|e| Bandage::Hint(e.to_string()))?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+impl Tabular<BatchRecord> for BatchRecords {
+    fn headers() -> Vec<String> {
+        BatchColumns::iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>()
+    }
+
+    fn rows(&self) -> Vec<BatchRecord> {
+        self.to_vec()
+    }
+
+    fn set_rows(&mut self, rows: Vec<BatchRecord>) {
+        self.0 = rows;
+    }
+}
+
+impl Filtration<BatchRecords, String> for BatchRecords {
+    /// Filters the records to the match type named by `filter`.
+    fn filter(&mut self, filter: &String) -> Self {
+        let mut records = self.to_vec();
+        records.retain(|v| v.kind.to_string() == *filter);
+        Self(records)
+    }
+}
+
+impl From<&BatchRecords> for CandidatePoints {
+    /// Marks the located records, leaving out the unmatched.
+    fn from(records: &BatchRecords) -> Self {
+        let records = records
+            .iter()
+            .filter_map(|v| match (v.latitude, v.longitude) {
+                (Some(latitude), Some(longitude)) => Some(CandidatePoint::new(latitude, longitude)),
+                _ => None,
+            })
+            .collect::<Vec<CandidatePoint>>();
+        Self { records }
+    }
+}
@@ -0,0 +1,242 @@
+//! The `catalog` module tracks the address datasets loaded into the application.  Each
+//! [`Dataset`] records where the addresses came from, so that operations select datasets by id
+//! rather than by position.
+use crate::address::{AddressPoint, AddressPoints};
+use crate::audit::{self, AuditKind};
+use crate::data::AddressSource;
+use address::prelude::{
+    Addresses, GrantsPassSpatialAddresses, JosephineCountySpatialAddresses2024, Portable,
+    SpatialAddresses,
+};
+use aid::prelude::{Bandage, Clean};
+use chrono::{DateTime, Local, Utc};
+use derive_more::{Deref, DerefMut};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::Arc;
+use tracing::info;
+
+/// The `Dataset` struct holds a set of addresses loaded from a single file, with metadata
+/// describing the file.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Dataset {
+    pub id: uuid::Uuid,
+    /// The `source` field records the agency schema the addresses were read with.
+    pub source: AddressSource,
+    /// The `path` field holds the file the addresses were read from.
+    pub path: String,
+    /// The `loaded` field records when the file was read.
+    pub loaded: DateTime<Utc>,
+    /// The `records` field holds the number of addresses read from the file.
+    pub records: usize,
+    /// The `hash` field holds the SHA-256 hash of the file contents, as lower case hex.
+    pub hash: String,
+    /// The `label` field holds a name for the dataset chosen by the user.
+    pub label: String,
+    /// The `addresses` field is shared, so copies of the catalog kept for undo do not copy the
+    /// records.  Datasets are not edited in place; edits produce a new dataset.
+    pub addresses: Arc<SpatialAddresses>,
+}
+
+impl Dataset {
+    /// Creates a dataset from `addresses` read from `path` using the schema of `source`.  The
+    /// label defaults to the source name and file name.
+    pub fn new<P: AsRef<Path>>(
+        source: AddressSource,
+        path: P,
+        hash: String,
+        addresses: SpatialAddresses,
+    ) -> Self {
+        let path = path.as_ref();
+        let file = match path.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => path.display().to_string(),
+        };
+        let label = format!("{source} ({file})");
+        Self {
+            id: uuid::Uuid::new_v4(),
+            source,
+            path: path.display().to_string(),
+            loaded: Utc::now(),
+            records: addresses.len(),
+            hash,
+            label,
+            addresses: Arc::new(addresses),
+        }
+    }
+
+    /// Builds a dataset for unit tests from `records`.
+    #[cfg(test)]
+    pub fn sample(records: &[address::prelude::SpatialAddress]) -> Self {
+        Self::new(
+            AddressSource::grants_pass(),
+            "test.csv",
+            String::new(),
+            SpatialAddresses::from(records),
+        )
+    }
+
+    /// Reads addresses from the csv file at `path`, trying the schema of each [`AddressSource`]
+    /// and keeping the one that reads the most records.
+    pub fn read<P: AsRef<Path>>(path: P) -> Clean<Self> {
+        let path = path.as_ref();
+        let hash = content_hash(&std::fs::read(path)?);
+        let mut records = SpatialAddresses::default();
+        let mut source = None;
+        if let Ok(values) = GrantsPassSpatialAddresses::from_csv(path) {
+            if values.len() > records.len() {
+                source = Some(AddressSource::grants_pass());
+                records = SpatialAddresses::from(&values[..]);
+            }
+        }
+        if let Ok(mut values) = JosephineCountySpatialAddresses2024::from_csv(path) {
+            if values.len() > records.len() {
+                source = Some(AddressSource::josephine_county());
+                values.standardize();
+                records = SpatialAddresses::from(&values[..]);
+            }
+        }
+        match source {
+            Some(source) if !records.is_empty() => {
+                info!("Records found: {}", records.len());
+                Ok(Self::new(source, path, hash, records))
+            }
+            _ => Err(Bandage::Hint(format!(
+                "No addresses found in {}.",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Loads addresses from the binary file at `path`, previously read using the schema of
+    /// `source`.
+    pub fn load<P: AsRef<Path>>(source: AddressSource, path: P) -> Clean<Self> {
+        let path = path.as_ref();
+        let hash = content_hash(&std::fs::read(path)?);
+        let addresses = SpatialAddresses::load(path)?;
+        Ok(Self::new(source, path, hash, addresses))
+    }
+
+    /// Creates a new dataset holding `addresses` edited from the records of `self`, with the same
+    /// source and file.
+    pub fn edited(&self, addresses: SpatialAddresses) -> Clean<Self> {
+        let hash = content_hash(&bincode::serialize(&addresses)?);
+        let mut dataset = Self::new(self.source.clone(), &self.path, hash, addresses);
+        dataset.label = format!("{} (edited)", self.label);
+        Ok(dataset)
+    }
+
+    /// The records of the dataset as map points.  Each id is derived from the dataset id and the
+    /// position of the record, so the ids are the same each time the dataset is opened, and a
+    /// saved change set still finds its records.
+    pub fn points(&self) -> AddressPoints {
+        let mut points = AddressPoints::default();
+        for (i, address) in self.addresses.iter().enumerate() {
+            let mut point = AddressPoint::from(address);
+            point.id = self.record_id(i);
+            points.push(point);
+        }
+        points
+    }
+
+    /// The id of the record at position `index`, as assigned by [`Self::points`].
+    pub fn record_id(&self, index: usize) -> uuid::Uuid {
+        let (high, _) = self.id.as_u64_pair();
+        uuid::Uuid::from_u64_pair(high, index as u64)
+    }
+
+    /// Records the import of the dataset in the audit log.
+    pub fn audit(&self) {
+        let summary = format!(
+            "Imported {} records from {} as {}.",
+            self.records, self.path, self.label
+        );
+        audit::record(AuditKind::Import, Some(self.id), &summary);
+    }
+
+    /// The load time in local time, for display.
+    pub fn loaded_local(&self) -> String {
+        self.loaded
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    }
+
+    /// The first twelve characters of the content hash, for display.
+    pub fn short_hash(&self) -> String {
+        self.hash.chars().take(12).collect()
+    }
+}
+
+/// The SHA-256 hash of `bytes`, as lower case hex.
+pub fn content_hash(bytes: &[u8]) -> String {
+    let digest = Sha256::digest(bytes);
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The `Catalog` struct holds the loaded datasets, in the order loaded.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize, Deref, DerefMut)]
+pub struct Catalog(Vec<Dataset>);
+
+impl Catalog {
+    /// Adds `dataset` to the catalog, warning if a dataset with the same contents is already
+    /// loaded.
+    pub fn insert(&mut self, dataset: Dataset) {
+        if let Some(other) = self.iter().find(|v| v.hash == dataset.hash) {
+            tracing::warn!(
+                "{} has the same contents as {}.",
+                dataset.label,
+                other.label
+            );
+        }
+        self.push(dataset);
+    }
+
+    pub fn get(&self, id: &uuid::Uuid) -> Option<&Dataset> {
+        self.iter().find(|v| v.id == *id)
+    }
+
+    pub fn get_mut(&mut self, id: &uuid::Uuid) -> Option<&mut Dataset> {
+        self.iter_mut().find(|v| v.id == *id)
+    }
+
+    /// Removes the dataset with id `id`, if present.
+    pub fn remove(&mut self, id: &uuid::Uuid) -> Option<Dataset> {
+        let index = self.iter().position(|v| v.id == *id)?;
+        Some(self.0.remove(index))
+    }
+
+    /// The label of the dataset with id `id`, or an empty string if not present.
+    pub fn label(&self, id: &Option<uuid::Uuid>) -> String {
+        match id.and_then(|v| self.get(&v)) {
+            Some(dataset) => dataset.label.clone(),
+            None => "".to_string(),
+        }
+    }
+
+    /// Combo box selecting a dataset from the catalog.  Sets `selected` to the id of the chosen
+    /// dataset, returning true if the selection changed.
+    pub fn combo(&self, ui: &mut egui::Ui, label: &str, selected: &mut Option<uuid::Uuid>) -> bool {
+        let mut changed = false;
+        let text = match selected.and_then(|v| self.get(&v)) {
+            Some(dataset) => dataset.label.clone(),
+            None => "Select dataset".to_string(),
+        };
+        egui::ComboBox::from_label(label)
+            .selected_text(text)
+            .show_ui(ui, |ui| {
+                for dataset in self.iter() {
+                    if ui
+                        .selectable_label(*selected == Some(dataset.id), &dataset.label)
+                        .clicked()
+                    {
+                        *selected = Some(dataset.id);
+                        changed = true;
+                        info!("{label} set to {}", dataset.label);
+                    }
+                }
+            });
+        changed
+    }
+}
@@ -0,0 +1,260 @@
+//! The `changes` module records attribute edits and additions to address records.  Each [`Change`]
+//! holds the old and new value of a single field, with the user and time of the edit.  A
+//! [`ChangeSet`] can be exported for review or applied to the original records to produce a new
+//! snapshot.
+use crate::address::{AddressColumns, AddressPoint, AddressPoints};
+use crate::lifecycle::allowed;
+use crate::table::{Cell, CellEdit, Columnar, Filtration, Tabular};
+use crate::utils::user_name;
+use address::prelude::{Address, AddressStatus, SpatialAddresses};
+use aid::prelude::{Bandage, Clean};
+use chrono::{DateTime, Local, Utc};
+use derive_more::{Deref, DerefMut};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// The `Change` struct records an edit to one field of an address record, or the addition of a new
+/// record.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Change {
+    pub id: uuid::Uuid,
+    /// The `record` field holds the id of the edited [`AddressPoint`](crate::address::AddressPoint).
+    pub record: uuid::Uuid,
+    /// The `address` field holds the label of the record before the edit, for display.
+    pub address: String,
+    pub field: AddressColumns,
+    pub old: String,
+    pub new: String,
+    pub user: String,
+    pub time: DateTime<Utc>,
+    /// The `added` field holds the new record when the change adds an address.
+    pub added: Option<AddressPoint>,
+}
+
+impl Change {
+    /// Describes the change in a sentence, for the audit log.
+    pub fn summary(&self) -> String {
+        match &self.added {
+            Some(point) => format!(
+                "Added {} with status {}.",
+                self.address,
+                point.address.status()
+            ),
+            None => format!(
+                "{}: {} changed from {} to {}.",
+                self.address, self.field, self.old, self.new
+            ),
+        }
+    }
+
+    /// Applies the edit to the matching record in `points`.
+    pub fn apply(&self, points: &mut AddressPoints) -> Clean<()> {
+        if let Some(point) = &self.added {
+            points.push(point.clone());
+            return Ok(());
+        }
+        match points.get_mut(&self.record) {
+            Some(point) => point.set_column(&self.field, &self.new),
+            None => Err(Bandage::Hint(format!(
+                "Record for {} not found.",
+                self.address
+            ))),
+        }
+    }
+}
+
+#[derive(EnumIter, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ChangeColumns {
+    Time,
+    User,
+    Address,
+    Field,
+    Old,
+    New,
+}
+
+impl ChangeColumns {
+    pub fn value(&self, change: &Change) -> String {
+        match self {
+            Self::Time => change
+                .time
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+            Self::User => change.user.clone(),
+            Self::Address => change.address.clone(),
+            Self::Field => match change.added {
+                Some(_) => "New address".to_string(),
+                None => change.field.to_string(),
+            },
+            Self::Old => change.old.clone(),
+            Self::New => change.new.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for ChangeColumns {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Time => write!(f, "Time"),
+            Self::User => write!(f, "User"),
+            Self::Address => write!(f, "Address"),
+            Self::Field => write!(f, "Field"),
+            Self::Old => write!(f, "Old Value"),
+            Self::New => write!(f, "New Value"),
+        }
+    }
+}
+
+impl Columnar for Change {
+    fn cells(&self) -> Vec<Cell> {
+        ChangeColumns::iter()
+            .map(|v| Cell::Text(v.value(self)))
+            .collect::<Vec<Cell>>()
+    }
+
+    fn id(&self) -> uuid::Uuid {
+        self.id
+    }
+}
+
+/// The `ChangeSet` struct holds edits to a set of address records, in the order made.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize, Deref, DerefMut)]
+pub struct ChangeSet(Vec<Change>);
+
+impl ChangeSet {
+    /// Validates `edit` and applies it to the matching record in `points`, recording the change.
+    /// Returns `Ok(false)` if the value is unchanged.  Columns that are not editable, such as the
+    /// status, are refused.
+    pub fn edit(&mut self, points: &mut AddressPoints, edit: &CellEdit) -> Clean<bool> {
+        let field = AddressColumns::try_from(edit.column)?;
+        if !field.editable() {
+            return Err(Bandage::Hint(format!("{field} cannot be edited directly.")));
+        }
+        self.set(points, &edit.row, field, &edit.value)
+    }
+
+    /// Moves the record with id `record` in `points` to status `to`, recording the change.
+    /// Returns `Ok(false)` if the record already has status `to`, and an error if the lifecycle
+    /// does not allow the move.
+    pub fn status(
+        &mut self,
+        points: &mut AddressPoints,
+        record: &uuid::Uuid,
+        to: &AddressStatus,
+    ) -> Clean<bool> {
+        let point = points
+            .get_mut(record)
+            .ok_or(Bandage::Hint("Edited record not found.".to_string()))?;
+        let from = point.address.status();
+        if !allowed(from, to) {
+            let address = point.address.label();
+            return Err(Bandage::Hint(format!(
+                "{address} cannot move from {from} to {to}."
+            )));
+        }
+        self.set(points, record, AddressColumns::Status, &to.to_string())
+    }
+
+    /// Sets `field` of the record with id `record` to `value`, recording the change.
+    fn set(
+        &mut self,
+        points: &mut AddressPoints,
+        record: &uuid::Uuid,
+        field: AddressColumns,
+        value: &str,
+    ) -> Clean<bool> {
+        let point = points
+            .get_mut(record)
+            .ok_or(Bandage::Hint("Edited record not found.".to_string()))?;
+        let address = point.address.label();
+        let old = point.column::<String>(&field);
+        point.set_column(&field, value)?;
+        // Read the value back, so the change holds the normalized form.
+        let new = point.column::<String>(&field);
+        if old == new {
+            return Ok(false);
+        }
+        tracing::info!("{}: {} changed from {} to {}.", address, field, old, new);
+        self.push(Change {
+            id: uuid::Uuid::new_v4(),
+            record: *record,
+            address,
+            field,
+            old,
+            new,
+            user: user_name(),
+            time: Utc::now(),
+            added: None,
+        });
+        Ok(true)
+    }
+
+    /// Adds `point` to `points`, recording the addition.
+    pub fn add(&mut self, points: &mut AddressPoints, point: AddressPoint) {
+        let address = point.address.label();
+        tracing::info!("{} added with status {}.", address, point.address.status());
+        self.push(Change {
+            id: uuid::Uuid::new_v4(),
+            record: point.id,
+            address: address.clone(),
+            field: AddressColumns::Label,
+            old: "".to_string(),
+            new: address,
+            user: user_name(),
+            time: Utc::now(),
+            added: Some(point.clone()),
+        });
+        points.push(point);
+    }
+
+    /// The records added by the change set.
+    pub fn added(&self) -> AddressPoints {
+        let mut points = AddressPoints::default();
+        for point in self.iter().filter_map(|v| v.added.clone()) {
+            points.push(point);
+        }
+        points
+    }
+
+    /// Applies the changes in order to a copy of `points`, returning the edited addresses.
+    pub fn apply(&self, points: &AddressPoints) -> Clean<SpatialAddresses> {
+        let mut points = points.clone();
+        for change in self.iter() {
+            change.apply(&mut points)?;
+        }
+        Ok(points.addresses())
+    }
+
+    pub fn to_csv<P: AsRef<Path>>(&self, path: P) -> Clean<()> {
+        let mut wtr = csv::Writer::from_path(path).map_err(|e| Bandage::Hint(e.to_string()))?;
+        wtr.write_record(Self::headers())
+            .map_err(|e| Bandage::Hint(e.to_string()))?;
+        for change in self.iter() {
+            wtr.write_record(change.values())
+                .map_err(|e| Bandage::Hint(e.to_string()))?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+impl Tabular<Change> for ChangeSet {
+    fn headers() -> Vec<String> {
+        ChangeColumns::iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>()
+    }
+
+    fn rows(&self) -> Vec<Change> {
+        self.to_vec()
+    }
+
+    fn set_rows(&mut self, rows: Vec<Change>) {
+        self.0 = rows;
+    }
+}
+
+impl Filtration<ChangeSet, String> for ChangeSet {}
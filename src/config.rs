@@ -0,0 +1,20 @@
+//! The `config` module reads the application config file.  Each module reads the tables it needs
+//! into its own struct, and ignores the rest.
+use aid::prelude::{Bandage, Clean};
+use serde::de::DeserializeOwned;
+
+/// Location of the config file, relative to the working directory.
+pub const CONFIG_PATH: &str = "config.toml";
+
+/// Reads `config.toml` from the working directory if present, falling back to the config bundled
+/// with the application, and parses it as `T`.
+pub fn read<T: DeserializeOwned>() -> Clean<T> {
+    let config = match std::fs::read_to_string(CONFIG_PATH) {
+        Ok(value) => value,
+        Err(_) => {
+            let bundled = include_bytes!("../config.toml");
+            String::from_utf8_lossy(bundled).to_string()
+        }
+    };
+    toml::from_str(&config).map_err(|e| Bandage::Hint(e.to_string()))
+}
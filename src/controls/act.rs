@@ -218,6 +218,9 @@ pub enum EguiAct {
     NextRow,
     PreviousRow,
     InspectTree,
+    Undo,
+    Redo,
+    History,
     #[default]
     Be,
 }
@@ -240,7 +243,10 @@ impl EguiAct {
             Self::NextRow => 8,
             Self::PreviousRow => 9,
             Self::InspectTree => 10,
-            Self::Be => 11,
+            Self::Undo => 11,
+            Self::Redo => 12,
+            Self::History => 13,
+            Self::Be => 14,
         }
     }
 }
@@ -273,6 +279,9 @@ impl std::string::ToString for EguiAct {
             Self::NextRow => "Next Row",
             Self::PreviousRow => "Previous Row",
             Self::InspectTree => "Inspect Tree",
+            Self::Undo => "Undo",
+            Self::Redo => "Redo",
+            Self::History => "History",
             Self::Be => "Be",
         };
         str.to_string()
@@ -294,6 +303,9 @@ impl std::str::FromStr for EguiAct {
             "next_row" => Ok(Self::NextRow),
             "previous_row" => Ok(Self::PreviousRow),
             "inspect_tree" => Ok(Self::InspectTree),
+            "undo" => Ok(Self::Undo),
+            "redo" => Ok(Self::Redo),
+            "history" => Ok(Self::History),
             "be" => Ok(Self::Be),
             _ => Err(aid::prelude::Bandage::Hint("Undefined act.".to_string())),
         }
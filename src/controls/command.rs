@@ -437,10 +437,10 @@ impl Ord for CommandGroup {
 }
 
 impl table::Columnar for CommandGroup {
-    fn values(&self) -> Vec<String> {
+    fn cells(&self) -> Vec<table::Cell> {
         let command = self.binding.to_string();
         let act = self.name.clone();
-        vec![command, act]
+        vec![table::Cell::Text(command), table::Cell::Text(act)]
     }
 
     fn id(&self) -> Uuid {
@@ -708,8 +708,11 @@ impl CommandRow {
 }
 
 impl table::Columnar for CommandRow {
-    fn values(&self) -> Vec<String> {
-        vec![self.command.clone(), self.act.clone()]
+    fn cells(&self) -> Vec<table::Cell> {
+        vec![
+            table::Cell::Text(self.command.clone()),
+            table::Cell::Text(self.act.clone()),
+        ]
     }
 
     fn id(&self) -> Uuid {
@@ -750,26 +753,8 @@ impl table::Tabular<CommandRow> for CommandTable {
         self.0.clone()
     }
 
-    fn sort_by_col(&mut self, column_index: usize, reverse: bool) {
-        match column_index {
-            0 => {
-                if reverse {
-                    self.0.sort_by(|a, b| b.command.cmp(&a.command));
-                } else {
-                    self.0.sort_by(|a, b| a.command.cmp(&b.command));
-                }
-            }
-            1 => {
-                if reverse {
-                    self.0.sort_by(|a, b| b.act.cmp(&a.act));
-                } else {
-                    self.0.sort_by(|a, b| a.act.cmp(&b.act));
-                }
-            }
-            _ => {
-                tracing::info!("Column index not recognized.");
-            }
-        }
+    fn set_rows(&mut self, rows: Vec<CommandRow>) {
+        self.0 = rows;
     }
 }
 
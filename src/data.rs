@@ -1,10 +1,10 @@
+use crate::catalog::{Catalog, Dataset};
+use crate::master::StreetMaster;
+use crate::matching::{CompareRecord, CompareRecords};
 use crate::prelude::{
-    toggle_select, Columnar, Compare, Filtration, Parcels, TableConfig, TableView, Tabular,
-};
-use address::prelude::{
-    Addresses, GrantsPassSpatialAddresses, JosephineCountySpatialAddresses2024, MatchRecord,
-    MatchRecords, Portable, SpatialAddresses,
+    toggle_select, Cell, Columnar, Compare, Filtration, Parcels, TableConfig, TableView, Tabular,
 };
+use address::prelude::{MatchRecord, MatchRecords};
 use aid::prelude::Clean;
 use egui::Ui;
 use rfd::FileDialog;
@@ -18,9 +18,9 @@ use tracing::info;
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Data {
-    pub addresses: Vec<SpatialAddresses>,
-    pub address_sources: Vec<AddressSource>,
-    pub compare: Option<TableView<MatchRecords, MatchRecord, String>>,
+    /// The `catalog` field holds the loaded address datasets.
+    pub catalog: Catalog,
+    pub compare: Option<TableView<CompareRecords, CompareRecord, String>>,
     pub parcels: Option<Arc<Parcels>>,
     pub selection: HashSet<usize>,
     pub target: AddressSource,
@@ -33,41 +33,29 @@ impl Data {
             .set_directory("/")
             .pick_file();
 
-        let mut records = SpatialAddresses::default();
         if let Some(path) = files {
-            if let Ok(values) = GrantsPassSpatialAddresses::from_csv(path.clone()) {
-                if values.len() > records.len() {
-                    self.address_sources.push(AddressSource::grants_pass());
-                    records = SpatialAddresses::from(&values[..]);
-                    // records.save("data/addresses.data").unwrap();
-                }
-            }
-            if let Ok(mut values) = JosephineCountySpatialAddresses2024::from_csv(path.clone()) {
-                if values.len() > records.len() {
-                    self.address_sources.push(AddressSource::josephine_county());
-                    values.standardize();
-                    records = SpatialAddresses::from(&values[..]);
-                    // records.save("data/county_addresses.data").unwrap();
+            match Dataset::read(&path) {
+                Ok(dataset) => {
+                    dataset.audit();
+                    self.catalog.insert(dataset);
                 }
-            }
-            if records.len() > 0 {
-                info!("Records found: {}", records.len());
-                self.addresses.push(records);
-            } else {
-                info!("No records found.");
+                Err(e) => info!("{}", e.to_string()),
             }
         }
     }
 
     pub fn sample_data(&mut self) -> Clean<()> {
-        self.address_sources = vec![
-            AddressSource::grants_pass(),
+        let mut catalog = Catalog::default();
+        let city = Dataset::load(AddressSource::grants_pass(), "data/addresses.data")?;
+        let county = Dataset::load(
             AddressSource::josephine_county(),
-        ];
-        let mut records = Vec::new();
-        records.push(SpatialAddresses::load("data/addresses.data")?);
-        records.push(SpatialAddresses::load("data/county_addresses.data")?);
-        self.addresses = records;
+            "data/county_addresses.data",
+        )?;
+        for dataset in [city, county] {
+            dataset.audit();
+            catalog.insert(dataset);
+        }
+        self.catalog = catalog;
         Ok(())
     }
 
@@ -85,18 +73,27 @@ impl Data {
         toggle_select(&mut self.selection, row, response);
     }
 
-    pub fn compare(&mut self, data: &Compare) -> TableView<MatchRecords, MatchRecord, String> {
-        let subject = &self.addresses[data.subject_idx][..];
-        let target = &self.addresses[data.target_idx][..];
+    /// Compares the subject and target datasets selected in `data`.  Returns `None` if either
+    /// dataset is not in the catalog.
+    pub fn compare(
+        &mut self,
+        data: &Compare,
+        master: &StreetMaster,
+    ) -> Option<TableView<CompareRecords, CompareRecord, String>> {
+        let subject = &self.catalog.get(&data.subject?)?.addresses[..];
+        let target = &self.catalog.get(&data.target?)?.addresses[..];
         let config = TableConfig::new().with_search().with_slider();
-        let table = TableView::with_config(MatchRecords::compare(subject, target), config);
+        // Fuzzy matching only runs when enabled in the compare widget.
+        let fuzzy = data.fuzzy.then_some(&data.fuzzy_config);
+        let records = CompareRecords::compare(subject, target, fuzzy, &master.lookup());
+        let table = TableView::with_config(records, config);
         self.compare = Some(table.clone());
-        table
+        Some(table)
     }
 
     pub fn filter(&mut self, filter: &str) {
         if let Some(table) = &mut self.compare {
-            table.data = table.data.clone().filter(filter);
+            table.data = table.data.clone().filter(&filter.to_string());
         }
     }
 }
@@ -107,7 +104,16 @@ impl Tabular<AddressSource> for Data {
     }
 
     fn rows(&self) -> Vec<AddressSource> {
-        self.address_sources.clone()
+        self.catalog
+            .iter()
+            .map(|v| v.source.clone())
+            .collect::<Vec<AddressSource>>()
+    }
+
+    /// Reorders the datasets of the catalog to follow `rows`, as after sorting.
+    fn set_rows(&mut self, rows: Vec<AddressSource>) {
+        self.catalog
+            .sort_by_key(|v| rows.iter().position(|source| *source == v.source));
     }
 }
 
@@ -143,8 +149,8 @@ impl fmt::Display for AddressSource {
 }
 
 impl Columnar for AddressSource {
-    fn values(&self) -> Vec<String> {
-        vec![format!("{self}")]
+    fn cells(&self) -> Vec<Cell> {
+        vec![Cell::Text(format!("{self}"))]
     }
 
     fn id(&self) -> uuid::Uuid {
@@ -168,16 +174,16 @@ pub enum MatchColumns {
 }
 
 impl MatchColumns {
-    pub fn value(&self, record: &MatchRecord) -> String {
+    pub fn value(&self, record: &MatchRecord) -> Cell {
         match self {
-            Self::MatchStatus => format!("{:?}", record.match_status),
-            Self::Address => format!("{}", record.address_label),
-            Self::Subaddress => format!("{:?}", record.subaddress_type),
-            Self::Floor => format!("{:?}", record.floor),
-            Self::Building => format!("{:?}", record.building),
-            Self::Status => format!("{:?}", record.status),
-            Self::Latitude => format!("{}", record.latitude),
-            Self::Longitude => format!("{}", record.longitude),
+            Self::MatchStatus => Cell::Text(format!("{:?}", record.match_status)),
+            Self::Address => Cell::Text(format!("{}", record.address_label)),
+            Self::Subaddress => Cell::Text(format!("{:?}", record.subaddress_type)),
+            Self::Floor => Cell::Text(format!("{:?}", record.floor)),
+            Self::Building => Cell::Text(format!("{:?}", record.building)),
+            Self::Status => Cell::Text(format!("{:?}", record.status)),
+            Self::Latitude => Cell::Coordinate(record.latitude),
+            Self::Longitude => Cell::Coordinate(record.longitude),
         }
     }
 }
@@ -204,10 +210,10 @@ impl Columnar for MatchRecord {
     //         .collect::<Vec<String>>()
     // }
 
-    fn values(&self) -> Vec<String> {
+    fn cells(&self) -> Vec<Cell> {
         MatchColumns::iter()
             .map(|v| v.value(self))
-            .collect::<Vec<String>>()
+            .collect::<Vec<Cell>>()
     }
 
     fn id(&self) -> uuid::Uuid {
@@ -225,6 +231,10 @@ impl Tabular<MatchRecord> for MatchRecords {
     fn rows(&self) -> Vec<MatchRecord> {
         self.to_vec()
     }
+
+    fn set_rows(&mut self, rows: Vec<MatchRecord>) {
+        **self = rows;
+    }
 }
 
 impl Filtration<MatchRecords, String> for MatchRecords {
@@ -0,0 +1,217 @@
+//! The `export` module reads vendor range-export profiles from the config toml.  Each profile
+//! names a boundary, a column layout, abbreviation rules and a file format, so that a new vendor
+//! street-range table only needs a new entry in the config file.
+use crate::config;
+use crate::prelude::Boundary;
+use address::prelude::{LexisNexis, LexisNexisItem};
+use aid::prelude::{Bandage, Clean};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use strum_macros::EnumIter;
+use tracing::{info, trace, warn};
+
+/// The `RangeField` enum names the fields of a street range available for export.
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter, Deserialize, Serialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum RangeField {
+    NumberFrom,
+    NumberTo,
+    Directional,
+    StreetName,
+    StreetType,
+    Community,
+    Zip,
+}
+
+impl RangeField {
+    /// Returns the string value of the field from range `item`.
+    pub fn value(&self, item: &LexisNexisItem) -> String {
+        match self {
+            Self::NumberFrom => format!("{}", item.address_number_from),
+            Self::NumberTo => format!("{}", item.address_number_to),
+            Self::Directional => match &item.street_name_pre_directional {
+                Some(value) => value.clone(),
+                None => "".to_string(),
+            },
+            Self::StreetName => item.street_name.clone(),
+            Self::StreetType => item.street_name_post_type.clone(),
+            Self::Community => item.postal_community.clone(),
+            Self::Zip => format!("{}", item.zip_code),
+        }
+    }
+}
+
+/// The `ExportColumn` struct pairs a [`RangeField`] with the header name expected by the vendor.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ExportColumn {
+    pub field: RangeField,
+    pub header: String,
+    /// The `abbreviations` field replaces any value of this column matching a key with the
+    /// associated value.  Keys are not case sensitive.  Scoped to the column, so a rule for
+    /// directionals leaves a street named "E" alone.
+    #[serde(default)]
+    pub abbreviations: BTreeMap<String, String>,
+}
+
+/// The `ExportFormat` enum selects the delimiter of the output file.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Tsv,
+    Pipe,
+}
+
+impl ExportFormat {
+    pub fn delimiter(&self) -> u8 {
+        match self {
+            Self::Csv => b',',
+            Self::Tsv => b'\t',
+            Self::Pipe => b'|',
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Tsv => "tsv",
+            Self::Pipe => "txt",
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Csv => write!(f, "Comma separated"),
+            Self::Tsv => write!(f, "Tab separated"),
+            Self::Pipe => write!(f, "Pipe separated"),
+        }
+    }
+}
+
+/// The `ExportProfile` struct describes the street-range table delivered to a single vendor.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ExportProfile {
+    /// The `name` field is the display name of the profile.
+    pub name: String,
+    /// The `boundary` field is the path to a binary [`Boundary`] file.  Addresses inside the
+    /// boundary are included in the ranges.
+    pub boundary: PathBuf,
+    /// The `file_name` field is the default name of the output file, without extension.
+    pub file_name: String,
+    /// The `format` field selects the delimiter of the output file.
+    #[serde(default)]
+    pub format: ExportFormat,
+    /// The `columns` field lists the fields to export, in order, with their header names.
+    pub columns: Vec<ExportColumn>,
+    /// The `uppercase` field converts all values to upper case when set.
+    #[serde(default)]
+    pub uppercase: bool,
+}
+
+impl ExportProfile {
+    /// Loads the boundary associated with the profile.
+    pub fn boundary(&self) -> Clean<Boundary> {
+        Boundary::load(&self.boundary)
+    }
+
+    /// The default file name of the output, including the extension.
+    pub fn default_file_name(&self) -> String {
+        format!("{}.{}", self.file_name, self.format.extension())
+    }
+
+    /// The header row of the output.
+    pub fn headers(&self) -> Vec<String> {
+        self.columns
+            .iter()
+            .map(|v| v.header.clone())
+            .collect::<Vec<String>>()
+    }
+
+    /// Applies the abbreviation rules of `column` and the case rule of the profile to `value`.
+    pub fn rule(&self, column: &ExportColumn, value: &str) -> String {
+        let key = value.to_uppercase();
+        let mut result = value.to_string();
+        for (from, to) in &column.abbreviations {
+            if from.to_uppercase() == key {
+                result = to.clone();
+                break;
+            }
+        }
+        if self.uppercase {
+            result = result.to_uppercase();
+        }
+        result
+    }
+
+    /// The output row for range `item`.
+    pub fn row(&self, item: &LexisNexisItem) -> Vec<String> {
+        self.columns
+            .iter()
+            .map(|v| self.rule(v, &v.field.value(item)))
+            .collect::<Vec<String>>()
+    }
+
+    /// Writes the street ranges in `ranges` to `path` using the layout of the profile.
+    pub fn write<P: AsRef<Path>>(&self, ranges: &LexisNexis, path: P) -> Clean<()> {
+        let mut wtr = csv::WriterBuilder::new()
+            .delimiter(self.format.delimiter())
+            .from_path(path)
+            .map_err(|e| Bandage::Hint(e.to_string()))?;
+        wtr.write_record(self.headers())
+            .map_err(|e| Bandage::Hint(e.to_string()))?;
+        for item in ranges.iter() {
+            wtr.write_record(self.row(item))
+                .map_err(|e| Bandage::Hint(e.to_string()))?;
+        }
+        wtr.flush()?;
+        info!("{} ranges written for {}.", ranges.len(), self.name);
+        Ok(())
+    }
+}
+
+/// The `ExportSettings` struct reads the `export` table from the config toml.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ExportSettings {
+    /// The `default_profile` field holds the key of the profile selected when the range export
+    /// opens, e.g. "lexis_nexis".
+    #[serde(default)]
+    pub default_profile: Option<String>,
+}
+
+/// The `ProfileConfig` struct reads the `export` and `profiles` tables from the config toml.
+/// Other tables in the config file are ignored.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ProfileConfig {
+    #[serde(default)]
+    pub export: ExportSettings,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, ExportProfile>,
+}
+
+impl ProfileConfig {
+    /// Reads export profiles from `config.toml` in the working directory if present, falling back
+    /// to the config bundled with the application.  The default profile comes first.
+    pub fn with_config() -> Clean<Vec<ExportProfile>> {
+        let mut config: Self = config::read()?;
+        trace!("Profiles read: {}", config.profiles.len());
+        if config.profiles.is_empty() {
+            return Err(Bandage::Hint("No export profiles defined.".to_string()));
+        }
+        let mut profiles = Vec::new();
+        if let Some(key) = &config.export.default_profile {
+            match config.profiles.remove(key) {
+                Some(profile) => profiles.push(profile),
+                None => warn!("Default export profile {} is not defined.", key),
+            }
+        }
+        profiles.extend(config.profiles.into_values());
+        Ok(profiles)
+    }
+}
@@ -0,0 +1,770 @@
+//! The `geocode` module finds address points from free-text addresses.  A [`Geocoder`] indexes the
+//! points of a dataset by canonical street name, and ranks the points matching a parsed [`Query`]
+//! as [`Candidate`] results.  Candidates can be displayed on the map as [`CandidatePoints`].  A
+//! [`ReverseGeocode`] works the other way, describing the addresses, parcel and boundaries at a
+//! location.
+use crate::address::{AddressPoint, AddressPoints};
+use crate::boundaries::Boundary;
+use crate::catalog::Dataset;
+use crate::master::{StreetLookup, StreetMaster};
+use crate::matching::similarity;
+use crate::parcels::Parcels;
+use crate::table::{Cell, Columnar, Filtration, Tabular};
+use crate::utils::{distance, web_mercator};
+use address::prelude::{Address, Parser, SpatialAddress};
+use derive_more::{Deref, DerefMut};
+use galileo::galileo_types::cartesian::{CartesianPoint2d, CartesianPoint3d, Point2d};
+use galileo::galileo_types::geo::impls::GeoPoint2d;
+use galileo::galileo_types::geo::{GeoPoint, NewGeoPoint};
+use galileo::galileo_types::geometry::CartesianGeometry2d;
+use galileo::galileo_types::geometry::Geom;
+use galileo::galileo_types::geometry_type::{GeoSpace2d, GeometryType, PointGeometryType};
+use galileo::galileo_types::impls::{Contour, Polygon};
+use galileo::layer::feature_layer::symbol::Symbol;
+use galileo::layer::feature_layer::Feature;
+use galileo::render::point_paint::PointPaint;
+use galileo::render::render_bundle::RenderPrimitive;
+use galileo::Color;
+use geo::algorithm::contains::Contains;
+use num_traits::AsPrimitive;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// Minimum street score of a candidate, below which the street is not considered a match.
+const STREET_THRESHOLD: f64 = 0.6;
+/// Number of address points used to project a reverse geocoded location.
+const NEIGHBORS: usize = 12;
+/// Largest difference in address number between the neighbors of an interpolated address, e.g.
+/// two blocks of numbering.  Wider gaps skip too much of the street to place a number in them.
+const MAX_NUMBER_SPAN: i64 = 200;
+/// Largest distance in meters between the neighbors of an interpolated address.
+const MAX_GAP: f64 = 500.0;
+
+/// The `Query` struct holds the parts of a free-text address used to find candidates.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Query {
+    pub number: Option<i64>,
+    /// The `street` field holds the street key, normalized through the street master list.
+    pub street: String,
+    pub subaddress: Option<String>,
+}
+
+impl Query {
+    /// Parses `text` with the `address` crate parser, normalizing the street through `streets`.
+    /// Partial input, as typed into the search bar, falls back to splitting off a leading number
+    /// from the street.
+    pub fn parse(text: &str, streets: &StreetLookup) -> Self {
+        let text = text.trim().to_uppercase();
+        if let Ok((_, partial)) = Parser::address(&text) {
+            if let (Some(number), Some(name)) = (partial.address_number(), partial.street_name()) {
+                let mut street = Vec::new();
+                if let Some(value) = partial.street_name_pre_directional() {
+                    street.push(value.to_string());
+                }
+                street.push(name.to_string());
+                if let Some(value) = partial.street_name_post_type() {
+                    street.push(value.abbreviate().to_string());
+                }
+                return Self {
+                    number: Some(number),
+                    street: streets.street(&street.join(" ")),
+                    subaddress: partial.subaddress_identifier().map(|v| v.to_uppercase()),
+                };
+            }
+        }
+        let mut words = text.split_whitespace().peekable();
+        let number = words.peek().and_then(|v| v.parse::<i64>().ok());
+        if number.is_some() {
+            words.next();
+        }
+        let rest = words.collect::<Vec<&str>>().join(" ");
+        let street = if rest.is_empty() {
+            rest
+        } else {
+            streets.street(&rest)
+        };
+        Self {
+            number,
+            street,
+            subaddress: None,
+        }
+    }
+
+    /// Scores the street key `key` against the query street, or returns `None` if it does not
+    /// match.  A prefix of the key scores nearly as well as the full name, so suggestions appear
+    /// while the user is typing.  A query without a street matches every street, so a number
+    /// alone still finds its addresses.
+    fn street_score(&self, key: &str) -> Option<f64> {
+        if self.street.is_empty() {
+            return Some(0.5);
+        }
+        let score = if key == self.street {
+            1.0
+        } else if key.starts_with(&self.street) {
+            0.9
+        } else if key.split_whitespace().any(|v| v.starts_with(&self.street)) {
+            0.8
+        } else {
+            similarity(key, &self.street)
+        };
+        if score >= STREET_THRESHOLD {
+            Some(score)
+        } else {
+            None
+        }
+    }
+
+    /// Scores the address number `number` against the query, or returns `None` if it does not
+    /// match.
+    fn number_score(&self, number: i64) -> Option<f64> {
+        match self.number {
+            None => Some(0.5),
+            Some(value) if value == number => Some(1.0),
+            Some(value) if number.to_string().starts_with(&value.to_string()) => Some(0.6),
+            Some(_) => None,
+        }
+    }
+
+    /// Scores the subaddress of an address against the query.  Without a subaddress in the
+    /// query, the base address ranks above its units.
+    fn subaddress_score(&self, subaddress: Option<String>) -> f64 {
+        match (&self.subaddress, subaddress) {
+            (None, None) => 1.0,
+            (None, Some(_)) => 0.9,
+            (Some(a), Some(b)) if *a == b.to_uppercase() => 1.0,
+            _ => 0.5,
+        }
+    }
+}
+
+/// The `Candidate` struct is an address point found for a query, with its match score from 0 to 1.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Candidate {
+    /// The `id` field holds a [`uuid::Uuid`] for use by the [`crate::table::TableView`].
+    pub id: uuid::Uuid,
+    /// The `label` field holds the label of the address, which links the candidate to the record.
+    pub label: String,
+    /// The `record` field holds the id of the address in its dataset, as assigned by
+    /// [`Dataset::record_id`].
+    pub record: uuid::Uuid,
+    pub score: f64,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Candidate {
+    pub fn new(address: &SpatialAddress, record: uuid::Uuid, score: f64) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4(),
+            label: address.label(),
+            record,
+            score,
+            latitude: address.latitude,
+            longitude: address.longitude,
+        }
+    }
+}
+
+/// The `Geocoder` struct indexes the addresses of a dataset by street key, so a query only scores
+/// the addresses on matching streets.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Geocoder {
+    addresses: Vec<SpatialAddress>,
+    // The record id of each address in the dataset.
+    ids: Vec<uuid::Uuid>,
+    streets: BTreeMap<String, Vec<usize>>,
+    lookup: StreetLookup,
+}
+
+/// The `GeocoderKey` type identifies the input of a [`Geocoder`]: the id and record count of the
+/// dataset, and the revision of the street master list used to normalize street names.
+pub type GeocoderKey = (uuid::Uuid, usize, usize);
+
+impl Geocoder {
+    /// Returns the geocoder in `cache`, indexing `dataset` again if the cache is empty or was
+    /// built for another dataset or revision of the street master list.
+    pub fn cached(
+        cache: &mut Option<(GeocoderKey, Arc<Self>)>,
+        dataset: &Dataset,
+        streets: &StreetMaster,
+        revision: usize,
+    ) -> Arc<Self> {
+        let key = (dataset.id, dataset.records, revision);
+        match cache {
+            Some((indexed, geocoder)) if *indexed == key => geocoder.clone(),
+            _ => {
+                let geocoder = Arc::new(Self::new(dataset, streets.lookup()));
+                *cache = Some((key, geocoder.clone()));
+                geocoder
+            }
+        }
+    }
+
+    /// Indexes the addresses of `dataset`, normalizing street names through `lookup`.
+    pub fn new(dataset: &Dataset, lookup: StreetLookup) -> Self {
+        let mut streets = BTreeMap::new();
+        for (i, address) in dataset.addresses.iter().enumerate() {
+            streets
+                .entry(lookup.address(address))
+                .or_insert(Vec::new())
+                .push(i);
+        }
+        let ids = (0..dataset.addresses.len())
+            .map(|i| dataset.record_id(i))
+            .collect::<Vec<uuid::Uuid>>();
+        Self {
+            addresses: dataset.addresses.to_vec(),
+            ids,
+            streets,
+            lookup,
+        }
+    }
+
+    pub fn addresses(&self) -> &[SpatialAddress] {
+        &self.addresses
+    }
+
+    pub fn lookup(&self) -> &StreetLookup {
+        &self.lookup
+    }
+
+    /// Parses `text` and returns up to `limit` candidates, best first.
+    pub fn search(&self, text: &str, limit: usize) -> Vec<Candidate> {
+        if text.trim().is_empty() {
+            return Vec::new();
+        }
+        let query = Query::parse(text, &self.lookup);
+        self.candidates(&query, limit)
+    }
+
+    /// Returns up to `limit` candidates for `query`, best first.  Streets weigh as much as the
+    /// number, with the subaddress breaking ties.
+    pub fn candidates(&self, query: &Query, limit: usize) -> Vec<Candidate> {
+        let mut candidates = Vec::new();
+        for (key, members) in self.streets.iter() {
+            let Some(street) = query.street_score(key) else {
+                continue;
+            };
+            for i in members {
+                let address = &self.addresses[*i];
+                let Some(number) = query.number_score(address.number()) else {
+                    continue;
+                };
+                let subaddress = query.subaddress_score(address.subaddress_id());
+                let score = 0.45 * street + 0.45 * number + 0.1 * subaddress;
+                candidates.push((score, *i));
+            }
+        }
+        // Ties keep the order of the streets, then of the records.
+        candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+        candidates
+            .iter()
+            .take(limit)
+            .map(|(score, i)| Candidate::new(&self.addresses[*i], self.ids[*i], *score))
+            .collect::<Vec<Candidate>>()
+    }
+
+    /// Estimates the location of the number in `query` from the nearest numbers below and above
+    /// it on the query street, preferring numbers of the same parity.  Only an exact street match
+    /// is used, as a location on the wrong street is worse than none.  The neighbors must share a
+    /// zip code and postal community, since the same street name recurs in different towns, and
+    /// lie within [`MAX_NUMBER_SPAN`] and [`MAX_GAP`] of each other.  Returns `None` if the query
+    /// has no number, or no such neighbors bracket the number.
+    pub fn interpolate(&self, query: &Query) -> Option<Interpolation> {
+        let number = query.number?;
+        let members = self.streets.get(&query.street)?;
+        let mut towns = BTreeMap::new();
+        for i in members {
+            let address = &self.addresses[*i];
+            let key = (address.zip(), address.postal_community.to_uppercase());
+            towns.entry(key).or_insert(Vec::new()).push(address);
+        }
+        let mut best: Option<Interpolation> = None;
+        for addresses in towns.values() {
+            let estimate = Self::bracket(number, addresses, true)
+                .or_else(|| Self::bracket(number, addresses, false));
+            if let Some(estimate) = estimate {
+                if best
+                    .as_ref()
+                    .map(|v| estimate.error < v.error)
+                    .unwrap_or(true)
+                {
+                    best = Some(estimate);
+                }
+            }
+        }
+        best
+    }
+
+    /// Interpolates `number` between its nearest neighbors in `addresses`, keeping only numbers
+    /// of the same parity if `parity` is true.
+    fn bracket(number: i64, addresses: &[&SpatialAddress], parity: bool) -> Option<Interpolation> {
+        let mut low: Option<&SpatialAddress> = None;
+        let mut high: Option<&SpatialAddress> = None;
+        for address in addresses.iter().copied() {
+            let value = address.number();
+            if parity && (value - number) % 2 != 0 {
+                continue;
+            }
+            match (low, high) {
+                (Some(v), _) if value < number && value <= v.number() => {}
+                (_, Some(v)) if value > number && value >= v.number() => {}
+                _ if value < number => low = Some(address),
+                _ if value > number => high = Some(address),
+                _ => {}
+            }
+        }
+        let (low, high) = (low?, high?);
+        let gap = distance(low.latitude, low.longitude, high.latitude, high.longitude);
+        if high.number() - low.number() > MAX_NUMBER_SPAN || gap > MAX_GAP {
+            return None;
+        }
+        Some(Interpolation::between(number, low, high, parity))
+    }
+}
+
+/// The `Interpolation` struct is a location estimated for an address number missing from the
+/// data, between the nearest numbers on either side along the same street.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Interpolation {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// The `error` field holds the estimated error of the location in meters.
+    pub error: f64,
+    /// The `low` field holds the label of the address below the number.
+    pub low: String,
+    /// The `high` field holds the label of the address above the number.
+    pub high: String,
+    /// The `parity` field is true if the neighbors are on the same side of the street as the
+    /// number.
+    pub parity: bool,
+}
+
+impl Interpolation {
+    /// Places `number` between the addresses `low` and `high` in proportion to its place between
+    /// their numbers.  Lots are rarely even, so the error grows toward the middle of the gap, and
+    /// doubles if the neighbors are across the street.
+    fn between(number: i64, low: &SpatialAddress, high: &SpatialAddress, parity: bool) -> Self {
+        let span = (high.number() - low.number()) as f64;
+        let t = (number - low.number()) as f64 / span;
+        let latitude = low.latitude + t * (high.latitude - low.latitude);
+        let longitude = low.longitude + t * (high.longitude - low.longitude);
+        let gap = distance(low.latitude, low.longitude, high.latitude, high.longitude);
+        let mut error = t.min(1.0 - t) * gap;
+        if !parity {
+            error *= 2.0;
+        }
+        Self {
+            latitude,
+            longitude,
+            error,
+            low: low.label(),
+            high: high.label(),
+            parity,
+        }
+    }
+}
+
+/// The `CandidatePoint` struct is a map feature marking a geocoded location.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CandidatePoint {
+    geo_point: GeoPoint2d,
+}
+
+impl CandidatePoint {
+    pub fn new(latitude: f64, longitude: f64) -> Self {
+        Self {
+            geo_point: GeoPoint2d::latlon(latitude, longitude),
+        }
+    }
+}
+
+impl From<&Candidate> for CandidatePoint {
+    fn from(candidate: &Candidate) -> Self {
+        Self::new(candidate.latitude, candidate.longitude)
+    }
+}
+
+impl GeoPoint for CandidatePoint {
+    type Num = f64;
+
+    fn lat(&self) -> Self::Num {
+        self.geo_point.lat()
+    }
+
+    fn lon(&self) -> Self::Num {
+        self.geo_point.lon()
+    }
+}
+
+impl GeometryType for CandidatePoint {
+    type Type = PointGeometryType;
+    type Space = GeoSpace2d;
+}
+
+impl Feature for CandidatePoint {
+    type Geom = GeoPoint2d;
+
+    fn geometry(&self) -> &Self::Geom {
+        &self.geo_point
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CandidatePoints {
+    pub records: Vec<CandidatePoint>,
+}
+
+impl From<&Vec<Candidate>> for CandidatePoints {
+    fn from(candidates: &Vec<Candidate>) -> Self {
+        let records = candidates
+            .iter()
+            .map(CandidatePoint::from)
+            .collect::<Vec<CandidatePoint>>();
+        Self { records }
+    }
+}
+
+impl From<&ReverseGeocode> for CandidatePoints {
+    /// Marks the reverse geocoded location and the nearest address.
+    fn from(location: &ReverseGeocode) -> Self {
+        let mut records = vec![CandidatePoint::new(location.latitude, location.longitude)];
+        if let Some(nearest) = location.nearby.first() {
+            records.push(CandidatePoint::new(nearest.latitude, nearest.longitude));
+        }
+        Self { records }
+    }
+}
+
+/// The `CandidateSymbol` struct draws a geocoded location as a ringed point, so it stands out from
+/// the address layers beneath it.
+pub struct CandidateSymbol {}
+
+impl Symbol<CandidatePoint> for CandidateSymbol {
+    fn render<'a, N, P>(
+        &self,
+        _feature: &CandidatePoint,
+        geometry: &'a Geom<P>,
+        _min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N> + Clone,
+    {
+        let mut primitives = Vec::new();
+        let Geom::Point(point) = geometry else {
+            return primitives;
+        };
+        primitives.push(RenderPrimitive::new_point_ref(
+            point,
+            PointPaint::circle(Color::from_hex("#ffffff"), 16.0),
+        ));
+        primitives.push(RenderPrimitive::new_point_ref(
+            point,
+            PointPaint::circle(Color::from_hex("#c4007a"), 11.0),
+        ));
+        primitives
+    }
+}
+
+/// The `NearbyAddress` struct is an address point near a reverse geocoded location.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct NearbyAddress {
+    /// The `id` field holds a [`uuid::Uuid`] for use by the [`crate::table::TableView`].
+    pub id: uuid::Uuid,
+    pub label: String,
+    /// The `distance` field holds the distance in meters from the location.
+    pub distance: f64,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl NearbyAddress {
+    pub fn new(point: &AddressPoint, distance: f64) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4(),
+            label: point.address.label(),
+            distance,
+            latitude: point.address.latitude,
+            longitude: point.address.longitude,
+        }
+    }
+}
+
+#[derive(EnumIter, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NearbyColumns {
+    Address,
+    Distance,
+}
+
+impl NearbyColumns {
+    pub fn value(&self, nearby: &NearbyAddress) -> Cell {
+        match self {
+            Self::Address => Cell::Text(nearby.label.clone()),
+            Self::Distance => Cell::Float(nearby.distance, 1),
+        }
+    }
+}
+
+impl fmt::Display for NearbyColumns {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Address => write!(f, "Address"),
+            Self::Distance => write!(f, "Distance (m)"),
+        }
+    }
+}
+
+impl Columnar for NearbyAddress {
+    fn cells(&self) -> Vec<Cell> {
+        NearbyColumns::iter()
+            .map(|v| v.value(self))
+            .collect::<Vec<Cell>>()
+    }
+
+    fn id(&self) -> uuid::Uuid {
+        self.id
+    }
+}
+
+/// The `NearbyAddresses` struct is a wrapper around a vector of type [`NearbyAddress`], for
+/// display in a [`crate::table::TableView`].
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize, Deref, DerefMut)]
+pub struct NearbyAddresses(Vec<NearbyAddress>);
+
+impl Tabular<NearbyAddress> for NearbyAddresses {
+    fn headers() -> Vec<String> {
+        NearbyColumns::iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>()
+    }
+
+    fn rows(&self) -> Vec<NearbyAddress> {
+        self.to_vec()
+    }
+
+    fn set_rows(&mut self, rows: Vec<NearbyAddress>) {
+        self.0 = rows;
+    }
+}
+
+impl Filtration<NearbyAddresses, String> for NearbyAddresses {
+    /// Filters the addresses to labels containing `filter`.
+    fn filter(&mut self, filter: &String) -> Self {
+        let mut nearby = self.to_vec();
+        nearby.retain(|v| v.label.contains(&filter.to_uppercase()));
+        Self(nearby)
+    }
+}
+
+/// The `ReverseGeocode` struct describes a location: the nearest address points, the parcel
+/// containing the location and the boundaries it falls within.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ReverseGeocode {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// The `nearby` field lists the nearest address points, nearest first.
+    pub nearby: NearbyAddresses,
+    /// The `parcel` field holds the map number and owner of the containing parcel, if any.
+    pub parcel: Option<String>,
+    /// The `boundaries` field lists the names of the boundaries containing the location.
+    pub boundaries: Vec<String>,
+    /// The `estimated` field is false when too few address points surround the location to
+    /// project it, in which case the parcel and boundaries are those of the nearest address.
+    pub estimated: bool,
+}
+
+impl ReverseGeocode {
+    /// Finds the `count` address points in `points` nearest to `latitude` and `longitude`, and the
+    /// parcel in `parcels` and members of `boundaries` containing the location.
+    pub fn locate(
+        points: &AddressPoints,
+        latitude: f64,
+        longitude: f64,
+        count: usize,
+        parcels: Option<&Parcels>,
+        boundaries: &[Boundary],
+    ) -> Self {
+        let nearest = points.nearest(latitude, longitude, count.max(NEIGHBORS));
+        let nearby = nearest
+            .iter()
+            .take(count)
+            .map(|(point, distance)| NearbyAddress::new(point, *distance))
+            .collect::<Vec<NearbyAddress>>();
+        let neighbors = nearest
+            .iter()
+            .map(|(v, _)| *v)
+            .collect::<Vec<&AddressPoint>>();
+        let projected = project(&neighbors, latitude, longitude);
+        let estimated = projected.is_some();
+        let location = match (projected, neighbors.first()) {
+            (Some((x, y)), _) => Some(Point2d::new(x, y)),
+            (None, Some(point)) => Some(point.point),
+            (None, None) => None,
+        };
+        let mut parcel = None;
+        let mut within = Vec::new();
+        if let Some(location) = location {
+            if let Some(parcels) = parcels {
+                parcel = parcels
+                    .records
+                    .iter()
+                    .find(|v| v.is_point_inside(&location, 0.0))
+                    .map(|v| match &v.owner.name {
+                        Some(name) => format!("{} ({})", v.owner.id, name),
+                        None => v.owner.id.clone(),
+                    });
+            }
+            let point = geo::geometry::Point::new(location.x(), location.y());
+            for boundary in boundaries {
+                if boundary.geometry.contains(&point) && !within.contains(&boundary.name) {
+                    within.push(boundary.name.clone());
+                }
+            }
+        }
+        Self {
+            latitude,
+            longitude,
+            nearby: NearbyAddresses(nearby),
+            parcel,
+            boundaries: within,
+            estimated,
+        }
+    }
+}
+
+/// Estimates the projected coordinates of a location from the `neighbors` around it, by fitting an
+/// affine transform from web mercator to the projection of the address data.  Over the short
+/// distances between neighbors the two projections differ by very nearly an affine transform.
+/// Returns `None` if the neighbors are too few or lie along a line.
+fn project(neighbors: &[&AddressPoint], latitude: f64, longitude: f64) -> Option<(f64, f64)> {
+    if neighbors.len() < 3 {
+        return None;
+    }
+    // Center the inputs on the location to keep the normal equations well conditioned.
+    let (ox, oy) = web_mercator(latitude, longitude);
+    let mut m = [[0.0; 3]; 3];
+    let mut bx = [0.0; 3];
+    let mut by = [0.0; 3];
+    for point in neighbors {
+        let (u, v) = web_mercator(point.address.latitude, point.address.longitude);
+        let row = [u - ox, v - oy, 1.0];
+        for (i, a) in row.iter().enumerate() {
+            for (j, b) in row.iter().enumerate() {
+                m[i][j] += a * b;
+            }
+            bx[i] += a * point.point.x();
+            by[i] += a * point.point.y();
+        }
+    }
+    let det = determinant(&m);
+    // Scale the tolerance by the spread of the points, so the test does not depend on units.
+    if det.abs() <= 1e-9 * m[0][0] * m[1][1] * m[2][2] {
+        return None;
+    }
+    // At the location the centered inputs are zero, so only the constant term is needed.
+    let solve = |b: &[f64; 3]| {
+        let mut c = m;
+        for (row, value) in c.iter_mut().zip(b) {
+            row[2] = *value;
+        }
+        determinant(&c) / det
+    };
+    Some((solve(&bx), solve(&by)))
+}
+
+fn determinant(m: &[[f64; 3]; 3]) -> f64 {
+    m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+        - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+        + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn number_alone_matches_every_street() {
+        let query = Query::parse("100", &StreetLookup::default());
+        assert_eq!(query.number, Some(100));
+        assert_eq!(query.street_score("MAIN"), Some(0.5));
+        assert_eq!(query.street_score("N 21ST ST"), Some(0.5));
+        // A typed street still filters.
+        let query = Query::parse("100 MAIN", &StreetLookup::default());
+        assert_eq!(query.street_score("MAIN"), Some(1.0));
+        assert_eq!(query.street_score("OAK"), None);
+    }
+
+    fn interpolate(geocoder: &Geocoder, text: &str) -> Option<Interpolation> {
+        geocoder.interpolate(&Query::parse(text, geocoder.lookup()))
+    }
+
+    #[test]
+    fn interpolate_needs_the_exact_street() {
+        let records = [
+            AddressPoint::sample_at(100, "MAIN", 97526, 0.0, 0.0),
+            AddressPoint::sample_at(200, "MAIN", 97526, 1.0, 0.0),
+        ];
+        let geocoder = Geocoder::new(&Dataset::sample(&records), StreetLookup::default());
+        let estimate = interpolate(&geocoder, "150 MAIN").unwrap();
+        assert_eq!(
+            (estimate.low, estimate.high),
+            (records[0].label(), records[1].label())
+        );
+        assert!(interpolate(&geocoder, "150 MAI").is_none());
+        assert!(interpolate(&geocoder, "150").is_none());
+    }
+
+    #[test]
+    fn interpolate_stays_in_one_town() {
+        let records = [
+            AddressPoint::sample_at(100, "MAIN", 97526, 0.0, 0.0),
+            AddressPoint::sample_at(200, "MAIN", 97527, 1.0, 0.0),
+        ];
+        let geocoder = Geocoder::new(&Dataset::sample(&records), StreetLookup::default());
+        assert!(interpolate(&geocoder, "150 MAIN").is_none());
+    }
+
+    #[test]
+    fn interpolate_caps_the_span() {
+        // Too many numbers apart.
+        let records = [
+            AddressPoint::sample_at(100, "MAIN", 97526, 0.0, 0.0),
+            AddressPoint::sample_at(900, "MAIN", 97526, 1.0, 0.0),
+        ];
+        let geocoder = Geocoder::new(&Dataset::sample(&records), StreetLookup::default());
+        assert!(interpolate(&geocoder, "150 MAIN").is_none());
+        // Too far apart, about 1.1 km.
+        let records = [
+            AddressPoint::sample_at(100, "MAIN", 97526, 0.0, 0.0),
+            AddressPoint::sample_at(200, "MAIN", 97526, 10.0, 0.0),
+        ];
+        let geocoder = Geocoder::new(&Dataset::sample(&records), StreetLookup::default());
+        assert!(interpolate(&geocoder, "150 MAIN").is_none());
+    }
+
+    #[test]
+    fn between_places_number_by_its_share_of_the_gap() {
+        let low = AddressPoint::sample(100, "MAIN", 97526, 42.44, -123.33);
+        let high = AddressPoint::sample(200, "MAIN", 97526, 42.45, -123.33);
+        let gap = distance(low.latitude, low.longitude, high.latitude, high.longitude);
+
+        let middle = Interpolation::between(150, &low, &high, true);
+        assert!((middle.latitude - 42.445).abs() < 1e-9);
+        assert!((middle.longitude + 123.33).abs() < 1e-9);
+        assert!((middle.error - gap / 2.0).abs() < 1e-6);
+
+        let quarter = Interpolation::between(125, &low, &high, true);
+        assert!((quarter.latitude - 42.4425).abs() < 1e-9);
+        assert!((quarter.error - gap / 4.0).abs() < 1e-6);
+
+        // Neighbors across the street double the error.
+        let across = Interpolation::between(125, &low, &high, false);
+        assert!((across.error - gap / 2.0).abs() < 1e-6);
+        assert_eq!((across.low, across.high), (low.label(), high.label()));
+    }
+}
@@ -1,29 +1,87 @@
 pub mod address;
 pub mod app;
+pub mod audit;
+pub mod batch;
 pub mod boundaries;
+pub mod catalog;
+pub mod changes;
+pub mod config;
 pub mod controls;
 pub mod convert;
 pub mod data;
+pub mod export;
+pub mod geocode;
+pub mod lifecycle;
+pub mod master;
+pub mod matching;
 pub mod ops;
 pub mod parcels;
+pub mod presence;
+pub mod project;
+pub mod qa;
+pub mod query;
+pub mod rules;
+pub mod runs;
+pub mod spatial;
 pub mod state;
+pub mod streets;
 pub mod tab;
 pub mod table;
+pub mod undo;
 pub mod utils;
 
 pub mod prelude {
     pub use crate::address::{
         AddressPoint, AddressPoints, AddressSymbol, MatchPoint, MatchPoints, MatchSymbol,
     };
+    pub use crate::audit::{AuditEntry, AuditKind, AuditLog};
+    pub use crate::batch::{
+        BatchColumns, BatchInput, BatchRecord, BatchRecords, MatchKind, FUZZY_THRESHOLD,
+    };
     pub use crate::boundaries::{
         Boundary, BoundarySymbol, BoundaryView, CityLimits, PublicSafetyAgreement,
     };
+    pub use crate::catalog::{Catalog, Dataset};
+    pub use crate::changes::{Change, ChangeSet};
     pub use crate::controls::{Action, Binding, KEY_BINDINGS, MOUSE_BINDINGS};
     pub use crate::convert::Convert;
     pub use crate::data::{AddressSource, Data};
-    pub use crate::ops::{Compare, Operations};
+    pub use crate::export::{
+        ExportFormat, ExportProfile, ExportSettings, ProfileConfig, RangeField,
+    };
+    pub use crate::geocode::{
+        Candidate, CandidatePoint, CandidatePoints, CandidateSymbol, Geocoder, Interpolation,
+        NearbyAddress, NearbyAddresses, NearbyColumns, Query, ReverseGeocode,
+    };
+    pub use crate::lifecycle::{Dates, Lifecycle, Queue, QueueItem, Transition};
+    pub use crate::master::{StreetLookup, StreetMaster, StreetName};
+    pub use crate::matching::{
+        CompareRecord, CompareRecords, CompareStatus, DivergenceReason, FieldDiff, FuzzyConfig,
+        PairIndex,
+    };
+    pub use crate::ops::{
+        Audit, Batch, Compare, Draft, Editor, Geocode, Master, Matrix, Operations, Reverse,
+        Spatial, Streets, Validation,
+    };
     pub use crate::parcels::{Parcel, Parcels};
-    pub use crate::state::{EguiState, GalileoState, State, WgpuFrame};
-    pub use crate::table::{Columnar, Filtration, TableConfig, TableView, Tabular};
-    pub use crate::utils::{from_csv, point_bounds, toggle_select};
+    pub use crate::presence::{Presence, PresenceFilter, PresenceMatrix, PresenceSummary};
+    pub use crate::project::{Autosave, Project, RecentProjects};
+    pub use crate::qa::{Issue, IssueKind, IssuePoints, IssueSymbol, Issues};
+    pub use crate::query::TableQuery;
+    pub use crate::rules::{
+        Check, Rule, RuleIssue, RuleIssues, RulePoints, RuleSymbol, Rules, Severity,
+    };
+    pub use crate::runs::{Run, RunHistory, RunSummary, RunTrend};
+    pub use crate::spatial::{SpatialCheck, SpatialConfig};
+    pub use crate::state::{EguiState, GalileoState, MapLayer, State, WgpuFrame};
+    pub use crate::streets::{
+        CenterlineFields, CenterlineSymbol, Centerlines, Segment, SegmentViews, StreetCheck,
+    };
+    pub use crate::table::{
+        Cell, CellEdit, Columnar, Filtration, SortKey, Stats, TableConfig, TableView, Tabular,
+    };
+    pub use crate::undo::{History, Revision, Snapshot};
+    pub use crate::utils::{
+        distance, from_csv, point_bounds, toggle_select, user_name, web_mercator,
+    };
 }
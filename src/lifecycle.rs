@@ -0,0 +1,246 @@
+//! The `lifecycle` module manages the status of address records over time.  Addresses move
+//! between statuses only along the transitions allowed by [`allowed`], each transition is logged
+//! with the user and reason, and effective and expiration dates drive the [`Queue`] of addresses
+//! awaiting action.
+use crate::address::AddressPoints;
+use crate::table::{Cell, Columnar, Filtration, Tabular};
+use crate::utils::user_name;
+use address::prelude::{Address, AddressStatus};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// Returns true if an address may move from status `from` to status `to`.  Retired addresses stay
+/// retired; a retired number is assigned again as a new address.
+pub fn allowed(from: &AddressStatus, to: &AddressStatus) -> bool {
+    use AddressStatus::*;
+    match (from, to) {
+        (a, b) if a == b => true,
+        (Pending, Current | Temporary | Retired) => true,
+        (Temporary, Current | Retired) => true,
+        (Current, Temporary | Retired) => true,
+        (Virtual, Current | Retired) => true,
+        (Other, _) => true,
+        _ => false,
+    }
+}
+
+/// The `Dates` struct holds the effective and expiration dates of an address.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Dates {
+    /// The `effective` field holds the date the address takes effect.
+    pub effective: Option<NaiveDate>,
+    /// The `expires` field holds the date a temporary address expires.
+    pub expires: Option<NaiveDate>,
+    /// The `since` field holds the date the address entered its current status, if known.
+    #[serde(default)]
+    pub since: Option<NaiveDate>,
+}
+
+/// The `Transition` struct records a change in status of an address.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Transition {
+    pub id: uuid::Uuid,
+    /// The `record` field holds the id of the [`AddressPoint`](crate::address::AddressPoint).
+    pub record: uuid::Uuid,
+    pub address: String,
+    pub from: AddressStatus,
+    pub to: AddressStatus,
+    /// The `effective` field holds the date the new status takes effect.
+    pub effective: NaiveDate,
+    pub user: String,
+    /// The `reason` field holds the reason given by the user.
+    pub reason: String,
+    pub time: DateTime<Utc>,
+}
+
+/// The `Lifecycle` struct holds the dates and status history of the addresses in a dataset.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Lifecycle {
+    /// The `dates` field holds the dates of each address, by record id.  Record ids are derived
+    /// from the dataset and the position of the record (see
+    /// [`Dataset::record_id`](crate::catalog::Dataset::record_id)), so they survive reopening the
+    /// dataset, and unlike labels they stay unique and do not change when the address is edited.
+    pub dates: HashMap<uuid::Uuid, Dates>,
+    /// The `log` field holds the status transitions, oldest first.
+    pub log: Vec<Transition>,
+    /// The `revision` field counts changes to the lifecycle, so the undo history can detect them.
+    pub revision: usize,
+}
+
+impl Lifecycle {
+    pub fn dates(&self, record: &uuid::Uuid) -> Dates {
+        self.dates.get(record).copied().unwrap_or_default()
+    }
+
+    /// Sets the dates of the record with id `record`.
+    pub fn set_dates(&mut self, record: uuid::Uuid, dates: Dates) {
+        self.dates.insert(record, dates);
+        self.revision += 1;
+    }
+
+    /// Copies the dates of each record in `ids` from its old id to its new id, as when edits are
+    /// saved as a new dataset.  The dates under the old ids are kept for the original dataset.
+    pub fn carry(&mut self, ids: &[(uuid::Uuid, uuid::Uuid)]) {
+        for (old, new) in ids {
+            if let Some(dates) = self.dates.get(old).copied() {
+                self.dates.insert(*new, dates);
+            }
+        }
+        self.revision += 1;
+    }
+
+    /// Logs a transition of `address` from `from` to `to`, effective on `effective`.  Promotion
+    /// sets the effective date of the address, and retirement sets the expiration date.
+    pub fn record(
+        &mut self,
+        record: uuid::Uuid,
+        address: &str,
+        from: AddressStatus,
+        to: AddressStatus,
+        effective: NaiveDate,
+        reason: &str,
+    ) {
+        let mut dates = self.dates(&record);
+        dates.since = Some(effective);
+        match to {
+            AddressStatus::Retired => dates.expires = Some(effective),
+            _ => dates.effective = Some(effective),
+        }
+        self.dates.insert(record, dates);
+        tracing::info!("{} moved from {} to {}: {}", address, from, to, reason);
+        self.log.push(Transition {
+            id: uuid::Uuid::new_v4(),
+            record,
+            address: address.to_string(),
+            from,
+            to,
+            effective,
+            user: user_name(),
+            reason: reason.to_string(),
+            time: Utc::now(),
+        });
+        self.revision += 1;
+    }
+
+    /// Lists the Pending addresses in `points` awaiting activation, and the Temporary addresses
+    /// past their expiration date as of `today`.
+    pub fn queue(&self, points: &AddressPoints, today: NaiveDate) -> Queue {
+        let mut items = Vec::new();
+        for point in points.iter() {
+            let address = point.address.label();
+            let dates = self.dates(&point.id);
+            let action = match point.address.status() {
+                AddressStatus::Pending => match dates.effective {
+                    Some(date) if date <= today => "Due for activation",
+                    Some(_) => "Scheduled",
+                    None => "Awaiting activation",
+                },
+                AddressStatus::Temporary => match dates.expires {
+                    Some(date) if date < today => "Expired",
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            items.push(QueueItem {
+                id: point.id,
+                address,
+                status: point.address.status().to_string(),
+                effective: dates.effective,
+                expires: dates.expires,
+                action: action.to_string(),
+            });
+        }
+        Queue(items)
+    }
+}
+
+/// The `QueueItem` struct is a row in the lifecycle queue.  The `id` matches the
+/// [`AddressPoint`](crate::address::AddressPoint).
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct QueueItem {
+    pub id: uuid::Uuid,
+    pub address: String,
+    pub status: String,
+    pub effective: Option<NaiveDate>,
+    pub expires: Option<NaiveDate>,
+    /// The `action` field describes why the address is in the queue.
+    pub action: String,
+}
+
+#[derive(EnumIter, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum QueueColumns {
+    Address,
+    Status,
+    Effective,
+    Expires,
+    Action,
+}
+
+impl QueueColumns {
+    pub fn value(&self, item: &QueueItem) -> String {
+        let date = |v: Option<NaiveDate>| match v {
+            Some(date) => date.to_string(),
+            None => "".to_string(),
+        };
+        match self {
+            Self::Address => item.address.clone(),
+            Self::Status => item.status.clone(),
+            Self::Effective => date(item.effective),
+            Self::Expires => date(item.expires),
+            Self::Action => item.action.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for QueueColumns {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Address => write!(f, "Address"),
+            Self::Status => write!(f, "Status"),
+            Self::Effective => write!(f, "Effective"),
+            Self::Expires => write!(f, "Expires"),
+            Self::Action => write!(f, "Action"),
+        }
+    }
+}
+
+impl Columnar for QueueItem {
+    fn cells(&self) -> Vec<Cell> {
+        QueueColumns::iter()
+            .map(|v| Cell::Text(v.value(self)))
+            .collect::<Vec<Cell>>()
+    }
+
+    fn id(&self) -> uuid::Uuid {
+        self.id
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize, derive_more::Deref)]
+pub struct Queue(Vec<QueueItem>);
+
+impl Tabular<QueueItem> for Queue {
+    fn headers() -> Vec<String> {
+        QueueColumns::iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>()
+    }
+
+    fn rows(&self) -> Vec<QueueItem> {
+        self.to_vec()
+    }
+
+    fn set_rows(&mut self, rows: Vec<QueueItem>) {
+        self.0 = rows;
+    }
+}
+
+impl Filtration<Queue, String> for Queue {}
+
+/// Today's date in local time.
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
@@ -0,0 +1,368 @@
+//! The `master` module holds the street master list, a table of canonical street names and the
+//! aliases used for them in other address sources, such as "HWY 199" for "REDWOOD HWY".  Matching,
+//! validation and the centerline checks normalize street names through the list, so variants of
+//! the same street compare equal.
+use crate::matching::normalize_ordinals;
+use crate::qa::address_street;
+use crate::rules::{RuleIssue, RuleIssues, Severity};
+use crate::streets::normalize_street;
+use crate::table::{Cell, CellEdit, Columnar, Filtration, Tabular};
+use crate::utils::from_csv;
+use address::prelude::SpatialAddress;
+use aid::prelude::{Bandage, Clean};
+use derive_more::{Deref, DerefMut};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::path::Path;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// Name of the issue reported for addresses on a street missing from the master list.
+pub const UNLISTED: &str = "Unlisted street";
+
+/// Reduces a street name to the key used to compare names, abbreviating the directional and street
+/// type and converting spelled-out ordinals to numerals.
+pub fn street_key(name: &str) -> String {
+    normalize_street(&normalize_ordinals(&name.replace('.', "")))
+}
+
+/// The `StreetName` struct is an entry in the street master list.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct StreetName {
+    /// The `id` field holds a [`uuid::Uuid`] for use by the [`crate::table::TableView`].
+    pub id: uuid::Uuid,
+    /// The `canonical` field holds the name of the street as it should appear in the address
+    /// data, e.g. "REDWOOD HWY".
+    pub canonical: String,
+    /// The `aliases` field lists other names for the street, e.g. "HWY 199".
+    pub aliases: Vec<String>,
+}
+
+impl StreetName {
+    pub fn new(canonical: &str, aliases: &[String]) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4(),
+            canonical: canonical.trim().to_uppercase(),
+            aliases: aliases
+                .iter()
+                .map(|v| v.trim().to_uppercase())
+                .filter(|v| !v.is_empty())
+                .collect::<Vec<String>>(),
+        }
+    }
+
+    /// Splits a list of aliases separated by semicolons.
+    pub fn split(aliases: &str) -> Vec<String> {
+        aliases
+            .split(';')
+            .map(|v| v.trim().to_uppercase())
+            .filter(|v| !v.is_empty())
+            .collect::<Vec<String>>()
+    }
+}
+
+/// The `StreetRow` struct is the csv form of a [`StreetName`], with aliases separated by
+/// semicolons.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+struct StreetRow {
+    canonical: String,
+    #[serde(default)]
+    aliases: String,
+}
+
+#[derive(EnumIter, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StreetNameColumns {
+    Canonical,
+    Aliases,
+}
+
+impl StreetNameColumns {
+    pub fn value(&self, street: &StreetName) -> String {
+        match self {
+            Self::Canonical => street.canonical.clone(),
+            Self::Aliases => street.aliases.join("; "),
+        }
+    }
+}
+
+impl fmt::Display for StreetNameColumns {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Canonical => write!(f, "Canonical Name"),
+            Self::Aliases => write!(f, "Aliases"),
+        }
+    }
+}
+
+impl Columnar for StreetName {
+    fn cells(&self) -> Vec<Cell> {
+        StreetNameColumns::iter()
+            .map(|v| Cell::Text(v.value(self)))
+            .collect::<Vec<Cell>>()
+    }
+
+    fn id(&self) -> uuid::Uuid {
+        self.id
+    }
+}
+
+/// The `StreetMaster` struct holds the street master list.  It is stored with the workspace, and
+/// can be exchanged with other users as a csv file.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize, Deref, DerefMut)]
+pub struct StreetMaster(Vec<StreetName>);
+
+impl StreetMaster {
+    /// Reads the list from a csv file at `path` with the columns `canonical` and `aliases`.
+    pub fn from_csv<P: AsRef<Path>>(path: P) -> Clean<Self> {
+        let rows: Vec<StreetRow> = from_csv(path)?;
+        let streets = rows
+            .iter()
+            .filter(|v| !v.canonical.trim().is_empty())
+            .map(|v| StreetName::new(&v.canonical, &StreetName::split(&v.aliases)))
+            .collect::<Vec<StreetName>>();
+        let master = Self(streets);
+        master.validate()?;
+        Ok(master)
+    }
+
+    pub fn to_csv<P: AsRef<Path>>(&self, path: P) -> Clean<()> {
+        let mut wtr = csv::Writer::from_path(path).map_err(|e| Bandage::Hint(e.to_string()))?;
+        for street in self.iter() {
+            let row = StreetRow {
+                canonical: street.canonical.clone(),
+                aliases: street.aliases.join(";"),
+            };
+            wtr.serialize(row)
+                .map_err(|e| Bandage::Hint(e.to_string()))?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+
+    /// Checks that every canonical name is present, and that no name or alias belongs to more
+    /// than one street.
+    pub fn validate(&self) -> Clean<()> {
+        let mut owners: HashMap<String, usize> = HashMap::new();
+        for (i, street) in self.iter().enumerate() {
+            if street.canonical.trim().is_empty() {
+                return Err(Bandage::Hint("Canonical name is empty.".to_string()));
+            }
+            let mut names = vec![&street.canonical];
+            names.extend(street.aliases.iter());
+            for name in names {
+                let key = street_key(name);
+                match owners.get(&key) {
+                    Some(owner) if *owner != i => {
+                        return Err(Bandage::Hint(format!(
+                            "{name} is already listed under {}.",
+                            self[*owner].canonical
+                        )));
+                    }
+                    _ => {
+                        owners.insert(key, i);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds a street to the list, rejecting names already listed.
+    pub fn add(&mut self, street: StreetName) -> Clean<()> {
+        self.push(street);
+        if let Err(e) = self.validate() {
+            self.pop();
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Applies a cell edit from the table, returning true if the entry changed.  The list is left
+    /// unchanged if the edit would make it invalid.
+    pub fn edit(&mut self, edit: &CellEdit) -> Clean<bool> {
+        let Some(index) = self.iter().position(|v| v.id == edit.row) else {
+            return Ok(false);
+        };
+        let old = self[index].clone();
+        match edit.column {
+            0 => self[index].canonical = edit.value.trim().to_uppercase(),
+            1 => self[index].aliases = StreetName::split(&edit.value),
+            _ => return Ok(false),
+        }
+        if let Err(e) = self.validate() {
+            self[index] = old;
+            return Err(e);
+        }
+        Ok(self[index] != old)
+    }
+
+    /// Adds the streets of `addresses` missing from the list, with no aliases.  Spellings of a
+    /// street that share a key are added once, under the first spelling in sort order.  Returns
+    /// the number of streets added, and leaves the list unchanged if the result is invalid.
+    pub fn seed(&mut self, addresses: &[SpatialAddress]) -> Clean<usize> {
+        let lookup = self.lookup();
+        let mut streets = BTreeMap::new();
+        for street in addresses
+            .iter()
+            .map(address_street)
+            .filter(|v| !lookup.contains(v))
+            .collect::<BTreeSet<String>>()
+        {
+            streets.entry(street_key(&street)).or_insert(street);
+        }
+        let count = streets.len();
+        let len = self.len();
+        for street in streets.into_values() {
+            self.push(StreetName::new(&street, &[]));
+        }
+        if let Err(e) = self.validate() {
+            self.truncate(len);
+            return Err(e);
+        }
+        Ok(count)
+    }
+
+    /// Indexes the keys of each name and alias by the key of the canonical name.
+    pub fn lookup(&self) -> StreetLookup {
+        let mut names = HashMap::new();
+        for street in self.iter() {
+            let canonical = street_key(&street.canonical);
+            for alias in street.aliases.iter() {
+                names.insert(street_key(alias), canonical.clone());
+            }
+            names.insert(canonical.clone(), canonical);
+        }
+        StreetLookup(names)
+    }
+
+    /// Reports the addresses in `addresses` on streets missing from the list.
+    pub fn unlisted(&self, addresses: &[SpatialAddress]) -> RuleIssues {
+        let lookup = self.lookup();
+        let issues = addresses
+            .iter()
+            .filter_map(|v| {
+                lookup
+                    .unlisted(v)
+                    .map(|detail| RuleIssue::with_name(UNLISTED, Severity::Warning, v, &detail))
+            })
+            .collect::<Vec<RuleIssue>>();
+        tracing::info!("Addresses on unlisted streets: {}", issues.len());
+        RuleIssues::from(issues)
+    }
+}
+
+impl Tabular<StreetName> for StreetMaster {
+    fn headers() -> Vec<String> {
+        StreetNameColumns::iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>()
+    }
+
+    fn rows(&self) -> Vec<StreetName> {
+        self.to_vec()
+    }
+
+    fn set_rows(&mut self, rows: Vec<StreetName>) {
+        self.0 = rows;
+    }
+
+    fn editable(_column_index: usize) -> bool {
+        true
+    }
+}
+
+impl Filtration<StreetMaster, String> for StreetMaster {
+    /// Filters the list to streets with aliases ("aliased") or without them ("unaliased").
+    fn filter(&mut self, filter: &String) -> Self {
+        let mut streets = self.to_vec();
+        match filter.as_str() {
+            "aliased" => streets.retain(|v| !v.aliases.is_empty()),
+            "unaliased" => streets.retain(|v| v.aliases.is_empty()),
+            _ => {}
+        }
+        Self(streets)
+    }
+}
+
+/// The `StreetLookup` struct maps the key of each listed name and alias to the key of its
+/// canonical name.  Build it once with [`StreetMaster::lookup`] before normalizing many addresses.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StreetLookup(HashMap<String, String>);
+
+impl StreetLookup {
+    /// Normalizes `name` to the key of its canonical name, or to its own key if unlisted.
+    pub fn street(&self, name: &str) -> String {
+        let key = street_key(name);
+        match self.0.get(&key) {
+            Some(canonical) => canonical.clone(),
+            None => key,
+        }
+    }
+
+    /// Normalizes the street of `address`, including the directional and street type.
+    pub fn address(&self, address: &SpatialAddress) -> String {
+        self.street(&address_street(address))
+    }
+
+    /// Returns true if `name` is a canonical name or alias in the list.
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.contains_key(&street_key(name))
+    }
+
+    /// Describes why the street of `address` is unlisted, or returns `None` if it is listed.  An
+    /// empty list has nothing to check against, so every street passes.
+    pub fn unlisted(&self, address: &SpatialAddress) -> Option<String> {
+        let street = address_street(address);
+        if self.0.is_empty() || self.contains(&street) {
+            None
+        } else {
+            Some(format!("Street {street} is not in the street master list."))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::AddressPoint;
+
+    #[test]
+    fn street_key_normalizes_spellings() {
+        let key = street_key("N 21ST ST");
+        for name in [
+            "North Twenty-First Street",
+            "N. 21st St.",
+            "north twenty first st",
+        ] {
+            assert_eq!(street_key(name), key, "{name}");
+        }
+        assert_ne!(street_key("S 21ST ST"), key);
+        assert_ne!(street_key("N 21ST AVE"), key);
+    }
+
+    #[test]
+    fn lookup_resolves_aliases() {
+        let mut master = StreetMaster::default();
+        master.push(StreetName::new("REDWOOD HWY", &["HWY 199".to_string()]));
+        let lookup = master.lookup();
+        assert_eq!(lookup.street("Hwy 199"), "REDWOOD HWY");
+        assert_eq!(lookup.street("Redwood Highway"), "REDWOOD HWY");
+        assert_eq!(lookup.street("Elm St"), "ELM ST");
+    }
+
+    #[test]
+    fn seed_adds_each_street_once() {
+        let mut master = StreetMaster::default();
+        master.push(StreetName::new("ELM", &[]));
+        let records = vec![
+            AddressPoint::sample(100, "TWENTY FIRST", 97526, 0.0, 0.0),
+            AddressPoint::sample(102, "21ST", 97526, 0.0, 0.0),
+            AddressPoint::sample(104, "ELM", 97526, 0.0, 0.0),
+        ];
+        assert_eq!(master.seed(&records).unwrap(), 1);
+        assert_eq!(master.len(), 2);
+        assert!(master.validate().is_ok());
+        assert_eq!(master.seed(&records).unwrap(), 0);
+    }
+}
@@ -0,0 +1,841 @@
+//! The `matching` module extends the exact comparison of [`MatchRecords`] with an optional fuzzy
+//! mode.  Subject addresses missing from the target are scored against unmatched target addresses
+//! with the same address number, and the best candidate scoring above the acceptance threshold is
+//! reported as a probable match.  Street names are normalized through the
+//! [`StreetMaster`](crate::master::StreetMaster), so addresses differing only by a listed street
+//! alias are paired.
+use crate::master::StreetLookup;
+use crate::presence::LOCATION_TOLERANCE;
+use crate::qa::abbreviate_directional;
+use crate::table::{Cell, Columnar, Filtration, Tabular};
+use crate::utils::distance;
+use address::prelude::{Address, MatchRecord, MatchRecords, MatchStatus, SpatialAddress};
+use aid::prelude::{Bandage, Clean};
+use derive_more::{Deref, DerefMut};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// The `FuzzyConfig` struct holds the acceptance threshold and the weights given to each
+/// component of the match score.  Weights are normalized by their sum, so only their relative
+/// size matters.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct FuzzyConfig {
+    /// The `threshold` field is the minimum score, between 0 and 1, of a probable match.
+    pub threshold: f64,
+    /// The `radius` field is the distance in meters at which the proximity score reaches zero.
+    pub radius: f64,
+    pub number: f64,
+    pub street_name: f64,
+    pub street_type: f64,
+    pub directional: f64,
+    pub subaddress: f64,
+    pub proximity: f64,
+}
+
+impl Default for FuzzyConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 0.8,
+            radius: 100.0,
+            number: 0.2,
+            street_name: 0.35,
+            street_type: 0.1,
+            directional: 0.1,
+            subaddress: 0.1,
+            proximity: 0.15,
+        }
+    }
+}
+
+impl FuzzyConfig {
+    /// Scores the similarity of `subject` to `candidate`, from 0 (no similarity) to 1 (identical).
+    /// Streets that `streets` normalizes to the same canonical name score as identical.
+    pub fn score(
+        &self,
+        subject: &SpatialAddress,
+        candidate: &SpatialAddress,
+        streets: &StreetLookup,
+    ) -> f64 {
+        // A number one typo away, such as 1243 for 1234, earns half credit.
+        let number = if subject.number() == candidate.number() {
+            1.0
+        } else if near_numbers(subject.number()).contains(&candidate.number()) {
+            0.5
+        } else {
+            0.0
+        };
+        let (street_name, street_type, directional) =
+            if streets.address(subject) == streets.address(candidate) {
+                (1.0, 1.0, 1.0)
+            } else {
+                let street_name = similarity(
+                    &normalize_ordinals(&subject.street_name().to_string()),
+                    &normalize_ordinals(&candidate.street_name().to_string()),
+                );
+                let street_type = if street_type(subject) == street_type(candidate) {
+                    1.0
+                } else {
+                    0.0
+                };
+                let directional = if directional(subject) == directional(candidate) {
+                    1.0
+                } else {
+                    0.0
+                };
+                (street_name, street_type, directional)
+            };
+        let subaddress = match (subject.subaddress_id(), candidate.subaddress_id()) {
+            (None, None) => 1.0,
+            // A missing unit designator is a common omission, so it only counts against half.
+            (Some(_), None) | (None, Some(_)) => 0.5,
+            (Some(a), Some(b)) => {
+                if a.to_uppercase() == b.to_uppercase() {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        };
+        let meters = distance(
+            subject.latitude,
+            subject.longitude,
+            candidate.latitude,
+            candidate.longitude,
+        );
+        let proximity = (1.0 - meters / self.radius).max(0.0);
+        let total = self.number
+            + self.street_name
+            + self.street_type
+            + self.directional
+            + self.subaddress
+            + self.proximity;
+        if total <= 0.0 {
+            return 0.0;
+        }
+        (number * self.number
+            + street_name * self.street_name
+            + street_type * self.street_type
+            + directional * self.directional
+            + subaddress * self.subaddress
+            + proximity * self.proximity)
+            / total
+    }
+}
+
+fn street_type(address: &SpatialAddress) -> String {
+    match address.street_type() {
+        Some(value) => format!("{}", value.abbreviate()).to_uppercase(),
+        None => "".to_string(),
+    }
+}
+
+fn directional(address: &SpatialAddress) -> String {
+    match address.directional() {
+        Some(value) => abbreviate_directional(&value.to_string()),
+        None => "".to_string(),
+    }
+}
+
+/// Similarity of two strings based on the Levenshtein edit distance, from 0 to 1.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let len = a.chars().count().max(b.chars().count());
+    if len == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(a, b) as f64 / len as f64
+}
+
+/// The address numbers one typo away from `number`: a digit changed, dropped, added or swapped
+/// with its neighbor.  Numbers with a leading zero are left out.
+pub fn near_numbers(number: i64) -> Vec<i64> {
+    let digits = number.to_string().chars().collect::<Vec<char>>();
+    let mut variants = Vec::new();
+    for i in 0..=digits.len() {
+        for d in '0'..='9' {
+            // Change the digit at `i`.
+            if i < digits.len() && digits[i] != d {
+                let mut v = digits.clone();
+                v[i] = d;
+                variants.push(v);
+            }
+            // Add a digit before `i`.
+            let mut v = digits.clone();
+            v.insert(i, d);
+            variants.push(v);
+        }
+        if i < digits.len() {
+            // Drop the digit at `i`.
+            let mut v = digits.clone();
+            v.remove(i);
+            variants.push(v);
+        }
+        if i + 1 < digits.len() && digits[i] != digits[i + 1] {
+            // Swap the digits at `i` and `i + 1`.
+            let mut v = digits.clone();
+            v.swap(i, i + 1);
+            variants.push(v);
+        }
+    }
+    let mut numbers = variants
+        .into_iter()
+        .filter(|v| !v.is_empty() && (v.len() == 1 || v[0] != '0'))
+        .filter_map(|v| v.into_iter().collect::<String>().parse::<i64>().ok())
+        .filter(|v| *v != number)
+        .collect::<Vec<i64>>();
+    numbers.sort();
+    numbers.dedup();
+    numbers
+}
+
+/// The number of single-character insertions, deletions or substitutions needed to change `a`
+/// into `b`.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<char>>();
+    let mut previous = (0..=b.len()).collect::<Vec<usize>>();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            current[j + 1] = (previous[j + 1] + 1)
+                .min(current[j] + 1)
+                .min(previous[j] + cost);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+const ORDINALS: [(&str, &str); 19] = [
+    ("FIRST", "1ST"),
+    ("SECOND", "2ND"),
+    ("THIRD", "3RD"),
+    ("FOURTH", "4TH"),
+    ("FIFTH", "5TH"),
+    ("SIXTH", "6TH"),
+    ("SEVENTH", "7TH"),
+    ("EIGHTH", "8TH"),
+    ("NINTH", "9TH"),
+    ("TENTH", "10TH"),
+    ("ELEVENTH", "11TH"),
+    ("TWELFTH", "12TH"),
+    ("THIRTEENTH", "13TH"),
+    ("FOURTEENTH", "14TH"),
+    ("FIFTEENTH", "15TH"),
+    ("SIXTEENTH", "16TH"),
+    ("SEVENTEENTH", "17TH"),
+    ("EIGHTEENTH", "18TH"),
+    ("NINETEENTH", "19TH"),
+];
+
+const TENS: [(&str, &str, &str); 8] = [
+    ("TWENTY", "TWENTIETH", "2"),
+    ("THIRTY", "THIRTIETH", "3"),
+    ("FORTY", "FORTIETH", "4"),
+    ("FIFTY", "FIFTIETH", "5"),
+    ("SIXTY", "SIXTIETH", "6"),
+    ("SEVENTY", "SEVENTIETH", "7"),
+    ("EIGHTY", "EIGHTIETH", "8"),
+    ("NINETY", "NINETIETH", "9"),
+];
+
+/// Converts spelled-out ordinals in a street name to numerals, so "SEVENTH" and "7TH" or
+/// "TWENTY-FIRST" and "21ST" compare equal.  The result is upper case.
+pub fn normalize_ordinals(name: &str) -> String {
+    let name = name.to_uppercase().replace('-', " ");
+    let words = name.split_whitespace().collect::<Vec<&str>>();
+    let mut result = Vec::new();
+    let mut i = 0;
+    while i < words.len() {
+        let word = words[i];
+        if let Some((_, _, digit)) = TENS.iter().find(|(tens, _, _)| *tens == word) {
+            // Compound ordinals such as "TWENTY FIRST".
+            if let Some(next) = words.get(i + 1) {
+                if let Some((_, numeral)) = ORDINALS.iter().take(9).find(|(w, _)| w == next) {
+                    result.push(format!("{digit}{numeral}"));
+                    i += 2;
+                    continue;
+                }
+            }
+        }
+        if let Some((_, _, digit)) = TENS.iter().find(|(_, ordinal, _)| *ordinal == word) {
+            result.push(format!("{digit}0TH"));
+        } else if let Some((_, numeral)) = ORDINALS.iter().find(|(w, _)| *w == word) {
+            result.push(numeral.to_string());
+        } else {
+            result.push(word.to_string());
+        }
+        i += 1;
+    }
+    result.join(" ")
+}
+
+/// The `CompareStatus` enum extends [`MatchStatus`] with a probable match found by fuzzy
+/// matching.
+#[derive(
+    Debug,
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    EnumIter,
+    Deserialize,
+    Serialize,
+)]
+pub enum CompareStatus {
+    Matching,
+    Divergent,
+    Probable,
+    #[default]
+    Missing,
+}
+
+impl From<&MatchStatus> for CompareStatus {
+    fn from(status: &MatchStatus) -> Self {
+        match status {
+            MatchStatus::Matching => Self::Matching,
+            MatchStatus::Divergent => Self::Divergent,
+            MatchStatus::Missing => Self::Missing,
+        }
+    }
+}
+
+impl fmt::Display for CompareStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Matching => write!(f, "Matching"),
+            Self::Divergent => write!(f, "Divergent"),
+            Self::Probable => write!(f, "Probable match"),
+            Self::Missing => write!(f, "Missing"),
+        }
+    }
+}
+
+/// The `CompareRecord` struct is a row in the comparison table.  It wraps a [`MatchRecord`] from
+/// the exact comparison, adding the status and score from fuzzy matching.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CompareRecord {
+    pub record: MatchRecord,
+    pub status: CompareStatus,
+    /// The `score` field holds the fuzzy match score of the best candidate, if one was scored.
+    pub score: Option<f64>,
+    /// The `candidate` field holds the label of the best candidate in the target.
+    pub candidate: Option<String>,
+    /// The `reasons` field lists the fields that differ from the target, for divergent and
+    /// probable matches.
+    pub reasons: Vec<DivergenceReason>,
+}
+
+impl CompareRecord {
+    /// The label of the target address paired with this record.  A divergent record shares its
+    /// label with the target, unless paired through a street alias, while a probable match pairs
+    /// with the best candidate.
+    pub fn target_label(&self) -> Option<String> {
+        match self.status {
+            CompareStatus::Matching | CompareStatus::Divergent => Some(
+                self.candidate
+                    .clone()
+                    .unwrap_or_else(|| self.record.address_label.to_string()),
+            ),
+            CompareStatus::Probable => self.candidate.clone(),
+            CompareStatus::Missing => None,
+        }
+    }
+}
+
+impl From<&MatchRecord> for CompareRecord {
+    fn from(record: &MatchRecord) -> Self {
+        Self {
+            record: record.clone(),
+            status: CompareStatus::from(&record.match_status),
+            score: None,
+            candidate: None,
+            reasons: Vec::new(),
+        }
+    }
+}
+
+#[derive(EnumIter, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CompareColumns {
+    MatchStatus,
+    Score,
+    Address,
+    Candidate,
+    Reasons,
+    Subaddress,
+    Floor,
+    Building,
+    Status,
+    Latitude,
+    Longitude,
+}
+
+impl CompareColumns {
+    pub fn value(&self, record: &CompareRecord) -> Cell {
+        match self {
+            Self::MatchStatus => Cell::variant(&record.status),
+            Self::Score => record.score.map_or(Cell::Empty, |v| Cell::Float(v, 2)),
+            Self::Address => Cell::Text(record.record.address_label.to_string()),
+            Self::Candidate => Cell::optional(record.candidate.clone()),
+            Self::Reasons => Cell::Text(
+                record
+                    .reasons
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", "),
+            ),
+            Self::Subaddress => Cell::Text(format!("{:?}", record.record.subaddress_type)),
+            Self::Floor => Cell::Text(format!("{:?}", record.record.floor)),
+            Self::Building => Cell::Text(format!("{:?}", record.record.building)),
+            Self::Status => Cell::Text(format!("{:?}", record.record.status)),
+            Self::Latitude => Cell::Coordinate(record.record.latitude),
+            Self::Longitude => Cell::Coordinate(record.record.longitude),
+        }
+    }
+}
+
+impl fmt::Display for CompareColumns {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MatchStatus => write!(f, "Match Status"),
+            Self::Score => write!(f, "Score"),
+            Self::Address => write!(f, "Address"),
+            Self::Candidate => write!(f, "Candidate"),
+            Self::Reasons => write!(f, "Divergence"),
+            Self::Subaddress => write!(f, "Subaddress Type"),
+            Self::Floor => write!(f, "Floor"),
+            Self::Building => write!(f, "Building"),
+            Self::Status => write!(f, "Status"),
+            Self::Latitude => write!(f, "Latitude"),
+            Self::Longitude => write!(f, "Longitude"),
+        }
+    }
+}
+
+impl Columnar for CompareRecord {
+    fn cells(&self) -> Vec<Cell> {
+        CompareColumns::iter()
+            .map(|v| v.value(self))
+            .collect::<Vec<Cell>>()
+    }
+
+    fn id(&self) -> uuid::Uuid {
+        self.record.id
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize, Deref, DerefMut)]
+pub struct CompareRecords(Vec<CompareRecord>);
+
+impl CompareRecords {
+    /// Compares `subject` to `target`.  Subject addresses missing from the target are first paired
+    /// with target addresses that differ only by a street alias in `streets`.  When `fuzzy` is
+    /// set, the remaining missing addresses are scored against unmatched target addresses with the
+    /// same address number, or a number one typo away.
+    pub fn compare(
+        subject: &[SpatialAddress],
+        target: &[SpatialAddress],
+        fuzzy: Option<&FuzzyConfig>,
+        streets: &StreetLookup,
+    ) -> Self {
+        let records = MatchRecords::compare(subject, target)
+            .iter()
+            .map(CompareRecord::from)
+            .collect::<Vec<CompareRecord>>();
+        let mut records = Self(records);
+        records.alias(subject, target, streets);
+        if let Some(config) = fuzzy {
+            records.fuzzy(subject, target, config, streets);
+        }
+        records.diagnose(subject, target);
+        records
+    }
+
+    /// Records the reasons each divergent or probable match differs from its target address.
+    pub fn diagnose(&mut self, subject: &[SpatialAddress], target: &[SpatialAddress]) {
+        let subjects = index(subject);
+        let targets = index(target);
+        for record in self.iter_mut() {
+            let Some(label) = record.target_label() else {
+                continue;
+            };
+            if let (Some(a), Some(b)) = (
+                subjects.get(&record.record.address_label),
+                targets.get(&label),
+            ) {
+                record.reasons = DivergenceReason::compare(a, b);
+            }
+        }
+    }
+
+    /// Pairs missing records with unmatched addresses in `target` that have the same number,
+    /// subaddress and canonical street name in `streets`.  The record is matching if the pair
+    /// otherwise agrees, and divergent if not.
+    pub fn alias(
+        &mut self,
+        subject: &[SpatialAddress],
+        target: &[SpatialAddress],
+        streets: &StreetLookup,
+    ) {
+        let subjects = index(subject);
+        let labels = subject
+            .iter()
+            .map(|v| v.label())
+            .collect::<HashSet<String>>();
+        let mut candidates = HashMap::new();
+        for address in target.iter().filter(|v| !labels.contains(&v.label())) {
+            candidates
+                .entry(alias_key(address, streets))
+                .or_insert(address);
+        }
+        let mut paired = 0;
+        for record in self
+            .iter_mut()
+            .filter(|v| v.status == CompareStatus::Missing)
+        {
+            let Some(address) = subjects.get(&record.record.address_label) else {
+                continue;
+            };
+            // Each target address pairs with one subject address at most.
+            let Some(candidate) = candidates.remove(&alias_key(address, streets)) else {
+                continue;
+            };
+            record.candidate = Some(candidate.label());
+            record.status = if DivergenceReason::compare(address, candidate).is_empty() {
+                CompareStatus::Matching
+            } else {
+                CompareStatus::Divergent
+            };
+            paired += 1;
+        }
+        tracing::info!("Street alias matches: {}", paired);
+    }
+
+    /// Scores missing records against candidate addresses in `target` with the same number or a
+    /// number one typo away, marking the record as a probable match when the best score meets the
+    /// threshold in `config`.  Pairs are assigned best score first, and each target address is
+    /// claimed by one probable match at most.
+    pub fn fuzzy(
+        &mut self,
+        subject: &[SpatialAddress],
+        target: &[SpatialAddress],
+        config: &FuzzyConfig,
+        streets: &StreetLookup,
+    ) {
+        let subjects = index(subject);
+        // Target addresses with an exact match in the subject, or already paired through a street
+        // alias, are not candidates.
+        let mut labels = subject
+            .iter()
+            .map(|v| v.label())
+            .collect::<HashSet<String>>();
+        labels.extend(self.iter().filter_map(|v| v.candidate.clone()));
+        let mut candidates: HashMap<i64, Vec<usize>> = HashMap::new();
+        for (i, address) in target.iter().enumerate() {
+            if !labels.contains(&address.label()) {
+                candidates.entry(address.number()).or_default().push(i);
+            }
+        }
+        // Score each missing record against each candidate in its pool.
+        let mut scores = Vec::new();
+        for (i, record) in self.iter().enumerate() {
+            if record.status != CompareStatus::Missing {
+                continue;
+            }
+            let Some(address) = subjects.get(&record.record.address_label) else {
+                continue;
+            };
+            let mut numbers = near_numbers(address.number());
+            numbers.push(address.number());
+            for j in numbers.iter().filter_map(|v| candidates.get(v)).flatten() {
+                scores.push((config.score(address, &target[*j], streets), i, *j));
+            }
+        }
+        // Assign the best pairs first.  The sort is stable, so ties keep the order of the records.
+        scores.sort_by(|a, b| b.0.total_cmp(&a.0));
+        let mut scored = HashSet::new();
+        let mut claimed = HashSet::new();
+        let mut probable = 0;
+        for (score, i, j) in scores {
+            if scored.contains(&i) || claimed.contains(&j) {
+                continue;
+            }
+            scored.insert(i);
+            let record = &mut self[i];
+            record.score = Some(score);
+            record.candidate = Some(target[j].label());
+            if score >= config.threshold {
+                record.status = CompareStatus::Probable;
+                claimed.insert(j);
+                probable += 1;
+            }
+        }
+        tracing::info!("Probable matches: {}", probable);
+    }
+
+    /// Writes the comparison to a csv file at `path`.
+    pub fn to_csv<P: AsRef<Path>>(&self, path: P) -> Clean<()> {
+        let mut wtr = csv::Writer::from_path(path).map_err(|e| Bandage::Hint(e.to_string()))?;
+        wtr.write_record(Self::headers())
+            .map_err(|e| Bandage::Hint(e.to_string()))?;
+        for record in self.iter() {
+            wtr.write_record(record.values())
+                .map_err(|e| Bandage::Hint(e.to_string()))?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+impl Tabular<CompareRecord> for CompareRecords {
+    fn headers() -> Vec<String> {
+        CompareColumns::iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>()
+    }
+
+    fn rows(&self) -> Vec<CompareRecord> {
+        self.to_vec()
+    }
+
+    fn set_rows(&mut self, rows: Vec<CompareRecord>) {
+        self.0 = rows;
+    }
+}
+
+impl Filtration<CompareRecords, String> for CompareRecords {
+    /// Filters records by the lower case name of the [`CompareStatus`], e.g. "probable".  Filters
+    /// prefixed with "reason:" select records by [`DivergenceReason`], e.g. "reason:zip".
+    fn filter(&mut self, filter: &String) -> Self {
+        let mut records = self.to_vec();
+        match filter.strip_prefix("reason:") {
+            Some(reason) => records.retain(|v| {
+                v.reasons
+                    .iter()
+                    .any(|r| format!("{:?}", r).to_lowercase() == reason)
+            }),
+            None => records.retain(|v| format!("{:?}", v.status).to_lowercase() == *filter),
+        }
+        Self(records)
+    }
+}
+
+/// The `PairIndex` struct indexes the subject and target addresses of a comparison by label, so
+/// the pair of a record is found without scanning both datasets.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PairIndex {
+    subject: HashMap<String, usize>,
+    target: HashMap<String, usize>,
+}
+
+impl PairIndex {
+    pub fn new(subject: &[SpatialAddress], target: &[SpatialAddress]) -> Self {
+        Self {
+            subject: positions(subject),
+            target: positions(target),
+        }
+    }
+
+    /// Finds the subject and target addresses paired in `record`.
+    pub fn pair<'a>(
+        &self,
+        record: &CompareRecord,
+        subject: &'a [SpatialAddress],
+        target: &'a [SpatialAddress],
+    ) -> Option<(&'a SpatialAddress, &'a SpatialAddress)> {
+        let label = record.target_label()?;
+        let a = subject.get(*self.subject.get(&record.record.address_label)?)?;
+        let b = target.get(*self.target.get(&label)?)?;
+        Some((a, b))
+    }
+}
+
+/// Indexes the position of addresses by label, keeping the first address with each label.
+fn positions(addresses: &[SpatialAddress]) -> HashMap<String, usize> {
+    let mut index = HashMap::new();
+    for (i, address) in addresses.iter().enumerate() {
+        index.entry(address.label()).or_insert(i);
+    }
+    index
+}
+
+/// Keys an address by number, canonical street and subaddress, for pairing through street aliases.
+fn alias_key(address: &SpatialAddress, streets: &StreetLookup) -> String {
+    let subaddress = match address.subaddress_id() {
+        Some(value) => value.to_uppercase(),
+        None => "".to_string(),
+    };
+    format!(
+        "{} {} {}",
+        address.number(),
+        streets.address(address),
+        subaddress
+    )
+}
+
+/// Indexes addresses by label, keeping the first address with each label.
+fn index(addresses: &[SpatialAddress]) -> HashMap<String, &SpatialAddress> {
+    let mut index = HashMap::new();
+    for address in addresses {
+        index.entry(address.label()).or_insert(address);
+    }
+    index
+}
+
+/// The `DivergenceReason` enum names a field that differs between a subject address and its
+/// target.
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter, Deserialize, Serialize,
+)]
+pub enum DivergenceReason {
+    Status,
+    Zip,
+    Subaddress,
+    Building,
+    Floor,
+    Location,
+}
+
+impl DivergenceReason {
+    /// Lists the reasons `subject` diverges from `target`.
+    pub fn compare(subject: &SpatialAddress, target: &SpatialAddress) -> Vec<Self> {
+        let mut reasons = FieldDiff::compare(subject, target)
+            .into_iter()
+            .filter(|v| v.differs)
+            .filter_map(|v| v.reason)
+            .collect::<Vec<Self>>();
+        reasons.dedup();
+        reasons
+    }
+}
+
+impl fmt::Display for DivergenceReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Status => write!(f, "Status"),
+            Self::Zip => write!(f, "Zip"),
+            Self::Subaddress => write!(f, "Subaddress"),
+            Self::Building => write!(f, "Building"),
+            Self::Floor => write!(f, "Floor"),
+            Self::Location => write!(f, "Location"),
+        }
+    }
+}
+
+/// The `FieldDiff` struct holds the value of a single field in a subject address and its target,
+/// for side-by-side display.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDiff {
+    pub field: String,
+    pub subject: String,
+    pub target: String,
+    /// The `differs` field is true when the values differ.  Coordinates differ when the points
+    /// are further apart than [`LOCATION_TOLERANCE`].
+    pub differs: bool,
+    /// The `reason` field holds the reason code associated with the field, if any.
+    pub reason: Option<DivergenceReason>,
+}
+
+impl FieldDiff {
+    fn new(field: &str, subject: String, target: String, reason: Option<DivergenceReason>) -> Self {
+        let differs = subject.to_uppercase() != target.to_uppercase();
+        Self {
+            field: field.to_string(),
+            subject,
+            target,
+            differs,
+            reason,
+        }
+    }
+
+    /// Pairs each field of `subject` with the same field of `target`.
+    pub fn compare(subject: &SpatialAddress, target: &SpatialAddress) -> Vec<Self> {
+        use DivergenceReason::*;
+        let mut location = Self::new(
+            "Coordinates",
+            format!("{:.6}, {:.6}", subject.latitude, subject.longitude),
+            format!("{:.6}, {:.6}", target.latitude, target.longitude),
+            Some(Location),
+        );
+        location.differs = distance(
+            subject.latitude,
+            subject.longitude,
+            target.latitude,
+            target.longitude,
+        ) > LOCATION_TOLERANCE;
+        vec![
+            Self::new("Label", subject.label(), target.label(), None),
+            Self::new(
+                "Status",
+                subject.status().to_string(),
+                target.status().to_string(),
+                Some(Status),
+            ),
+            Self::new(
+                "Zip",
+                subject.zip().to_string(),
+                target.zip().to_string(),
+                Some(Zip),
+            ),
+            Self::new(
+                "Subaddress Type",
+                display(subject.subaddress_type()),
+                display(target.subaddress_type()),
+                Some(Subaddress),
+            ),
+            Self::new(
+                "Subaddress ID",
+                display(subject.subaddress_id()),
+                display(target.subaddress_id()),
+                Some(Subaddress),
+            ),
+            Self::new(
+                "Building",
+                display(subject.building()),
+                display(target.building()),
+                Some(Building),
+            ),
+            Self::new(
+                "Floor",
+                display(subject.floor()),
+                display(target.floor()),
+                Some(Floor),
+            ),
+            location,
+        ]
+    }
+}
+
+fn display<T: fmt::Display>(value: Option<T>) -> String {
+    match value {
+        Some(v) => v.to_string(),
+        None => "".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn near_numbers_cover_single_typos() {
+        let near = near_numbers(1234);
+        // Changed, swapped, dropped and added digits.
+        for number in [1235, 1243, 2134, 123, 134, 12334, 11234] {
+            assert!(near.contains(&number), "{number} missing");
+        }
+        assert!(!near.contains(&1234));
+        assert!(!near.contains(&4321));
+        // Dropping the leading digit leaves a leading zero.
+        assert!(!near_numbers(1023).contains(&23));
+    }
+}
@@ -1,21 +1,57 @@
+use crate::address::AddressColumns;
+use crate::audit::{self, AuditEntry, AuditKind, AuditLog};
+use crate::batch::{BatchInput, BatchRecord, BatchRecords, MatchKind};
+use crate::catalog::Dataset;
+use crate::changes::{Change, ChangeSet};
 use crate::controls::focus;
 use crate::data;
+use crate::export::{ExportProfile, ProfileConfig};
+use crate::geocode::{
+    Candidate, CandidatePoints, Geocoder, GeocoderKey, NearbyAddress, NearbyAddresses,
+    ReverseGeocode,
+};
+use crate::lifecycle::{today, Lifecycle, Queue, QueueItem};
+use crate::master::{StreetMaster, StreetName};
+use crate::matching::{
+    CompareRecord, CompareRecords, CompareStatus, DivergenceReason, FieldDiff, FuzzyConfig,
+    PairIndex,
+};
+use crate::parcels::Parcels;
 use crate::prelude::{
-    AddressPoints, AddressSource, Boundary, BoundaryView, Columnar, Filtration, TableView, Tabular,
+    AddressPoint, AddressPoints, Boundary, BoundaryView, Cell, CellEdit, Columnar, Filtration,
+    Issue, IssueKind, Issues, TableConfig, TableView, Tabular,
 };
+use crate::presence::{
+    Combination, Presence, PresenceFilter, PresenceMatrix, PresenceSummary, Source,
+};
+use crate::rules::{Check, RuleIssue, RuleIssues, Rules, Severity};
+use crate::runs::{Run, RunHistory, RunSummary, RunTrend};
+use crate::spatial::{SpatialCheck, SpatialConfig, CITY_LIMITS_PATH};
+use crate::streets::{CenterlineFields, Centerlines, StreetCheck, CENTERLINES_PATH};
+use crate::utils::{distance, web_mercator};
 use address::prelude::{
-    Addresses, LexisNexis, LexisNexisItem, MatchRecord, MatchRecords, MatchStatus, Portable,
-    SpatialAddresses,
+    Address, AddressStatus, Addresses, LexisNexis, LexisNexisItem, SpatialAddress, SpatialAddresses,
 };
 use aid::prelude::*;
+use chrono::NaiveDate;
 use geo::algorithm::contains::Contains;
+use prettydiff::basic::DiffOp;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
 use std::{env, fmt};
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 use tracing::info;
 
+/// Number of suggestions shown below the address search bar.
+const SUGGESTIONS: usize = 8;
+/// Number of nearby addresses listed by the reverse geocoder.
+const NEARBY: usize = 10;
+
 #[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Operations {
     pub compare: Compare,
@@ -27,10 +63,30 @@ pub struct Operations {
     pub load: bool,
     /// Contains the LexisNexis widget.
     pub lexis: Lexis,
-    /// The [`AddressSource`] associated with the subject data.
-    pub subject: AddressSource,
-    /// The index of the addresses in the `addresses` field of [`Data`].
-    pub subject_idx: usize,
+    /// Contains the N-way comparison widget.
+    #[serde(deserialize_with = "crate::project::v1::matrix")]
+    pub matrix: Matrix,
+    /// Contains the attribute editing widget.
+    pub editor: Editor,
+    /// Contains the audit log widget.
+    pub audit: Audit,
+    /// Contains the attribute validation widget.
+    #[serde(deserialize_with = "crate::project::v1::validation")]
+    pub validation: Validation,
+    /// Contains the spatial QA widget.
+    pub spatial: Spatial,
+    /// Contains the street centerline widget.
+    pub streets: Streets,
+    /// Contains the street master widget.
+    pub master: Master,
+    /// Contains the address search bar.
+    pub geocode: Geocode,
+    /// Contains the reverse geocoding widget.
+    pub reverse: Reverse,
+    /// Contains the batch geocoding widget.
+    pub batch: Batch,
+    /// The id of the subject dataset in the [`Catalog`](crate::catalog::Catalog).
+    pub subject: Option<uuid::Uuid>,
     // pub table: Option<TableView<AddressPoints, AddressPoint, String>>,
 }
 
@@ -40,29 +96,16 @@ impl Operations {
             let run = ui.button("Run");
             if run.clicked() {
                 tracing::info!("Run duplicates clicked.");
-                let duplicates = AddressPoints::from(&SpatialAddresses::from(
-                    &data.addresses[self.subject_idx].filter("duplicates")[..],
-                ));
-                let table = TableView::new(duplicates);
-                table.view();
+                if let Some(dataset) = self.subject.and_then(|id| data.catalog.get(&id)) {
+                    let duplicates = AddressPoints::from(&SpatialAddresses::from(
+                        &dataset.addresses.filter("duplicates")[..],
+                    ));
+                    let table = TableView::new(duplicates);
+                    table.view();
+                }
             }
-            egui::ComboBox::from_label("Select subject source")
-                .selected_text(format!("{}", self.subject.to_string()))
-                .show_ui(ui, |ui| {
-                    for (i, source) in AddressSource::iter().enumerate() {
-                        if ui
-                            .selectable_value(
-                                &mut self.subject,
-                                source.clone(),
-                                format!("{source}"),
-                            )
-                            .clicked()
-                        {
-                            self.subject_idx = i;
-                            info!("Subject set to {i}");
-                        }
-                    }
-                });
+            data.catalog
+                .combo(ui, "Select subject dataset", &mut self.subject);
         });
         // if let Some(t) = &mut self.table {
         //     t.table(ui);
@@ -81,6 +124,46 @@ impl Operations {
         self.lexis.visible
     }
 
+    pub fn matrix_visible(&self) -> bool {
+        self.matrix.visible
+    }
+
+    pub fn audit_visible(&self) -> bool {
+        self.audit.visible
+    }
+
+    pub fn editor_visible(&self) -> bool {
+        self.editor.visible
+    }
+
+    pub fn master_visible(&self) -> bool {
+        self.master.visible
+    }
+
+    pub fn geocode_visible(&self) -> bool {
+        self.geocode.visible
+    }
+
+    pub fn reverse_visible(&self) -> bool {
+        self.reverse.visible
+    }
+
+    pub fn batch_visible(&self) -> bool {
+        self.batch.visible
+    }
+
+    pub fn streets_visible(&self) -> bool {
+        self.streets.visible
+    }
+
+    pub fn spatial_visible(&self) -> bool {
+        self.spatial.visible
+    }
+
+    pub fn validation_visible(&self) -> bool {
+        self.validation.visible
+    }
+
     pub fn drift_visible(&self) -> bool {
         self.drift
     }
@@ -101,6 +184,68 @@ impl Operations {
         self.lexis.visible = !self.lexis.visible;
     }
 
+    pub fn toggle_matrix(&mut self) {
+        self.matrix.toggle();
+    }
+
+    pub fn toggle_editor(&mut self) {
+        self.editor.toggle();
+    }
+
+    pub fn toggle_audit(&mut self) {
+        self.audit.toggle();
+    }
+
+    pub fn toggle_validation(&mut self) {
+        self.validation.toggle();
+    }
+
+    pub fn toggle_spatial(&mut self) {
+        self.spatial.toggle();
+    }
+
+    pub fn toggle_streets(&mut self) {
+        self.streets.toggle();
+    }
+
+    pub fn toggle_master(&mut self) {
+        self.master.toggle();
+    }
+
+    pub fn toggle_geocode(&mut self) {
+        self.geocode.toggle();
+    }
+
+    pub fn toggle_reverse(&mut self) {
+        self.reverse.toggle();
+    }
+
+    pub fn toggle_batch(&mut self) {
+        self.batch.toggle();
+    }
+
+    /// Opens the editor on the dataset under validation, searching for the address labeled
+    /// `label`.  Unsaved edits to another dataset are kept, and the editor is left as is.
+    pub fn inspect(&mut self, label: &str, data: &data::Data) {
+        if self.editor.dataset != self.validation.dataset {
+            if !self.editor.changes.is_empty() {
+                info!("Save or export the open edits before editing another dataset.");
+                return;
+            }
+            self.editor.open(self.validation.dataset, data);
+        }
+        self.editor.reopen(data);
+        if let Some(t) = &mut self.editor.table {
+            t.search = label.to_string();
+        }
+        self.editor.visible = true;
+    }
+
+    /// Returns true while an operation is running in the background.
+    pub fn busy(&self) -> bool {
+        self.batch.running()
+    }
+
     pub fn toggle_drift(&mut self) {
         self.drift = !self.drift;
     }
@@ -135,13 +280,37 @@ impl Operations {
                         tracing::info!("Toast sent.");
                     }
 
-                    if data.addresses.len() > 0 {
-                        for (i, address) in data.addresses.iter().enumerate() {
-                            ui.horizontal(|ui| {
-                                ui.label(format!("{}", data.address_sources[i]));
+                    if !data.catalog.is_empty() {
+                        let mut remove = None;
+                        egui::Grid::new("catalog").striped(true).show(ui, |ui| {
+                            ui.label("");
+                            ui.strong("Label");
+                            ui.strong("Source");
+                            ui.strong("File");
+                            ui.strong("Loaded");
+                            ui.strong("Records");
+                            ui.strong("Hash");
+                            ui.label("");
+                            ui.end_row();
+                            for dataset in data.catalog.iter_mut() {
                                 ui.label(egui::RichText::new("■").color(egui::Color32::GREEN));
-                                ui.label(format!("{} records", address.len()));
-                            });
+                                ui.text_edit_singleline(&mut dataset.label);
+                                ui.label(dataset.source.to_string());
+                                ui.label(&dataset.path);
+                                ui.label(dataset.loaded_local());
+                                ui.label(format!("{}", dataset.records));
+                                ui.label(dataset.short_hash()).on_hover_text(&dataset.hash);
+                                if ui.button("Remove").clicked() {
+                                    remove = Some(dataset.id);
+                                }
+                                ui.end_row();
+                            }
+                        });
+                        if let Some(id) = remove {
+                            if let Some(dataset) = data.catalog.remove(&id) {
+                                let summary = format!("Removed dataset {}.", dataset.label);
+                                audit::record(AuditKind::Edit, Some(id), &summary);
+                            }
                         }
                     } else {
                         ui.horizontal(|ui| {
@@ -174,15 +343,27 @@ impl Operations {
 
 #[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Compare {
-    pub subject: AddressSource,
-    pub subject_idx: usize,
-    pub target: AddressSource,
-    pub target_idx: usize,
-    pub table: Option<TableView<MatchRecords, MatchRecord, String>>,
+    /// The id of the subject dataset in the [`Catalog`](crate::catalog::Catalog).
+    pub subject: Option<uuid::Uuid>,
+    /// The id of the target dataset in the [`Catalog`](crate::catalog::Catalog).
+    pub target: Option<uuid::Uuid>,
+    pub table: Option<TableView<CompareRecords, CompareRecord, String>>,
     pub visible: bool,
-    pub status: Option<MatchStatus>,
-    pub status_pkg: Option<MatchStatus>,
-    pub package: Option<TableView<MatchRecords, MatchRecord, String>>,
+    pub status: Option<CompareStatus>,
+    pub status_pkg: Option<CompareStatus>,
+    pub package: Option<TableView<CompareRecords, CompareRecord, String>>,
+    /// The `fuzzy` field enables fuzzy matching of addresses missing from the target.
+    pub fuzzy: bool,
+    /// The `fuzzy_config` field holds the acceptance threshold and weights for fuzzy matching.
+    pub fuzzy_config: FuzzyConfig,
+    /// The `history` field holds the trend view of past comparison runs, loaded on demand.
+    pub history: Option<TableView<RunTrend, RunSummary, String>>,
+    // The label index of the compared datasets, keyed by dataset id and record count.
+    #[serde(skip)]
+    pairs: Option<((uuid::Uuid, usize, uuid::Uuid, usize), Arc<PairIndex>)>,
+    // The pair shown in the detail panel, keyed by the id of the record.
+    #[serde(skip)]
+    detail: Option<(uuid::Uuid, Option<(SpatialAddress, SpatialAddress)>)>,
 }
 
 impl Compare {
@@ -191,6 +372,7 @@ impl Compare {
         ui: &mut egui::Ui,
         parent_tree: &mut focus::Tree,
         data: &mut data::Data,
+        master: &StreetMaster,
     ) {
         let mut tree = focus::Tree::new();
         let parent_node = tree.with_new_window();
@@ -200,7 +382,7 @@ impl Compare {
             // Register button with focus tree.
             tree.focusable(&run);
             if run.clicked() {
-                self.run(data);
+                self.run(data, master);
             }
 
             let save = ui.button("Save");
@@ -217,7 +399,7 @@ impl Compare {
                     tracing::info!("Compare id: {:?}", run.id);
                     if id == run.id {
                         tracing::info!("Run compare button in focus.");
-                        self.run(data);
+                        self.run(data, master);
                         // Clear the `enter` field after taking action.
                         parent_tree.enter = None;
                     }
@@ -231,39 +413,27 @@ impl Compare {
             }
         });
         ui.push_id("subject", |ui| {
-            egui::ComboBox::from_label("Select subject source")
-                .selected_text(self.subject.to_string())
-                .show_ui(ui, |ui| {
-                    for (i, source) in AddressSource::iter().enumerate() {
-                        if ui
-                            .selectable_value(&mut self.subject, source.clone(), source.to_string())
-                            .clicked()
-                        {
-                            self.subject_idx = i;
-                            info!("Subject set to {i}");
-                        }
-                    }
-                });
+            data.catalog
+                .combo(ui, "Select subject dataset", &mut self.subject);
         });
         ui.push_id("target", |ui| {
-            egui::ComboBox::from_label("Select comparison source")
-                .selected_text(self.target.to_string())
-                .show_ui(ui, |ui| {
-                    for (i, target) in AddressSource::iter().enumerate() {
-                        if ui
-                            .selectable_value(&mut self.target, target.clone(), target.to_string())
-                            .clicked()
-                        {
-                            self.target_idx = i;
-                            info!("Target set to {i}");
-                        }
-                    }
-                });
+            data.catalog
+                .combo(ui, "Select comparison dataset", &mut self.target);
+        });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.fuzzy, "Fuzzy matching");
+            ui.add_enabled(
+                self.fuzzy,
+                egui::Slider::new(&mut self.fuzzy_config.threshold, 0.5..=1.0)
+                    .text("Acceptance threshold"),
+            );
         });
         self.filter_panel(ui);
         if let Some(t) = &mut self.table {
             t.table(ui);
         }
+        self.detail_panel(ui, data);
+        self.history_panel(ui, data);
         if parent_tree.contains_new(&tree) {
             parent_tree.graft(tree);
             tracing::info!("Compare tree added.");
@@ -278,21 +448,28 @@ impl Compare {
                     .radio_value(&mut t.filter, Some("matching".to_string()), "Matching")
                     .clicked()
                 {
-                    t.view = t.data.clone().filter("matching");
+                    t.view = t.data.clone().filter(&"matching".to_string());
                     t.package = Some(t.view.clone());
                 };
                 if ui
                     .radio_value(&mut t.filter, Some("divergent".to_string()), "Divergent")
                     .clicked()
                 {
-                    t.view = t.data.clone().filter("divergent");
+                    t.view = t.data.clone().filter(&"divergent".to_string());
+                    t.package = Some(t.view.clone());
+                };
+                if ui
+                    .radio_value(&mut t.filter, Some("probable".to_string()), "Probable")
+                    .clicked()
+                {
+                    t.view = t.data.clone().filter(&"probable".to_string());
                     t.package = Some(t.view.clone());
                 };
                 if ui
                     .radio_value(&mut t.filter, Some("missing".to_string()), "Missing")
                     .clicked()
                 {
-                    t.view = t.data.clone().filter("missing");
+                    t.view = t.data.clone().filter(&"missing".to_string());
                     t.package = Some(t.view.clone());
                 };
                 if ui.radio_value(&mut t.filter, None, "None").clicked() {
@@ -300,16 +477,253 @@ impl Compare {
                     t.package = Some(t.view.clone());
                 };
             });
+            ui.horizontal_wrapped(|ui| {
+                ui.label("Divergence:");
+                for reason in DivergenceReason::iter() {
+                    let filter = format!("reason:{:?}", reason).to_lowercase();
+                    if ui
+                        .radio_value(&mut t.filter, Some(filter.clone()), reason.to_string())
+                        .clicked()
+                    {
+                        t.view = t.data.clone().filter(&filter);
+                        t.package = Some(t.view.clone());
+                    };
+                }
+            });
+        }
+    }
+
+    /// The record in focus in the comparison table, or the selected record if only one is
+    /// selected.
+    pub fn selected(&self) -> Option<CompareRecord> {
+        let t = self.table.as_ref()?;
+        let id = match t.row_select {
+            Some(id) => id,
+            None if t.selection.len() == 1 => *t.selection.iter().next()?,
+            None => return None,
+        };
+        t.view.iter().find(|v| v.id() == id).cloned()
+    }
+
+    /// Finds the subject and target addresses of `record`.  The pair is cached until the
+    /// selection changes, and looked up through a label index of the compared datasets.
+    fn pair(
+        &mut self,
+        record: &CompareRecord,
+        subject: &Dataset,
+        target: &Dataset,
+    ) -> Option<(SpatialAddress, SpatialAddress)> {
+        if let Some((id, pair)) = &self.detail {
+            if *id == record.id() {
+                return pair.clone();
+            }
         }
+        let key = (
+            subject.id,
+            subject.addresses.len(),
+            target.id,
+            target.addresses.len(),
+        );
+        let index = match &self.pairs {
+            Some((cached, index)) if *cached == key => index.clone(),
+            _ => {
+                let index = Arc::new(PairIndex::new(&subject.addresses, &target.addresses));
+                self.pairs = Some((key, index.clone()));
+                index
+            }
+        };
+        let pair = index
+            .pair(record, &subject.addresses, &target.addresses)
+            .map(|(a, b)| (a.clone(), b.clone()));
+        self.detail = Some((record.id(), pair.clone()));
+        pair
+    }
+
+    /// Shows the subject and target addresses of the selected divergent or probable record side by
+    /// side, highlighting the fields that differ.
+    pub fn detail_panel(&mut self, ui: &mut egui::Ui, data: &data::Data) {
+        let Some(record) = self.selected() else {
+            return;
+        };
+        if record.target_label().is_none() {
+            return;
+        }
+        let (Some(subject), Some(target)) = (
+            self.subject.and_then(|id| data.catalog.get(&id)),
+            self.target.and_then(|id| data.catalog.get(&id)),
+        ) else {
+            return;
+        };
+        let Some((a, b)) = self.pair(&record, subject, target) else {
+            return;
+        };
+        let (a, b) = (&a, &b);
+        let highlight = egui::Color32::from_rgb(219, 110, 0);
+        egui::CollapsingHeader::new("Detail")
+            .default_open(true)
+            .show(ui, |ui| {
+                // Character-level diff of the labels, so small spelling differences stand out.
+                let label_a = a.label().chars().collect::<Vec<char>>();
+                let label_b = b.label().chars().collect::<Vec<char>>();
+                ui.horizontal_wrapped(|ui| {
+                    ui.spacing_mut().item_spacing.x = 0.0;
+                    for op in prettydiff::basic::diff(&label_a, &label_b) {
+                        match op {
+                            DiffOp::Equal(v) => {
+                                ui.label(v.iter().collect::<String>());
+                            }
+                            DiffOp::Remove(v) => {
+                                ui.label(
+                                    egui::RichText::new(v.iter().collect::<String>())
+                                        .strikethrough()
+                                        .color(egui::Color32::RED),
+                                );
+                            }
+                            DiffOp::Insert(v) => {
+                                ui.label(
+                                    egui::RichText::new(v.iter().collect::<String>())
+                                        .underline()
+                                        .color(egui::Color32::GREEN),
+                                );
+                            }
+                            DiffOp::Replace(old, new) => {
+                                ui.label(
+                                    egui::RichText::new(old.iter().collect::<String>())
+                                        .strikethrough()
+                                        .color(egui::Color32::RED),
+                                );
+                                ui.label(
+                                    egui::RichText::new(new.iter().collect::<String>())
+                                        .underline()
+                                        .color(egui::Color32::GREEN),
+                                );
+                            }
+                        }
+                    }
+                });
+                egui::Grid::new("compare_detail")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Field");
+                        ui.strong(&subject.label);
+                        ui.strong(&target.label);
+                        ui.end_row();
+                        for diff in FieldDiff::compare(a, b) {
+                            if diff.differs {
+                                ui.colored_label(highlight, &diff.field);
+                                ui.colored_label(highlight, &diff.subject);
+                                ui.colored_label(highlight, &diff.target);
+                            } else {
+                                ui.label(&diff.field);
+                                ui.label(&diff.subject);
+                                ui.label(&diff.target);
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
     }
 
     pub fn toggle(&mut self) {
         self.visible = !self.visible;
     }
 
-    pub fn run(&mut self, data: &mut data::Data) {
-        let table = Some(data.compare(&self));
-        self.table = table;
+    pub fn run(&mut self, data: &mut data::Data, master: &StreetMaster) {
+        let Some(table) = data.compare(self, master) else {
+            info!("Select subject and comparison datasets.");
+            return;
+        };
+        self.detail = None;
+        let (Some(subject), Some(target)) = (
+            self.subject.and_then(|id| data.catalog.get(&id)),
+            self.target.and_then(|id| data.catalog.get(&id)),
+        ) else {
+            return;
+        };
+        // Runs are grouped by source type, so successive deliveries of the same sources trend
+        // together.
+        let run = Run::new(
+            &subject.source.to_string(),
+            &target.source.to_string(),
+            &subject.path,
+            &target.path,
+            self.fuzzy,
+            &table.data,
+        );
+        let summary = format!(
+            "Compared {} with {}: {} matching, {} divergent, {} probable, {} missing.",
+            subject.label, target.label, run.matching, run.divergent, run.probable, run.missing
+        );
+        audit::record(AuditKind::Compare, self.subject, &summary);
+        match RunHistory::record(run, &table.data) {
+            Ok(history) => self.set_history(&history),
+            Err(e) => tracing::warn!("Could not record run: {}", e.to_string()),
+        }
+        self.table = Some(table);
+    }
+
+    fn set_history(&mut self, history: &RunHistory) {
+        let config = TableConfig::new().striped();
+        self.history = Some(TableView::with_config(history.trend(), config));
+    }
+
+    /// Shows past comparison runs, with the change in divergent and missing counts from the
+    /// previous delivery of the same sources.  The run in focus can be reloaded into the
+    /// comparison table.
+    pub fn history_panel(&mut self, ui: &mut egui::Ui, data: &data::Data) {
+        egui::CollapsingHeader::new("History").show(ui, |ui| {
+            if self.history.is_none() {
+                let history = RunHistory::local().unwrap_or_else(|e| {
+                    tracing::warn!("Could not read run history: {}", e.to_string());
+                    RunHistory::default()
+                });
+                self.set_history(&history);
+            }
+            let mut reload = None;
+            if let Some(t) = &mut self.history {
+                ui.horizontal(|ui| {
+                    let source =
+                        |id: &Option<uuid::Uuid>| match id.and_then(|v| data.catalog.get(&v)) {
+                            Some(dataset) => dataset.source.to_string(),
+                            None => "".to_string(),
+                        };
+                    let sources = format!("{} → {}", source(&self.subject), source(&self.target));
+                    if ui
+                        .radio_value(&mut t.filter, Some(sources.clone()), "Selected sources")
+                        .clicked()
+                    {
+                        t.view = t.data.clone().filter(&sources);
+                    }
+                    if ui.radio_value(&mut t.filter, None, "All runs").clicked() {
+                        t.view = t.data.clone();
+                    }
+                    if ui
+                        .add_enabled(t.row_select.is_some(), egui::Button::new("Reload"))
+                        .clicked()
+                    {
+                        reload = t.row_select;
+                    }
+                });
+                ui.push_id("history", |ui| {
+                    t.table(ui);
+                });
+            }
+            if let Some(id) = reload {
+                let history = RunHistory::local().unwrap_or_default();
+                if let Some(run) = history.run(&id) {
+                    match run.records() {
+                        Ok(records) => {
+                            info!("Reloading run from {}.", run.time);
+                            let config = TableConfig::new().with_search().with_slider();
+                            self.table = Some(TableView::with_config(records, config));
+                            self.fuzzy = run.fuzzy;
+                            self.detail = None;
+                        }
+                        Err(e) => tracing::warn!("Could not reload run: {}", e.to_string()),
+                    }
+                }
+            }
+        });
     }
 
     /// Saves the comparison table to a csv file on the local machine.
@@ -329,136 +743,2939 @@ impl Compare {
         if let Some(path) = file {
             if let Some(mut view) = self.table.clone() {
                 info!("Saving address comparison table.");
+                let summary = format!("Exported address comparison to {}.", path.display());
                 // The `view` field in a `TableView` holds a view of the table data with
                 // filters applied.
                 view.view.to_csv(path).unwrap();
+                audit::record(AuditKind::Export, self.subject, &summary);
             }
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
-pub struct Lexis {
-    pub boundary: Boundary,
-    pub boundary_view: BoundaryView,
-    pub addresses: Vec<SpatialAddresses>,
-    pub sources: Vec<AddressSource>,
-    pub selected: usize,
-    pub view: Option<TableView<LexisNexis, LexisNexisItem, String>>,
-    pub package: Option<TableView<LexisNexis, LexisNexisItem, String>>,
-    pub address_pkg: Option<Vec<SpatialAddresses>>,
-    pub boundary_pkg: Option<BoundaryView>,
-    visible: bool,
+/// The `Matrix` struct holds the N-way comparison widget, comparing any number of loaded address
+/// sources at once.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Matrix {
+    /// The `excluded` field holds the ids of catalog datasets left out of the comparison.  Newly
+    /// loaded datasets are included by default.
+    pub excluded: HashSet<uuid::Uuid>,
+    /// The `sources` field holds the sources included in the last run.
+    pub sources: Vec<Source>,
+    pub table: Option<TableView<PresenceMatrix, Presence, PresenceFilter>>,
+    pub summary: Option<TableView<PresenceSummary, Combination, String>>,
+    pub visible: bool,
 }
 
-impl Lexis {
-    pub fn try_default() -> Clean<Self> {
-        let boundary = Boundary::load("data/lexis_nexis_boundary.data")?;
-        if let Some(boundary_view) = BoundaryView::from_shp(&boundary) {
-            Ok(Self {
-                boundary,
-                boundary_view: boundary_view.clone(),
-                addresses: Default::default(),
-                sources: Default::default(),
-                selected: Default::default(),
-                view: None,
-                package: None,
-                address_pkg: None,
-                boundary_pkg: Some(boundary_view),
-                visible: false,
-            })
-        } else {
-            Err(Bandage::Hint(
-                "Could not load lexis boundary view.".to_string(),
-            ))
-        }
-    }
-
+impl Matrix {
     pub fn combo(&mut self, ui: &mut egui::Ui, parent_tree: &mut focus::Tree, data: &data::Data) {
-        if self.addresses.len() != data.addresses.len() {
-            self.addresses = data.addresses.clone();
-            self.sources = data.address_sources.clone();
-        }
         let mut tree = focus::Tree::new();
         let parent_node = tree.with_new_window();
-        if self.sources.is_empty() {
-            ui.label("No address data loaded.");
-        } else {
-            egui::ComboBox::from_label("Select address source")
-                .selected_text(format!("{}", self.sources[self.selected]))
-                .show_ui(ui, |ui| {
-                    for (i, source) in self.sources.iter().enumerate() {
-                        if ui
-                            .selectable_label(i == self.selected, format!("{source}"))
-                            .clicked()
-                        {
-                            self.selected = i;
-                            info!("Subject set to {i}");
-                        }
+        ui.horizontal(|ui| {
+            let run = ui.button("Run");
+            tree.with_new_leaf(parent_node, &run);
+            // Register button with focus tree.
+            tree.focusable(&run);
+            if run.clicked() {
+                self.run(data);
+            }
+
+            let save = ui.add_enabled(self.table.is_some(), egui::Button::new("Save"));
+            tree.with_new_leaf(parent_node, &save);
+            // Register button with focus tree.
+            tree.focusable(&save);
+            if save.clicked() {
+                self.save();
+            }
+            if parent_tree.enter.is_some() {
+                tracing::info!("Enter detected in matrix widget.");
+                if let Some(id) = parent_tree.current_leaf() {
+                    if id == run.id {
+                        tracing::info!("Run matrix button in focus.");
+                        self.run(data);
+                        // Clear the `enter` field after taking action.
+                        parent_tree.enter = None;
+                    }
+                    if id == save.id {
+                        tracing::info!("Save matrix button in focus.");
+                        self.save();
+                        // Clear the `enter` field after taking action.
+                        parent_tree.enter = None;
                     }
-                });
-            ui.horizontal(|ui| {
-                let run = ui.button("Run");
-                tree.with_new_leaf(parent_node, &run);
-                // Register button with focus tree.
-                tree.focusable(&run);
-                if run.clicked() {
-                    self.run();
-                }
-                let save = ui.button("Save");
-                tree.with_new_leaf(parent_node, &save);
-                // Register button with focus tree.
-                tree.focusable(&save);
-                if save.clicked() {
-                    self.save();
                 }
-                if parent_tree.enter.is_some() {
-                    tracing::info!("Enter detected in lexis widget.");
-                    if let Some(id) = parent_tree.current_leaf() {
-                        tracing::info!("Current focus: {:?}", id);
-                        tracing::info!("Run lexis id: {:?}", run.id);
-                        if id == run.id {
-                            tracing::info!("Run lexis button in focus.");
-                            self.run();
-                            // Clear the `enter` field after taking action.
-                            parent_tree.enter = None;
-                        }
-                        if id == save.id {
-                            tracing::info!("Save lexis button in focus.");
-                            self.save();
-                            // Clear the `enter` field after taking action.
-                            parent_tree.enter = None;
-                        }
+            }
+        });
+        if data.catalog.is_empty() {
+            ui.label("No data loaded.");
+        }
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Datasets:");
+            for dataset in data.catalog.iter() {
+                let mut include = !self.excluded.contains(&dataset.id);
+                if ui.checkbox(&mut include, &dataset.label).changed() {
+                    if include {
+                        self.excluded.remove(&dataset.id);
+                    } else {
+                        self.excluded.insert(dataset.id);
                     }
                 }
+            }
+        });
+        self.summary_panel(ui);
+        self.filter_panel(ui);
+        if let Some(t) = &mut self.table {
+            ui.push_id("presence", |ui| {
+                t.table(ui);
             });
         }
-        if let Some(view) = &mut self.view {
-            view.table(ui);
-        }
         if parent_tree.contains_new(&tree) {
             parent_tree.graft(tree);
-            tracing::info!("LexisNexis tree added.");
+            tracing::info!("Matrix tree added.");
         }
     }
 
-    /// Functionality for the run button in the Lexis Nexis widget.
-    pub fn run(&mut self) {
-        tracing::info!("Running LexisNexis.");
-        // `records` and `other` will hold addresses within and without the LexisNexis boundary.
-        // `records` are addresses inside City of Grants Pass service area.
-        let mut records = Vec::new();
-        // `other` are addresses outside the City of Grants Pass service area.
-        let mut other = Vec::new();
-        // `target` are the selected addresses to analzye.
-        let target = &self.addresses[self.selected];
-        // Convert to AddressPoints and then geo::geometry::Point type to access the spatial
-        // operation `contains` in the `geo` crate.
-        let ap = AddressPoints::from(target);
-        let gp = ap
-            .par_iter()
-            .map(|v| v.geo_point())
-            .collect::<Vec<geo::geometry::Point>>();
+    /// Shows per-source totals and the count of addresses in each combination of sources.
+    pub fn summary_panel(&mut self, ui: &mut egui::Ui) {
+        if let Some(t) = &self.table {
+            egui::Grid::new("source_totals")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("Source");
+                    ui.label("Present");
+                    ui.label("Missing");
+                    ui.end_row();
+                    for source in &self.sources {
+                        let (present, missing) = t.data.totals(&source.id);
+                        ui.label(&source.name);
+                        ui.label(format!("{present}"));
+                        ui.label(format!("{missing}"));
+                        ui.end_row();
+                    }
+                });
+        }
+        if let Some(s) = &mut self.summary {
+            egui::CollapsingHeader::new("Combinations")
+                .default_open(true)
+                .show(ui, |ui| {
+                    // Bars give an UpSet-style view of the share of addresses in each combination.
+                    for combination in s.data.iter() {
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::ProgressBar::new(combination.share as f32)
+                                    .desired_width(120.0)
+                                    .text(format!("{}", combination.count)),
+                            );
+                            ui.label(&combination.sources);
+                        });
+                    }
+                    ui.push_id("combinations", |ui| {
+                        s.table(ui);
+                    });
+                });
+        }
+    }
+
+    pub fn filter_panel(&mut self, ui: &mut egui::Ui) {
+        if let Some(t) = &mut self.table {
+            ui.horizontal_wrapped(|ui| {
+                ui.label("Missing from:");
+                for source in &self.sources {
+                    let filter = PresenceFilter::Missing(source.id);
+                    if ui
+                        .radio_value(&mut t.filter, Some(filter.clone()), &source.name)
+                        .clicked()
+                    {
+                        t.view = t.data.clone().filter(&filter);
+                        t.package = Some(t.view.clone());
+                    };
+                }
+                let filter = PresenceFilter::Disagreements;
+                if ui
+                    .radio_value(&mut t.filter, Some(filter.clone()), "Disagreements")
+                    .clicked()
+                {
+                    t.view = t.data.clone().filter(&filter);
+                    t.package = Some(t.view.clone());
+                };
+                if ui.radio_value(&mut t.filter, None, "None").clicked() {
+                    t.view = t.data.clone();
+                    t.package = Some(t.view.clone());
+                };
+            });
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Compares the selected sources, replacing the matrix and summary tables.
+    pub fn run(&mut self, data: &data::Data) {
+        let sources = data
+            .catalog
+            .iter()
+            .filter(|v| !self.excluded.contains(&v.id))
+            .map(|v| (Source::new(v.id, &v.label), &*v.addresses))
+            .collect::<Vec<(Source, &SpatialAddresses)>>();
+        if sources.len() < 2 {
+            info!("Select at least two sources to compare.");
+            return;
+        }
+        self.sources = sources
+            .iter()
+            .map(|(source, _)| source.clone())
+            .collect::<Vec<Source>>();
+        let matrix = PresenceMatrix::compare(&sources);
+        let summary = matrix.combinations();
+        let config = TableConfig::new().with_search().striped();
+        self.summary = Some(TableView::with_config(
+            summary,
+            TableConfig::new().striped(),
+        ));
+        self.table = Some(TableView::with_config(matrix, config));
+    }
+
+    /// Saves the filtered matrix to a csv file on the local machine.
+    pub fn save(&self) {
+        let path = env::current_dir().expect("Could not read current directory.");
+        let file = rfd::FileDialog::new()
+            .add_filter("csv", &["csv"])
+            .set_directory(&path)
+            .set_file_name("address_presence.csv")
+            .save_file();
+        if let Some(path) = file {
+            if let Some(view) = &self.table {
+                info!("Saving address presence matrix.");
+                let summary = format!(
+                    "Exported presence matrix of {} to {}.",
+                    self.sources
+                        .iter()
+                        .map(|v| v.name.as_str())
+                        .collect::<Vec<&str>>()
+                        .join(", "),
+                    path.display()
+                );
+                match view.view.to_csv(path) {
+                    Ok(_) => audit::record(AuditKind::Export, None, &summary),
+                    Err(e) => tracing::warn!("Could not save matrix: {}", e.to_string()),
+                }
+            }
+        }
+    }
+}
+
+/// The `Editor` struct holds the attribute editing widget.  Edits to the records of a dataset are
+/// validated, recorded in a [`ChangeSet`], and saved to the catalog as a new dataset.  Only the
+/// dataset id, change set and lifecycle are saved; the tables are rebuilt from the catalog when
+/// the editor is next shown.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Editor {
+    /// The id of the dataset open for editing in the [`Catalog`](crate::catalog::Catalog).
+    pub dataset: Option<uuid::Uuid>,
+    /// The `original` field holds the records of the dataset as opened, before any edits.
+    #[serde(skip)]
+    pub original: AddressPoints,
+    #[serde(skip)]
+    pub table: Option<TableView<AddressPoints, AddressPoint, String>>,
+    /// The `changes` field holds the edits made since the dataset was opened.
+    pub changes: ChangeSet,
+    /// The `log` field displays the change set.
+    #[serde(skip)]
+    pub log: Option<TableView<ChangeSet, Change, String>>,
+    /// The `column` field holds the column set by a bulk edit.
+    pub column: AddressColumns,
+    /// The `value` field holds the value set by a bulk edit.
+    pub value: String,
+    /// The `message` field holds the validation error from the last edit, if any.
+    #[serde(skip)]
+    pub message: Option<String>,
+    /// The `adding` field indicates the add address tool is active, so a click on the map places
+    /// a new address.
+    #[serde(skip)]
+    pub adding: bool,
+    /// The `draft` field holds the form for a new address placed on the map.
+    #[serde(skip)]
+    pub draft: Option<Draft>,
+    /// Packages the added addresses for delivery to the map window.
+    #[serde(skip)]
+    pub package: Option<AddressPoints>,
+    /// The `lifecycle` field holds the dates and status history of the addresses.
+    pub lifecycle: Lifecycle,
+    /// The `queue` field displays the addresses awaiting a status change.
+    #[serde(skip)]
+    pub queue: Option<TableView<Queue, QueueItem, String>>,
+    /// The `effective` field holds the date used by promote, retire and schedule actions.
+    pub effective: NaiveDate,
+    /// The `expires` field holds the expiration date set on temporary addresses.
+    pub expires: NaiveDate,
+    /// The `reason` field holds the reason recorded with a status change.
+    pub reason: String,
+    pub visible: bool,
+}
+
+impl Editor {
+    pub fn combo(
+        &mut self,
+        ui: &mut egui::Ui,
+        parent_tree: &mut focus::Tree,
+        data: &mut data::Data,
+    ) {
+        self.reopen(data);
+        let mut tree = focus::Tree::new();
+        let parent_node = tree.with_new_window();
+        let mut selected = self.dataset;
+        ui.horizontal(|ui| {
+            if data.catalog.combo(ui, "Dataset", &mut selected) {
+                self.open(selected, data);
+            }
+
+            let save = ui.add_enabled(
+                !self.changes.is_empty(),
+                egui::Button::new("Save as Dataset"),
+            );
+            tree.with_new_leaf(parent_node, &save);
+            // Register button with focus tree.
+            tree.focusable(&save);
+            if save.clicked() {
+                self.commit(data);
+            }
+
+            let export = ui.add_enabled(
+                !self.changes.is_empty(),
+                egui::Button::new("Export Changes"),
+            );
+            tree.with_new_leaf(parent_node, &export);
+            // Register button with focus tree.
+            tree.focusable(&export);
+            if export.clicked() {
+                self.export();
+            }
+            if parent_tree.enter.is_some() {
+                tracing::info!("Enter detected in editor widget.");
+                if let Some(id) = parent_tree.current_leaf() {
+                    if id == save.id {
+                        tracing::info!("Save changes button in focus.");
+                        self.commit(data);
+                        // Clear the `enter` field after taking action.
+                        parent_tree.enter = None;
+                    }
+                    if id == export.id {
+                        tracing::info!("Export changes button in focus.");
+                        self.export();
+                        // Clear the `enter` field after taking action.
+                        parent_tree.enter = None;
+                    }
+                }
+            }
+        });
+        if self.table.is_some() {
+            self.bulk_panel(ui);
+            ui.horizontal(|ui| {
+                if ui.selectable_label(self.adding, "Add Address").clicked() {
+                    self.adding = !self.adding;
+                }
+                if self.adding {
+                    ui.label("Click the map to place the new address.");
+                }
+            });
+            ui.label("Double click a cell to edit it, then press Enter to accept.");
+        }
+        self.draft_window(ui.ctx());
+        if let Some(message) = &self.message {
+            ui.colored_label(egui::Color32::RED, message);
+        }
+        if let Some(t) = &mut self.table {
+            ui.push_id("editor", |ui| {
+                t.table(ui);
+            });
+        }
+        self.apply_edits();
+        if self.table.is_some() {
+            egui::CollapsingHeader::new("Lifecycle").show(ui, |ui| {
+                self.lifecycle_panel(ui);
+            });
+        }
+        if let Some(log) = &mut self.log {
+            egui::CollapsingHeader::new(format!("Changes ({})", self.changes.len())).show(
+                ui,
+                |ui| {
+                    ui.push_id("changes", |ui| {
+                        log.table(ui);
+                    });
+                },
+            );
+        }
+        if parent_tree.contains_new(&tree) {
+            parent_tree.graft(tree);
+            tracing::info!("Editor tree added.");
+        }
+    }
+
+    /// Sets a column to the same value in every checked row.
+    pub fn bulk_panel(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Set");
+            egui::ComboBox::from_id_source("bulk_column")
+                .selected_text(self.column.to_string())
+                .show_ui(ui, |ui| {
+                    for column in AddressColumns::iter().filter(|v| v.editable()) {
+                        let text = column.to_string();
+                        ui.selectable_value(&mut self.column, column, text);
+                    }
+                });
+            ui.label("to");
+            ui.text_edit_singleline(&mut self.value);
+            let checked = match &self.table {
+                Some(t) => t.checks.values().filter(|v| **v).count(),
+                None => 0,
+            };
+            let apply = ui.add_enabled(
+                checked > 0 && self.column.editable(),
+                egui::Button::new(format!("Apply to {checked} checked")),
+            );
+            if apply.clicked() {
+                self.bulk_edit();
+            }
+        });
+    }
+
+    /// Queues an edit of the bulk edit column for each checked row.
+    pub fn bulk_edit(&mut self) {
+        let column = AddressColumns::iter().position(|v| v == self.column);
+        if let (Some(t), Some(column)) = (&mut self.table, column) {
+            let rows = t
+                .checks
+                .iter()
+                .filter(|(_, v)| **v)
+                .map(|(k, _)| *k)
+                .collect::<Vec<uuid::Uuid>>();
+            info!(
+                "Setting {} to {} in {} rows.",
+                self.column,
+                self.value,
+                rows.len()
+            );
+            for row in rows {
+                t.edits.push(CellEdit::new(row, column, &self.value));
+            }
+        }
+    }
+
+    /// Validates and applies the edits committed in the table, recording them in the change set.
+    pub fn apply_edits(&mut self) {
+        let Some(t) = &mut self.table else {
+            return;
+        };
+        if t.edits.is_empty() {
+            return;
+        }
+        let mut errors = Vec::new();
+        for edit in std::mem::take(&mut t.edits) {
+            match self.changes.edit(&mut t.data, &edit) {
+                Ok(true) => {
+                    if let Some(change) = self.changes.last() {
+                        audit::record(AuditKind::Edit, self.dataset, &change.summary());
+                    }
+                    sync_row(t, &edit.row);
+                }
+                Ok(false) => {}
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        self.message = match errors.len() {
+            0 => None,
+            1 => Some(errors[0].clone()),
+            n => Some(format!("{} ({} edits rejected)", errors[0], n)),
+        };
+        self.refresh_log();
+        self.refresh_queue();
+    }
+
+    /// Updates the queue of addresses awaiting a status change.
+    fn refresh_queue(&mut self) {
+        if let Some(t) = &self.table {
+            let queue = self.lifecycle.queue(&t.data, today());
+            self.queue = Some(TableView::with_config(queue, TableConfig::new().striped()));
+        }
+    }
+
+    /// Shows the lifecycle queue, with actions to promote, retire and schedule addresses.
+    pub fn lifecycle_panel(&mut self, ui: &mut egui::Ui) {
+        if let Some(q) = &mut self.queue {
+            ui.push_id("queue", |ui| {
+                q.table(ui);
+            });
+        }
+        let selected = match &self.queue {
+            Some(q) => q.selection.iter().copied().collect::<Vec<uuid::Uuid>>(),
+            None => Vec::new(),
+        };
+        egui::Grid::new("lifecycle").show(ui, |ui| {
+            ui.label("Effective");
+            ui.add(egui_extras::DatePickerButton::new(&mut self.effective).id_source("effective"));
+            ui.end_row();
+            ui.label("Expires");
+            ui.add(egui_extras::DatePickerButton::new(&mut self.expires).id_source("expires"));
+            ui.end_row();
+            ui.label("Reason");
+            ui.text_edit_singleline(&mut self.reason);
+            ui.end_row();
+        });
+        ui.horizontal(|ui| {
+            let enabled = !selected.is_empty();
+            if ui
+                .add_enabled(enabled, egui::Button::new("Promote Selected"))
+                .clicked()
+            {
+                self.transition(&selected, AddressStatus::Current);
+            }
+            if ui
+                .add_enabled(enabled, egui::Button::new("Retire Selected"))
+                .clicked()
+            {
+                self.transition(&selected, AddressStatus::Retired);
+            }
+            if ui.button("Schedule Checked").clicked() {
+                self.schedule();
+            }
+        });
+    }
+
+    /// Moves the addresses with ids in `records` to status `to`, logging the user and reason.
+    pub fn transition(&mut self, records: &[uuid::Uuid], to: AddressStatus) {
+        if self.reason.trim().is_empty() {
+            self.message = Some("Enter a reason for the status change.".to_string());
+            return;
+        }
+        let Some(t) = &mut self.table else {
+            return;
+        };
+        let mut errors = Vec::new();
+        for record in records {
+            let Some(point) = t.data.iter().find(|v| v.id == *record) else {
+                continue;
+            };
+            let from = point.address.status().clone();
+            let address = point.address.label();
+            match self.changes.status(&mut t.data, record, &to) {
+                // An address already in status `to` has nothing to record.
+                Ok(false) => {}
+                Ok(true) => {
+                    sync_row(t, record);
+                    let summary = format!("{address} moved from {from} to {to}: {}", self.reason);
+                    audit::record(AuditKind::Status, self.dataset, &summary);
+                    self.lifecycle.record(
+                        *record,
+                        &address,
+                        from,
+                        to.clone(),
+                        self.effective,
+                        &self.reason,
+                    );
+                }
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        self.message = errors.first().cloned();
+        self.refresh_log();
+        self.refresh_queue();
+    }
+
+    /// Sets the effective date of checked Pending addresses and the expiration date of checked
+    /// Temporary addresses.
+    pub fn schedule(&mut self) {
+        let Some(t) = &self.table else {
+            return;
+        };
+        for point in t.data.iter() {
+            if t.checks.get(&point.id) != Some(&true) {
+                continue;
+            }
+            let address = point.address.label();
+            let mut dates = self.lifecycle.dates(&point.id);
+            match point.address.status() {
+                AddressStatus::Pending => dates.effective = Some(self.effective),
+                AddressStatus::Temporary => dates.expires = Some(self.expires),
+                _ => continue,
+            }
+            let summary = match point.address.status() {
+                AddressStatus::Pending => {
+                    format!("{address} scheduled to take effect {}.", self.effective)
+                }
+                _ => format!("{address} scheduled to expire {}.", self.expires),
+            };
+            audit::record(AuditKind::Status, self.dataset, &summary);
+            self.lifecycle.set_dates(point.id, dates);
+        }
+        self.refresh_queue();
+    }
+
+    /// Updates the display of the change set.
+    fn refresh_log(&mut self) {
+        self.log = Some(TableView::with_config(
+            self.changes.clone(),
+            TableConfig::new().striped(),
+        ));
+    }
+
+    /// Opens the new address form for a point placed on the map at `latitude` and `longitude`.
+    pub fn place(&mut self, latitude: f64, longitude: f64) {
+        let Some(t) = &self.table else {
+            return;
+        };
+        info!("New address placed at {:.6}, {:.6}.", latitude, longitude);
+        match Draft::new(latitude, longitude, &t.data) {
+            Some(draft) => self.draft = Some(draft),
+            None => self.message = Some("No existing addresses to copy from.".to_string()),
+        }
+    }
+
+    /// Form for the new address placed on the map.
+    pub fn draft_window(&mut self, ctx: &egui::Context) {
+        let mut create = false;
+        let mut cancel = false;
+        if let Some(draft) = &mut self.draft {
+            egui::Window::new("New Address").show(ctx, |ui| {
+                ui.label(format!(
+                    "Location: {:.6}, {:.6}",
+                    draft.latitude, draft.longitude
+                ));
+                egui::Grid::new("new_address").show(ui, |ui| {
+                    for (column, value) in draft.values.iter_mut() {
+                        ui.label(column.to_string());
+                        ui.text_edit_singleline(value);
+                        ui.end_row();
+                    }
+                    ui.label("Status");
+                    ui.label("Pending");
+                    ui.end_row();
+                });
+                egui::CollapsingHeader::new("Nearby addresses").show(ui, |ui| {
+                    for label in &draft.nearby {
+                        ui.label(label);
+                    }
+                });
+                ui.horizontal(|ui| {
+                    create = ui.button("Create").clicked();
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+        }
+        if create {
+            self.create();
+        }
+        if cancel {
+            self.draft = None;
+        }
+    }
+
+    /// Adds the address on the form to the open dataset and the change set.
+    pub fn create(&mut self) {
+        let (Some(draft), Some(t)) = (&self.draft, &mut self.table) else {
+            return;
+        };
+        match draft.build() {
+            Ok(point) => {
+                t.view.push(point.clone());
+                let record = point.id;
+                self.changes.add(&mut t.data, point);
+                if let Some(change) = self.changes.last() {
+                    audit::record(AuditKind::Edit, self.dataset, &change.summary());
+                }
+                // New addresses start out Pending, so the pending age rule counts from today.
+                let mut dates = self.lifecycle.dates(&record);
+                dates.since = Some(today());
+                self.lifecycle.set_dates(record, dates);
+                self.draft = None;
+                self.adding = false;
+                self.message = None;
+                self.package = Some(self.changes.added());
+                self.refresh_log();
+                self.refresh_queue();
+            }
+            Err(e) => self.message = Some(e.to_string()),
+        }
+    }
+
+    /// Opens the dataset with id `id` for editing, discarding unsaved changes.
+    pub fn open(&mut self, id: Option<uuid::Uuid>, data: &data::Data) {
+        let Some(dataset) = id.and_then(|v| data.catalog.get(&v)) else {
+            return;
+        };
+        if !self.changes.is_empty() {
+            tracing::warn!("Discarding {} unsaved changes.", self.changes.len());
+        }
+        self.dataset = id;
+        self.original = dataset.points();
+        self.table = Some(TableView::with_config(
+            self.original.clone(),
+            Self::table_config(),
+        ));
+        self.changes = ChangeSet::default();
+        self.log = None;
+        self.message = None;
+        self.effective = today();
+        self.expires = today();
+        self.refresh_queue();
+    }
+
+    /// Rebuilds the tables from the catalog and the change set, after the editor is read from a
+    /// project or restored from the undo history without them.
+    pub fn reopen(&mut self, data: &data::Data) {
+        if self.table.is_some() {
+            return;
+        }
+        let Some(dataset) = self.dataset.and_then(|v| data.catalog.get(&v)) else {
+            return;
+        };
+        self.original = dataset.points();
+        let mut points = self.original.clone();
+        for change in self.changes.iter() {
+            if let Err(e) = change.apply(&mut points) {
+                tracing::warn!("Could not reapply change: {}", e.to_string());
+            }
+        }
+        self.table = Some(TableView::with_config(points, Self::table_config()));
+        self.package = Some(self.changes.added());
+        if !self.changes.is_empty() {
+            self.refresh_log();
+        }
+        self.refresh_queue();
+    }
+
+    /// Restores the open dataset, change set and lifecycle from the undo history.  If the same
+    /// dataset is open, the changes are reapplied to the records as opened and the table keeps
+    /// its search, sort and selection.  Otherwise the table is dropped, for [`Self::reopen`] to
+    /// rebuild.
+    pub fn restore(
+        &mut self,
+        dataset: Option<uuid::Uuid>,
+        changes: ChangeSet,
+        lifecycle: Lifecycle,
+    ) {
+        if self.dataset == dataset && self.changes == changes && self.lifecycle == lifecycle {
+            return;
+        }
+        if self.dataset != dataset {
+            self.dataset = dataset;
+            self.changes = changes;
+            self.lifecycle = lifecycle;
+            self.table = None;
+            self.log = None;
+            self.queue = None;
+            return;
+        }
+        self.changes = changes;
+        self.lifecycle = lifecycle;
+        if let Some(table) = &mut self.table {
+            let mut points = self.original.clone();
+            for change in self.changes.iter() {
+                if let Err(e) = change.apply(&mut points) {
+                    tracing::warn!("Could not reapply change: {}", e.to_string());
+                }
+            }
+            table.data = points;
+            // Keep the rows in view where they are, dropping removed records and appending
+            // restored ones.
+            let ids = table
+                .data
+                .iter()
+                .map(|v| v.id)
+                .collect::<HashSet<uuid::Uuid>>();
+            table.view.retain(|v| ids.contains(&v.id));
+            let shown = table
+                .view
+                .iter()
+                .map(|v| v.id)
+                .collect::<HashSet<uuid::Uuid>>();
+            let added = table
+                .data
+                .iter()
+                .filter(|v| !shown.contains(&v.id))
+                .cloned()
+                .collect::<Vec<AddressPoint>>();
+            for id in shown {
+                sync_row(table, &id);
+            }
+            table.view.extend(added);
+        }
+        self.package = Some(self.changes.added());
+        self.log = None;
+        if !self.changes.is_empty() {
+            self.refresh_log();
+        }
+        self.refresh_queue();
+    }
+
+    fn table_config() -> TableConfig {
+        TableConfig::new()
+            .checked()
+            .editable()
+            .resizable()
+            .with_search()
+            .striped()
+            .with_slider()
+    }
+
+    /// Applies the change set to the records as opened, adding the result to the catalog as a new
+    /// dataset and opening it for further edits.
+    pub fn commit(&mut self, data: &mut data::Data) {
+        let Some(dataset) = self.dataset.and_then(|id| data.catalog.get(&id)) else {
+            return;
+        };
+        let edited = self
+            .changes
+            .apply(&self.original)
+            .and_then(|addresses| dataset.edited(addresses));
+        match edited {
+            Ok(edited) => {
+                let summary = format!(
+                    "Saved {} changes to {} as {}.",
+                    self.changes.len(),
+                    dataset.label,
+                    edited.label
+                );
+                info!("{}", summary);
+                audit::record(AuditKind::Edit, Some(edited.id), &summary);
+                // The edited records keep their order, with added records last, so carry the
+                // dates of each record over to its id in the new dataset.
+                if let Some(t) = &self.table {
+                    let ids = t
+                        .data
+                        .iter()
+                        .enumerate()
+                        .map(|(i, v)| (v.id, edited.record_id(i)))
+                        .collect::<Vec<(uuid::Uuid, uuid::Uuid)>>();
+                    self.lifecycle.carry(&ids);
+                }
+                let id = edited.id;
+                data.catalog.insert(edited);
+                // The changes are part of the new dataset, so edits continue from there rather
+                // than being applied to the old records a second time.
+                self.changes = ChangeSet::default();
+                self.open(Some(id), data);
+            }
+            Err(e) => self.message = Some(e.to_string()),
+        }
+    }
+
+    /// Saves the change set to a csv file on the local machine.
+    pub fn export(&self) {
+        let path = env::current_dir().expect("Could not read current directory.");
+        let file = rfd::FileDialog::new()
+            .add_filter("csv", &["csv"])
+            .set_directory(&path)
+            .set_file_name("address_changes.csv")
+            .save_file();
+        if let Some(path) = file {
+            info!("Saving address changes.");
+            let summary = format!(
+                "Exported {} changes to {}.",
+                self.changes.len(),
+                path.display()
+            );
+            match self.changes.to_csv(path) {
+                Ok(_) => audit::record(AuditKind::Export, self.dataset, &summary),
+                Err(e) => tracing::warn!("Could not save changes: {}", e.to_string()),
+            }
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+}
+
+/// Copies the record with id `id` from the data of `table` into the view, keeping its place in
+/// the sort order.
+fn sync_row(table: &mut TableView<AddressPoints, AddressPoint, String>, id: &uuid::Uuid) {
+    let point = table.data.iter().find(|v| v.id == *id).cloned();
+    if let (Some(point), Some(row)) = (point, table.view.get_mut(id)) {
+        *row = point;
+    }
+}
+
+/// The `Draft` struct holds the form for a new address.  Fields are filled in from the nearest
+/// existing address, which also supplies the fields not shown on the form.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Draft {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// The `template` field holds the nearest existing address.
+    pub template: AddressPoint,
+    /// The `values` field holds the values entered on the form, by column.
+    pub values: Vec<(AddressColumns, String)>,
+    /// The `nearby` field holds the labels and distances of the nearest existing addresses.
+    pub nearby: Vec<String>,
+}
+
+impl Draft {
+    /// Number of nearby addresses listed on the form.
+    pub const NEARBY: usize = 5;
+
+    /// Creates a form for a new address at `latitude` and `longitude`, pre-filled from the nearest
+    /// address in `points`.  Returns `None` if `points` is empty.
+    pub fn new(latitude: f64, longitude: f64, points: &AddressPoints) -> Option<Self> {
+        let mut nearest = points
+            .iter()
+            .map(|v| {
+                let d = distance(latitude, longitude, v.address.latitude, v.address.longitude);
+                (d, v)
+            })
+            .collect::<Vec<(f64, &AddressPoint)>>();
+        nearest.sort_by(|a, b| a.0.total_cmp(&b.0));
+        nearest.truncate(Self::NEARBY);
+        let (_, template) = nearest.first()?;
+        let values = [
+            AddressColumns::Number,
+            AddressColumns::Directional,
+            AddressColumns::StreetName,
+            AddressColumns::StreetType,
+            AddressColumns::SubaddressType,
+            AddressColumns::SubaddressId,
+            AddressColumns::Zip,
+        ]
+        .into_iter()
+        .map(|column| {
+            // The number and unit are particular to the new address, so start them blank.
+            let value = match column {
+                AddressColumns::Number
+                | AddressColumns::SubaddressType
+                | AddressColumns::SubaddressId => "".to_string(),
+                _ => template.column::<String>(&column),
+            };
+            (column, value)
+        })
+        .collect::<Vec<(AddressColumns, String)>>();
+        let nearby = nearest
+            .iter()
+            .map(|(d, v)| format!("{} ({:.0} m)", v.address.label(), d))
+            .collect::<Vec<String>>();
+        Some(Self {
+            latitude,
+            longitude,
+            template: (*template).clone(),
+            values,
+            nearby,
+        })
+    }
+
+    /// Validates the form and creates the new address with `Pending` status.
+    pub fn build(&self) -> Clean<AddressPoint> {
+        let mut point = self.template.clone();
+        for (column, value) in &self.values {
+            point.set_column(column, value)?;
+        }
+        *point.address.status_mut() = AddressStatus::Pending;
+        point.address.latitude = self.latitude;
+        point.address.longitude = self.longitude;
+        let (x, y) = web_mercator(self.latitude, self.longitude);
+        point.address.x = x;
+        point.address.y = y;
+        // Converting gives the new point its own id and map geometry.
+        Ok(AddressPoint::from(&point.address))
+    }
+}
+
+/// The `Audit` struct holds the audit log widget, for browsing and exporting the record of edits,
+/// imports, comparison runs and exports.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Audit {
+    /// The `table` field is read from the log file when shown, so it is not saved with the
+    /// project.
+    #[serde(skip)]
+    pub table: Option<TableView<AuditLog, AuditEntry, String>>,
+    pub visible: bool,
+}
+
+impl Audit {
+    pub fn combo(&mut self, ui: &mut egui::Ui, parent_tree: &mut focus::Tree) {
+        if self.table.is_none() {
+            self.reload();
+        }
+        let mut tree = focus::Tree::new();
+        let parent_node = tree.with_new_window();
+        ui.horizontal(|ui| {
+            let reload = ui.button("Reload");
+            tree.with_new_leaf(parent_node, &reload);
+            // Register button with focus tree.
+            tree.focusable(&reload);
+            if reload.clicked() {
+                self.reload();
+            }
+
+            let export = ui.button("Export");
+            tree.with_new_leaf(parent_node, &export);
+            // Register button with focus tree.
+            tree.focusable(&export);
+            if export.clicked() {
+                self.export();
+            }
+            if parent_tree.enter.is_some() {
+                tracing::info!("Enter detected in audit widget.");
+                if let Some(id) = parent_tree.current_leaf() {
+                    if id == reload.id {
+                        tracing::info!("Reload audit button in focus.");
+                        self.reload();
+                        // Clear the `enter` field after taking action.
+                        parent_tree.enter = None;
+                    }
+                    if id == export.id {
+                        tracing::info!("Export audit button in focus.");
+                        self.export();
+                        // Clear the `enter` field after taking action.
+                        parent_tree.enter = None;
+                    }
+                }
+            }
+        });
+        self.filter_panel(ui);
+        if let Some(t) = &mut self.table {
+            if t.data.is_empty() {
+                ui.label("No actions recorded yet.");
+            }
+            ui.push_id("audit", |ui| {
+                t.table(ui);
+            });
+        }
+        if parent_tree.contains_new(&tree) {
+            parent_tree.graft(tree);
+            tracing::info!("Audit tree added.");
+        }
+    }
+
+    /// Filters the log by kind of action.
+    pub fn filter_panel(&mut self, ui: &mut egui::Ui) {
+        if let Some(t) = &mut self.table {
+            ui.horizontal_wrapped(|ui| {
+                for kind in AuditKind::iter() {
+                    let name = kind.to_string();
+                    if ui
+                        .radio_value(&mut t.filter, Some(name.clone()), &name)
+                        .clicked()
+                    {
+                        t.view = t.data.clone().filter(&name);
+                    };
+                }
+                if ui.radio_value(&mut t.filter, None, "None").clicked() {
+                    t.view = t.data.clone();
+                };
+            });
+        }
+    }
+
+    /// Reads the log from the local machine, newest entries first.
+    pub fn reload(&mut self) {
+        let mut log = AuditLog::local();
+        log.reverse();
+        let config = TableConfig::new().with_search().striped();
+        self.table = Some(TableView::with_config(log, config));
+    }
+
+    /// Saves the filtered log to a csv file on the local machine.
+    pub fn export(&self) {
+        let path = env::current_dir().expect("Could not read current directory.");
+        let file = rfd::FileDialog::new()
+            .add_filter("csv", &["csv"])
+            .set_directory(&path)
+            .set_file_name("audit_log.csv")
+            .save_file();
+        if let Some(path) = file {
+            if let Some(view) = &self.table {
+                info!("Saving audit log.");
+                if let Err(e) = view.view.to_csv(path) {
+                    tracing::warn!("Could not save audit log: {}", e.to_string());
+                }
+            }
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+}
+
+/// The `Validation` struct holds the attribute validation widget, which checks a dataset against
+/// the [`Rules`] in the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Validation {
+    /// The id of the dataset to validate in the [`Catalog`](crate::catalog::Catalog).
+    pub dataset: Option<uuid::Uuid>,
+    /// The `rules` field holds the rules read from the config file.  They are read again when a
+    /// project opens rather than saved with it, so changes to the config file take effect.
+    #[serde(skip, default = "Validation::rules")]
+    pub rules: Rules,
+    /// The `enabled` field holds the rules switched on or off by the user, by name, over the
+    /// settings in the config file.
+    #[serde(default)]
+    pub enabled: BTreeMap<String, bool>,
+    pub table: Option<TableView<RuleIssues, RuleIssue, String>>,
+    /// Packages the issues for delivery to the map window.
+    pub package: Option<RuleIssues>,
+    /// The `inspect` field holds the label of an address to open in the editor.
+    pub inspect: Option<String>,
+    pub visible: bool,
+}
+
+impl Validation {
+    pub fn combo(
+        &mut self,
+        ui: &mut egui::Ui,
+        parent_tree: &mut focus::Tree,
+        data: &data::Data,
+        lifecycle: &Lifecycle,
+        master: &StreetMaster,
+    ) {
+        let mut tree = focus::Tree::new();
+        let parent_node = tree.with_new_window();
+        ui.horizontal(|ui| {
+            data.catalog.combo(ui, "Dataset", &mut self.dataset);
+
+            let run = ui.button("Run");
+            tree.with_new_leaf(parent_node, &run);
+            // Register button with focus tree.
+            tree.focusable(&run);
+            if run.clicked() {
+                self.run(data, lifecycle, master);
+            }
+
+            let save = ui.add_enabled(self.table.is_some(), egui::Button::new("Save"));
+            tree.with_new_leaf(parent_node, &save);
+            // Register button with focus tree.
+            tree.focusable(&save);
+            if save.clicked() {
+                self.save();
+            }
+
+            let selected = self.table.as_ref().and_then(|t| t.row_select);
+            let edit = ui.add_enabled(selected.is_some(), egui::Button::new("Edit Address"));
+            tree.with_new_leaf(parent_node, &edit);
+            // Register button with focus tree.
+            tree.focusable(&edit);
+            if edit.clicked() {
+                self.inspect();
+            }
+            if parent_tree.enter.is_some() {
+                tracing::info!("Enter detected in validation widget.");
+                if let Some(id) = parent_tree.current_leaf() {
+                    if id == run.id {
+                        tracing::info!("Run validation button in focus.");
+                        self.run(data, lifecycle, master);
+                        // Clear the `enter` field after taking action.
+                        parent_tree.enter = None;
+                    }
+                    if id == save.id {
+                        tracing::info!("Save validation button in focus.");
+                        self.save();
+                        // Clear the `enter` field after taking action.
+                        parent_tree.enter = None;
+                    }
+                    if id == edit.id {
+                        tracing::info!("Edit address button in focus.");
+                        self.inspect();
+                        // Clear the `enter` field after taking action.
+                        parent_tree.enter = None;
+                    }
+                }
+            }
+        });
+        egui::CollapsingHeader::new("Rules").show(ui, |ui| {
+            if self.rules.is_empty() {
+                ui.label("No rules defined in the config file.");
+            }
+            self.rules.set_enabled(&self.enabled);
+            for rule in self.rules.iter_mut() {
+                if ui
+                    .checkbox(
+                        &mut rule.enabled,
+                        format!("{} ({})", rule.name, rule.severity),
+                    )
+                    .changed()
+                {
+                    self.enabled.insert(rule.name.clone(), rule.enabled);
+                }
+                if rule.check == Check::PendingAge {
+                    ui.weak("Counts only addresses with a Pending date recorded in the editor.");
+                }
+            }
+        });
+        self.filter_panel(ui);
+        if let Some(t) = &mut self.table {
+            ui.push_id("rule_issues", |ui| {
+                t.table(ui);
+            });
+        }
+        if parent_tree.contains_new(&tree) {
+            parent_tree.graft(tree);
+            tracing::info!("Validation tree added.");
+        }
+    }
+
+    /// Displays the issue totals, with a filter by severity or rule.
+    pub fn filter_panel(&mut self, ui: &mut egui::Ui) {
+        if let Some(t) = &mut self.table {
+            let totals = t
+                .data
+                .totals()
+                .iter()
+                .rev()
+                .map(|(severity, count)| format!("{count} {severity}"))
+                .collect::<Vec<String>>();
+            ui.separator();
+            if totals.is_empty() {
+                ui.heading("No issues found.");
+            } else {
+                ui.heading(format!("Issues: {}", totals.join(", ")));
+            }
+            ui.horizontal_wrapped(|ui| {
+                ui.label("Filter:");
+                let names = Severity::iter()
+                    .map(|v| v.to_string())
+                    .chain(self.rules.iter().map(|v| v.name.clone()))
+                    .collect::<Vec<String>>();
+                for name in names {
+                    if ui
+                        .radio_value(&mut t.filter, Some(name.clone()), &name)
+                        .clicked()
+                    {
+                        t.view = t.data.clone().filter(&name);
+                        self.package = Some(t.view.clone());
+                    }
+                }
+                if ui.radio_value(&mut t.filter, None, "None").clicked() {
+                    t.view = t.data.clone();
+                    self.package = Some(t.view.clone());
+                }
+            });
+        }
+    }
+
+    /// Checks the selected dataset against the enabled rules.
+    pub fn run(&mut self, data: &data::Data, lifecycle: &Lifecycle, master: &StreetMaster) {
+        let Some(dataset) = self.dataset.and_then(|id| data.catalog.get(&id)) else {
+            info!("Select a dataset to validate.");
+            return;
+        };
+        self.rules.set_enabled(&self.enabled);
+        let issues = self
+            .rules
+            .check(dataset, lifecycle, &master.lookup(), today());
+        let config = TableConfig::new().with_search().striped().with_slider();
+        self.table = Some(TableView::with_config(issues.clone(), config));
+        self.package = Some(issues);
+    }
+
+    /// Requests the address of the selected issue be opened in the editor.
+    pub fn inspect(&mut self) {
+        let Some(t) = &self.table else {
+            return;
+        };
+        if let Some(issue) = t
+            .row_select
+            .and_then(|id| t.data.iter().find(|v| v.id == id))
+        {
+            self.inspect = Some(issue.address.clone());
+        }
+    }
+
+    /// Saves the filtered issues to a csv file on the local machine.
+    pub fn save(&self) {
+        let path = env::current_dir().expect("Could not read current directory.");
+        let file = rfd::FileDialog::new()
+            .add_filter("csv", &["csv"])
+            .set_directory(&path)
+            .set_file_name("address_issues.csv")
+            .save_file();
+        if let Some(path) = file {
+            if let Some(view) = &self.table {
+                info!("Saving address issues.");
+                if let Err(e) = view.view.to_csv(path) {
+                    tracing::warn!("Could not save issues: {}", e.to_string());
+                }
+            }
+        }
+    }
+
+    /// Reads the rules from the config file, logging a warning and running no rules if the config
+    /// cannot be read.
+    fn rules() -> Rules {
+        match Rules::with_config() {
+            Ok(rules) => rules,
+            Err(e) => {
+                tracing::warn!("Could not read rules: {}", e.to_string());
+                Rules::default()
+            }
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+}
+
+impl Default for Validation {
+    fn default() -> Self {
+        Self {
+            dataset: None,
+            rules: Self::rules(),
+            enabled: BTreeMap::new(),
+            table: None,
+            package: None,
+            inspect: None,
+            visible: false,
+        }
+    }
+}
+
+/// The `Spatial` struct holds the spatial QA widget, which checks the location of address points
+/// in a dataset.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Spatial {
+    /// The id of the dataset to check in the [`Catalog`](crate::catalog::Catalog).
+    pub dataset: Option<uuid::Uuid>,
+    pub config: SpatialConfig,
+    pub table: Option<TableView<RuleIssues, RuleIssue, String>>,
+    /// Packages the issues for delivery to the map window.
+    pub package: Option<RuleIssues>,
+    /// The `focus` field holds the latitude and longitude of a point the map should zoom to.
+    #[serde(skip)]
+    pub focus: Option<(f64, f64)>,
+    /// The id of the issue the map last zoomed to.
+    #[serde(skip)]
+    zoomed: Option<uuid::Uuid>,
+    pub visible: bool,
+}
+
+impl Spatial {
+    pub fn combo(&mut self, ui: &mut egui::Ui, parent_tree: &mut focus::Tree, data: &data::Data) {
+        let mut tree = focus::Tree::new();
+        let parent_node = tree.with_new_window();
+        ui.horizontal(|ui| {
+            data.catalog.combo(ui, "Dataset", &mut self.dataset);
+
+            let run = ui.button("Run");
+            tree.with_new_leaf(parent_node, &run);
+            // Register button with focus tree.
+            tree.focusable(&run);
+            if run.clicked() {
+                self.run(data);
+            }
+
+            let save = ui.add_enabled(self.table.is_some(), egui::Button::new("Save"));
+            tree.with_new_leaf(parent_node, &save);
+            // Register button with focus tree.
+            tree.focusable(&save);
+            if save.clicked() {
+                self.save();
+            }
+
+            let selected = self.table.as_ref().and_then(|t| t.row_select);
+            let zoom = ui.add_enabled(selected.is_some(), egui::Button::new("Zoom to Issue"));
+            tree.with_new_leaf(parent_node, &zoom);
+            // Register button with focus tree.
+            tree.focusable(&zoom);
+            if zoom.clicked() {
+                self.zoom();
+            }
+            if parent_tree.enter.is_some() {
+                tracing::info!("Enter detected in spatial widget.");
+                if let Some(id) = parent_tree.current_leaf() {
+                    if id == run.id {
+                        tracing::info!("Run spatial button in focus.");
+                        self.run(data);
+                        // Clear the `enter` field after taking action.
+                        parent_tree.enter = None;
+                    }
+                    if id == save.id {
+                        tracing::info!("Save spatial button in focus.");
+                        self.save();
+                        // Clear the `enter` field after taking action.
+                        parent_tree.enter = None;
+                    }
+                    if id == zoom.id {
+                        tracing::info!("Zoom to issue button in focus.");
+                        self.zoom();
+                        // Clear the `enter` field after taking action.
+                        parent_tree.enter = None;
+                    }
+                }
+            }
+        });
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Checks:");
+            for check in SpatialCheck::iter() {
+                let mut include = self.config.checks.contains(&check);
+                if ui.checkbox(&mut include, check.to_string()).changed() {
+                    if include {
+                        self.config.checks.insert(check);
+                    } else {
+                        self.config.checks.remove(&check);
+                    }
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Outlier distance (m):");
+            ui.add(egui::DragValue::new(&mut self.config.outlier).clamp_range(50.0..=20000.0));
+            ui.label("Zip neighbors:");
+            ui.add(egui::DragValue::new(&mut self.config.neighbors).clamp_range(3..=50));
+            ui.label("Agreement:");
+            ui.add(egui::Slider::new(&mut self.config.agreement, 0.5..=1.0));
+        });
+        ui.horizontal(|ui| {
+            ui.label("City community:");
+            ui.text_edit_singleline(&mut self.config.community);
+        });
+        self.filter_panel(ui);
+        if let Some(t) = &mut self.table {
+            ui.push_id("spatial_issues", |ui| {
+                t.table(ui);
+            });
+        }
+        // Follow the selected row on the map, so issues can be reviewed one after another.
+        let selected = self.table.as_ref().and_then(|t| t.row_select);
+        if selected.is_some() && selected != self.zoomed {
+            self.zoom();
+        }
+        if parent_tree.contains_new(&tree) {
+            parent_tree.graft(tree);
+            tracing::info!("Spatial tree added.");
+        }
+    }
+
+    /// Displays the issue count, with a filter by check.
+    pub fn filter_panel(&mut self, ui: &mut egui::Ui) {
+        if let Some(t) = &mut self.table {
+            ui.separator();
+            ui.heading(format!("Spatial QA: {} issues", t.data.len()));
+            ui.horizontal_wrapped(|ui| {
+                ui.label("Filter:");
+                for check in SpatialCheck::iter() {
+                    let name = check.to_string();
+                    if ui
+                        .radio_value(&mut t.filter, Some(name.clone()), &name)
+                        .clicked()
+                    {
+                        t.view = t.data.clone().filter(&name);
+                        self.package = Some(t.view.clone());
+                    }
+                }
+                if ui.radio_value(&mut t.filter, None, "None").clicked() {
+                    t.view = t.data.clone();
+                    self.package = Some(t.view.clone());
+                }
+            });
+        }
+    }
+
+    /// Runs the selected checks over the selected dataset.
+    pub fn run(&mut self, data: &data::Data) {
+        let Some(dataset) = self.dataset.and_then(|id| data.catalog.get(&id)) else {
+            info!("Select a dataset to check.");
+            return;
+        };
+        let limits = match Boundary::load(CITY_LIMITS_PATH) {
+            Ok(boundary) => Some(boundary),
+            Err(e) => {
+                tracing::warn!("Could not load city limits: {}", e.to_string());
+                None
+            }
+        };
+        let issues = self.config.check(&dataset.addresses, limits.as_ref());
+        let config = TableConfig::new().with_search().striped().with_slider();
+        self.table = Some(TableView::with_config(issues.clone(), config));
+        self.package = Some(issues);
+        self.zoomed = None;
+    }
+
+    /// Requests the map zoom to the selected issue.
+    pub fn zoom(&mut self) {
+        let Some(t) = &self.table else {
+            return;
+        };
+        if let Some(issue) = t
+            .row_select
+            .and_then(|id| t.data.iter().find(|v| v.id == id))
+        {
+            self.focus = Some((issue.latitude, issue.longitude));
+            self.zoomed = Some(issue.id);
+        }
+    }
+
+    /// Saves the filtered issues to a csv file on the local machine.
+    pub fn save(&self) {
+        let path = env::current_dir().expect("Could not read current directory.");
+        let file = rfd::FileDialog::new()
+            .add_filter("csv", &["csv"])
+            .set_directory(&path)
+            .set_file_name("spatial_issues.csv")
+            .save_file();
+        if let Some(path) = file {
+            if let Some(view) = &self.table {
+                info!("Saving spatial issues.");
+                if let Err(e) = view.view.to_csv(path) {
+                    tracing::warn!("Could not save issues: {}", e.to_string());
+                }
+            }
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+}
+
+/// The `Streets` struct holds the street centerline widget, which loads the centerline layer and
+/// checks address points against the nearest segment.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Streets {
+    /// The id of the dataset to check in the [`Catalog`](crate::catalog::Catalog).
+    pub dataset: Option<uuid::Uuid>,
+    /// The `centerlines` field holds the centerline layer.  It is shared rather than copied into
+    /// each undo snapshot, and reloaded from [`CENTERLINES_PATH`] rather than saved with the
+    /// workspace.
+    #[serde(skip)]
+    pub centerlines: Option<Arc<Centerlines>>,
+    /// The `search` field is the distance in meters within which to look for the nearest segment.
+    pub search: f64,
+    pub table: Option<TableView<RuleIssues, RuleIssue, String>>,
+    /// Packages the issues for delivery to the map window.
+    pub package: Option<RuleIssues>,
+    /// Packages the centerlines for delivery to the map window.
+    #[serde(skip)]
+    pub layer: Option<Arc<Centerlines>>,
+    /// The `focus` field holds the latitude and longitude of a point the map should zoom to.
+    #[serde(skip)]
+    pub focus: Option<(f64, f64)>,
+    /// The id of the issue the map last zoomed to.
+    #[serde(skip)]
+    zoomed: Option<uuid::Uuid>,
+    /// Indicates the saved centerlines have been looked for this session.
+    #[serde(skip)]
+    restored: bool,
+    pub visible: bool,
+}
+
+impl Streets {
+    pub fn combo(
+        &mut self,
+        ui: &mut egui::Ui,
+        parent_tree: &mut focus::Tree,
+        data: &data::Data,
+        master: &StreetMaster,
+    ) {
+        if !self.restored && Path::new(CENTERLINES_PATH).exists() {
+            self.restored = true;
+            match Centerlines::load(CENTERLINES_PATH) {
+                Ok(centerlines) => self.set_centerlines(centerlines),
+                Err(e) => tracing::warn!("Could not load centerlines: {}", e.to_string()),
+            }
+        }
+        let mut tree = focus::Tree::new();
+        let parent_node = tree.with_new_window();
+        ui.horizontal(|ui| {
+            let import = ui.button("Import Centerlines");
+            tree.with_new_leaf(parent_node, &import);
+            // Register button with focus tree.
+            tree.focusable(&import);
+            if import.clicked() {
+                self.import();
+            }
+            match &self.centerlines {
+                Some(centerlines) => ui.label(format!("{} segments", centerlines.records.len())),
+                None => ui.label("No centerlines loaded."),
+            };
+        });
+        ui.horizontal(|ui| {
+            data.catalog.combo(ui, "Dataset", &mut self.dataset);
+            ui.label("Search distance (m):");
+            ui.add(egui::DragValue::new(&mut self.search).clamp_range(10.0..=1000.0));
+        });
+        ui.horizontal(|ui| {
+            let run = ui.add_enabled(self.centerlines.is_some(), egui::Button::new("Run"));
+            tree.with_new_leaf(parent_node, &run);
+            // Register button with focus tree.
+            tree.focusable(&run);
+            if run.clicked() {
+                self.run(data, master);
+            }
+
+            let save = ui.add_enabled(self.table.is_some(), egui::Button::new("Save"));
+            tree.with_new_leaf(parent_node, &save);
+            // Register button with focus tree.
+            tree.focusable(&save);
+            if save.clicked() {
+                self.save();
+            }
+
+            let selected = self.table.as_ref().and_then(|t| t.row_select);
+            let zoom = ui.add_enabled(selected.is_some(), egui::Button::new("Zoom to Issue"));
+            tree.with_new_leaf(parent_node, &zoom);
+            // Register button with focus tree.
+            tree.focusable(&zoom);
+            if zoom.clicked() {
+                self.zoom();
+            }
+            if parent_tree.enter.is_some() {
+                tracing::info!("Enter detected in streets widget.");
+                if let Some(id) = parent_tree.current_leaf() {
+                    if id == run.id {
+                        tracing::info!("Run streets button in focus.");
+                        self.run(data, master);
+                        // Clear the `enter` field after taking action.
+                        parent_tree.enter = None;
+                    }
+                    if id == save.id {
+                        tracing::info!("Save streets button in focus.");
+                        self.save();
+                        // Clear the `enter` field after taking action.
+                        parent_tree.enter = None;
+                    }
+                    if id == zoom.id {
+                        tracing::info!("Zoom to issue button in focus.");
+                        self.zoom();
+                        // Clear the `enter` field after taking action.
+                        parent_tree.enter = None;
+                    }
+                }
+            }
+        });
+        self.filter_panel(ui);
+        if let Some(t) = &mut self.table {
+            ui.push_id("street_issues", |ui| {
+                t.table(ui);
+            });
+        }
+        // Follow the selected row on the map, so issues can be reviewed one after another.
+        let selected = self.table.as_ref().and_then(|t| t.row_select);
+        if selected.is_some() && selected != self.zoomed {
+            self.zoom();
+        }
+        if parent_tree.contains_new(&tree) {
+            parent_tree.graft(tree);
+            tracing::info!("Streets tree added.");
+        }
+    }
+
+    /// Displays the issue count, with a filter by check.
+    pub fn filter_panel(&mut self, ui: &mut egui::Ui) {
+        if let Some(t) = &mut self.table {
+            ui.separator();
+            ui.heading(format!("Street QA: {} issues", t.data.len()));
+            ui.horizontal_wrapped(|ui| {
+                ui.label("Filter:");
+                for check in StreetCheck::iter() {
+                    let name = check.to_string();
+                    if ui
+                        .radio_value(&mut t.filter, Some(name.clone()), &name)
+                        .clicked()
+                    {
+                        t.view = t.data.clone().filter(&name);
+                        self.package = Some(t.view.clone());
+                    }
+                }
+                if ui.radio_value(&mut t.filter, None, "None").clicked() {
+                    t.view = t.data.clone();
+                    self.package = Some(t.view.clone());
+                }
+            });
+        }
+    }
+
+    /// Reads a centerline shapefile or GeoJSON file selected by the user, keeping a binary copy at
+    /// [`CENTERLINES_PATH`] for later sessions.
+    pub fn import(&mut self) {
+        let path = env::current_dir().expect("Could not read current directory.");
+        let file = rfd::FileDialog::new()
+            .add_filter("centerlines", &["shp", "geojson", "json"])
+            .set_directory(&path)
+            .pick_file();
+        let Some(path) = file else {
+            return;
+        };
+        let fields = match CenterlineFields::with_config() {
+            Ok(fields) => fields,
+            Err(e) => {
+                tracing::warn!("Could not read centerline fields: {}", e.to_string());
+                CenterlineFields::default()
+            }
+        };
+        match Centerlines::read(&path, &fields) {
+            Ok(centerlines) => {
+                if let Err(e) = centerlines.save(CENTERLINES_PATH) {
+                    tracing::warn!("Could not save centerlines: {}", e.to_string());
+                }
+                let summary = format!(
+                    "Imported {} street segments from {}.",
+                    centerlines.records.len(),
+                    path.display()
+                );
+                audit::record(AuditKind::Import, None, &summary);
+                self.set_centerlines(centerlines);
+            }
+            Err(e) => tracing::warn!("Could not read centerlines: {}", e.to_string()),
+        }
+    }
+
+    fn set_centerlines(&mut self, centerlines: Centerlines) {
+        let centerlines = Arc::new(centerlines);
+        self.layer = Some(centerlines.clone());
+        self.centerlines = Some(centerlines);
+    }
+
+    /// Checks the selected dataset against the centerlines.
+    pub fn run(&mut self, data: &data::Data, master: &StreetMaster) {
+        let Some(dataset) = self.dataset.and_then(|id| data.catalog.get(&id)) else {
+            info!("Select a dataset to check.");
+            return;
+        };
+        let Some(centerlines) = &self.centerlines else {
+            info!("Import centerlines to check.");
+            return;
+        };
+        let issues = centerlines.check(&dataset.addresses, self.search, &master.lookup());
+        let config = TableConfig::new().with_search().striped().with_slider();
+        self.table = Some(TableView::with_config(issues.clone(), config));
+        self.package = Some(issues);
+        self.zoomed = None;
+    }
+
+    /// Requests the map zoom to the selected issue.
+    pub fn zoom(&mut self) {
+        let Some(t) = &self.table else {
+            return;
+        };
+        if let Some(issue) = t
+            .row_select
+            .and_then(|id| t.data.iter().find(|v| v.id == id))
+        {
+            self.focus = Some((issue.latitude, issue.longitude));
+            self.zoomed = Some(issue.id);
+        }
+    }
+
+    /// Saves the filtered issues to a csv file on the local machine.
+    pub fn save(&self) {
+        let path = env::current_dir().expect("Could not read current directory.");
+        let file = rfd::FileDialog::new()
+            .add_filter("csv", &["csv"])
+            .set_directory(&path)
+            .set_file_name("street_issues.csv")
+            .save_file();
+        if let Some(path) = file {
+            if let Some(view) = &self.table {
+                info!("Saving street issues.");
+                if let Err(e) = view.view.to_csv(path) {
+                    tracing::warn!("Could not save issues: {}", e.to_string());
+                }
+            }
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+}
+
+impl Default for Streets {
+    fn default() -> Self {
+        Self {
+            dataset: None,
+            centerlines: None,
+            search: 100.0,
+            table: None,
+            package: None,
+            layer: None,
+            focus: None,
+            zoomed: None,
+            restored: false,
+            visible: false,
+        }
+    }
+}
+
+/// The `Master` struct holds the street master widget, where the canonical street names and their
+/// aliases are maintained.  The list is stored with the workspace.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Master {
+    /// The `streets` field holds the street master list.
+    pub streets: StreetMaster,
+    pub table: Option<TableView<StreetMaster, StreetName, String>>,
+    /// The `canonical` field holds the canonical name of a new entry.
+    pub canonical: String,
+    /// The `aliases` field holds the aliases of a new entry, separated by semicolons.
+    pub aliases: String,
+    /// The id of the dataset checked for unlisted streets in the
+    /// [`Catalog`](crate::catalog::Catalog).
+    pub dataset: Option<uuid::Uuid>,
+    /// The `report` field lists the addresses on streets missing from the list.
+    pub report: Option<TableView<RuleIssues, RuleIssue, String>>,
+    /// The `message` field holds the validation error from the last change, if any.
+    pub message: Option<String>,
+    /// The `revision` field counts changes to the list.  Cell edits do not change the shape of
+    /// the table, so the undo history tracks this count instead.
+    pub revision: usize,
+    pub visible: bool,
+}
+
+impl Master {
+    pub fn combo(&mut self, ui: &mut egui::Ui, parent_tree: &mut focus::Tree, data: &data::Data) {
+        let mut tree = focus::Tree::new();
+        let parent_node = tree.with_new_window();
+        if self.table.is_none() && !self.streets.is_empty() {
+            self.refresh();
+        }
+        ui.horizontal(|ui| {
+            let import = ui.button("Import");
+            tree.with_new_leaf(parent_node, &import);
+            // Register button with focus tree.
+            tree.focusable(&import);
+            if import.clicked() {
+                self.import();
+            }
+
+            let export = ui.add_enabled(!self.streets.is_empty(), egui::Button::new("Export"));
+            tree.with_new_leaf(parent_node, &export);
+            // Register button with focus tree.
+            tree.focusable(&export);
+            if export.clicked() {
+                self.export();
+            }
+
+            let selected = self.table.as_ref().and_then(|t| t.row_select);
+            let remove = ui.add_enabled(selected.is_some(), egui::Button::new("Remove Street"));
+            tree.with_new_leaf(parent_node, &remove);
+            // Register button with focus tree.
+            tree.focusable(&remove);
+            if remove.clicked() {
+                self.remove();
+            }
+            if parent_tree.enter.is_some() {
+                tracing::info!("Enter detected in street master widget.");
+                if let Some(id) = parent_tree.current_leaf() {
+                    if id == import.id {
+                        tracing::info!("Import streets button in focus.");
+                        self.import();
+                        // Clear the `enter` field after taking action.
+                        parent_tree.enter = None;
+                    }
+                    if id == export.id {
+                        tracing::info!("Export streets button in focus.");
+                        self.export();
+                        // Clear the `enter` field after taking action.
+                        parent_tree.enter = None;
+                    }
+                    if id == remove.id {
+                        tracing::info!("Remove street button in focus.");
+                        self.remove();
+                        // Clear the `enter` field after taking action.
+                        parent_tree.enter = None;
+                    }
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Canonical name");
+            ui.text_edit_singleline(&mut self.canonical);
+            ui.label("Aliases");
+            ui.text_edit_singleline(&mut self.aliases);
+            let add = ui.add_enabled(
+                !self.canonical.trim().is_empty(),
+                egui::Button::new("Add Street"),
+            );
+            if add.clicked() {
+                self.add();
+            }
+        });
+        ui.label("Separate aliases with semicolons.  Double click a cell to edit it, then press Enter to accept.");
+        if let Some(message) = &self.message {
+            ui.colored_label(egui::Color32::RED, message);
+        }
+        if let Some(t) = &mut self.table {
+            ui.push_id("street_master", |ui| {
+                t.table(ui);
+            });
+        }
+        self.apply_edits();
+        egui::CollapsingHeader::new("Unlisted Streets").show(ui, |ui| {
+            self.report_panel(ui, data);
+        });
+        if parent_tree.contains_new(&tree) {
+            parent_tree.graft(tree);
+            tracing::info!("Street master tree added.");
+        }
+    }
+
+    /// Checks a dataset for addresses on streets missing from the list, and adds the missing
+    /// streets to the list on request.
+    pub fn report_panel(&mut self, ui: &mut egui::Ui, data: &data::Data) {
+        ui.horizontal(|ui| {
+            data.catalog.combo(ui, "Dataset", &mut self.dataset);
+            if ui.button("Find Unlisted").clicked() {
+                self.unlisted(data);
+            }
+            if ui.button("Add Unlisted Streets").clicked() {
+                self.seed(data);
+            }
+            let save = ui.add_enabled(self.report.is_some(), egui::Button::new("Save Report"));
+            if save.clicked() {
+                self.save_report();
+            }
+        });
+        if let Some(t) = &mut self.report {
+            ui.label(format!("Addresses on unlisted streets: {}", t.data.len()));
+            ui.push_id("unlisted_streets", |ui| {
+                t.table(ui);
+            });
+        }
+    }
+
+    /// Rebuilds the table from the list, keeping the search text.
+    fn refresh(&mut self) {
+        let search = match &self.table {
+            Some(t) => t.search.clone(),
+            None => String::new(),
+        };
+        let config = TableConfig::new()
+            .with_search()
+            .striped()
+            .with_slider()
+            .editable();
+        let mut table = TableView::with_config(self.streets.clone(), config);
+        table.search = search;
+        self.table = Some(table);
+    }
+
+    /// Records a change to the list in the audit log and updates the table.
+    fn changed(&mut self, summary: &str) {
+        audit::record(AuditKind::Edit, None, summary);
+        self.revision += 1;
+        self.refresh();
+    }
+
+    /// Validates and applies the edits committed in the table.
+    pub fn apply_edits(&mut self) {
+        let Some(t) = &mut self.table else {
+            return;
+        };
+        if t.edits.is_empty() {
+            return;
+        }
+        let mut errors = Vec::new();
+        let mut edited = Vec::new();
+        for edit in std::mem::take(&mut t.edits) {
+            match self.streets.edit(&edit) {
+                Ok(true) => {
+                    if let Some(street) = self.streets.iter().find(|v| v.id == edit.row) {
+                        edited.push(street.canonical.clone());
+                    }
+                }
+                Ok(false) => {}
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        self.message = errors.first().cloned();
+        if !edited.is_empty() {
+            self.changed(&format!("Edited street master: {}.", edited.join(", ")));
+        }
+    }
+
+    /// Adds the street entered in the form to the list.
+    pub fn add(&mut self) {
+        let street = StreetName::new(&self.canonical, &StreetName::split(&self.aliases));
+        let summary = format!("Added {} to the street master.", street.canonical);
+        match self.streets.add(street) {
+            Ok(()) => {
+                self.canonical.clear();
+                self.aliases.clear();
+                self.message = None;
+                self.changed(&summary);
+            }
+            Err(e) => self.message = Some(e.to_string()),
+        }
+    }
+
+    /// Removes the selected street from the list.
+    pub fn remove(&mut self) {
+        let Some(id) = self.table.as_ref().and_then(|t| t.row_select) else {
+            return;
+        };
+        let Some(street) = self.streets.iter().find(|v| v.id == id).cloned() else {
+            return;
+        };
+        self.streets.retain(|v| v.id != id);
+        self.changed(&format!(
+            "Removed {} from the street master.",
+            street.canonical
+        ));
+    }
+
+    /// Adds the streets of the selected dataset that are missing from the list.
+    pub fn seed(&mut self, data: &data::Data) {
+        let Some(dataset) = self.dataset.and_then(|id| data.catalog.get(&id)) else {
+            info!("Select a dataset to check.");
+            return;
+        };
+        match self.streets.seed(&dataset.addresses[..]) {
+            Ok(0) => {}
+            Ok(count) => {
+                self.message = None;
+                self.changed(&format!(
+                    "Added {count} streets from {} to the street master.",
+                    dataset.label
+                ));
+                self.unlisted(data);
+            }
+            Err(e) => self.message = Some(e.to_string()),
+        }
+    }
+
+    /// Lists the addresses in the selected dataset on streets missing from the list.
+    pub fn unlisted(&mut self, data: &data::Data) {
+        let Some(dataset) = self.dataset.and_then(|id| data.catalog.get(&id)) else {
+            info!("Select a dataset to check.");
+            return;
+        };
+        let issues = self.streets.unlisted(&dataset.addresses[..]);
+        let config = TableConfig::new().with_search().striped().with_slider();
+        self.report = Some(TableView::with_config(issues, config));
+    }
+
+    /// Replaces the list with a csv file selected by the user.
+    pub fn import(&mut self) {
+        let path = env::current_dir().expect("Could not read current directory.");
+        let file = rfd::FileDialog::new()
+            .add_filter("csv", &["csv"])
+            .set_directory(&path)
+            .pick_file();
+        let Some(path) = file else {
+            return;
+        };
+        match StreetMaster::from_csv(&path) {
+            Ok(streets) => {
+                let summary = format!(
+                    "Imported {} streets to the street master from {}.",
+                    streets.len(),
+                    path.display()
+                );
+                audit::record(AuditKind::Import, None, &summary);
+                self.streets = streets;
+                self.message = None;
+                self.revision += 1;
+                self.refresh();
+            }
+            Err(e) => self.message = Some(e.to_string()),
+        }
+    }
+
+    /// Saves the list to a csv file on the local machine.
+    pub fn export(&self) {
+        let path = env::current_dir().expect("Could not read current directory.");
+        let file = rfd::FileDialog::new()
+            .add_filter("csv", &["csv"])
+            .set_directory(&path)
+            .set_file_name("street_master.csv")
+            .save_file();
+        if let Some(path) = file {
+            info!("Saving street master.");
+            match self.streets.to_csv(&path) {
+                Ok(()) => {
+                    let summary = format!(
+                        "Exported {} streets to {}.",
+                        self.streets.len(),
+                        path.display()
+                    );
+                    audit::record(AuditKind::Export, None, &summary);
+                }
+                Err(e) => tracing::warn!("Could not save street master: {}", e.to_string()),
+            }
+        }
+    }
+
+    /// Saves the filtered unlisted street report to a csv file on the local machine.
+    pub fn save_report(&self) {
+        let path = env::current_dir().expect("Could not read current directory.");
+        let file = rfd::FileDialog::new()
+            .add_filter("csv", &["csv"])
+            .set_directory(&path)
+            .set_file_name("unlisted_streets.csv")
+            .save_file();
+        if let Some(path) = file {
+            if let Some(view) = &self.report {
+                info!("Saving unlisted streets.");
+                if let Err(e) = view.view.to_csv(path) {
+                    tracing::warn!("Could not save report: {}", e.to_string());
+                }
+            }
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+}
+
+/// The `Geocode` struct holds the address search bar, which finds an address in a dataset from
+/// free text and zooms the map to it.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Geocode {
+    /// The id of the dataset to search in the [`Catalog`](crate::catalog::Catalog).
+    pub dataset: Option<uuid::Uuid>,
+    /// The `text` field holds the address typed by the user.
+    pub text: String,
+    /// The `suggestions` field holds the best candidates for the text, as it is typed.
+    pub suggestions: Vec<Candidate>,
+    /// The `selected` field holds the candidate chosen by the user.
+    pub selected: Option<Candidate>,
+    /// Packages the selected candidate for delivery to the map window.
+    pub package: Option<Vec<Candidate>>,
+    pub visible: bool,
+    /// The `focus` field holds the latitude and longitude of a point the map should zoom to.
+    #[serde(skip)]
+    pub focus: Option<(f64, f64)>,
+    /// The `geocoder` field indexes the addresses of the dataset, with the key it was built for.
+    #[serde(skip)]
+    geocoder: Option<(GeocoderKey, Arc<Geocoder>)>,
+}
+
+impl Geocode {
+    pub fn combo(
+        &mut self,
+        ui: &mut egui::Ui,
+        parent_tree: &mut focus::Tree,
+        data: &data::Data,
+        master: &Master,
+    ) {
+        let mut tree = focus::Tree::new();
+        let parent_node = tree.with_new_window();
+        if self.dataset.is_none() {
+            self.dataset = data.catalog.first().map(|v| v.id);
+        }
+        data.catalog.combo(ui, "Search dataset", &mut self.dataset);
+        let search =
+            ui.add(egui::TextEdit::singleline(&mut self.text).hint_text("Find an address"));
+        tree.with_new_leaf(parent_node, &search);
+        // Register search box with focus tree.
+        tree.focusable(&search);
+        if search.changed() {
+            self.suggest(data, master);
+        }
+        if search.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+            self.choose_first();
+        }
+        if parent_tree.enter.is_some() {
+            if let Some(id) = parent_tree.current_leaf() {
+                if id == search.id {
+                    tracing::info!("Address search in focus.");
+                    self.choose_first();
+                    // Clear the `enter` field after taking action.
+                    parent_tree.enter = None;
+                }
+            }
+        }
+        let mut chosen = None;
+        for candidate in self.suggestions.iter() {
+            let text = format!("{} ({:.0}%)", candidate.label, candidate.score * 100.0);
+            if ui.selectable_label(false, text).clicked() {
+                chosen = Some(candidate.clone());
+            }
+        }
+        if let Some(candidate) = chosen {
+            self.choose(candidate);
+        }
+        if parent_tree.contains_new(&tree) {
+            parent_tree.graft(tree);
+            tracing::info!("Geocode tree added.");
+        }
+    }
+
+    /// Updates the suggestions for the text in the search box.
+    pub fn suggest(&mut self, data: &data::Data, master: &Master) {
+        let Some(dataset) = self.dataset.and_then(|id| data.catalog.get(&id)) else {
+            info!("Select a dataset to search.");
+            return;
+        };
+        let geocoder = Geocoder::cached(
+            &mut self.geocoder,
+            dataset,
+            &master.streets,
+            master.revision,
+        );
+        self.suggestions = geocoder.search(&self.text, SUGGESTIONS);
+    }
+
+    fn choose_first(&mut self) {
+        if let Some(candidate) = self.suggestions.first().cloned() {
+            self.choose(candidate);
+        }
+    }
+
+    /// Zooms the map to `candidate` and marks it on the map.
+    pub fn choose(&mut self, candidate: Candidate) {
+        info!("Zooming to {}.", candidate.label);
+        self.text = candidate.label.clone();
+        self.focus = Some((candidate.latitude, candidate.longitude));
+        self.package = Some(vec![candidate.clone()]);
+        self.selected = Some(candidate);
+        self.suggestions.clear();
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+}
+
+/// The `BatchJob` struct holds the thread of a batch run.  Copies of the widget share the handle,
+/// so the run is received once, by whichever copy sees it finish first.
+#[derive(Debug, Clone)]
+struct BatchJob(Arc<Mutex<Option<JoinHandle<BatchRecords>>>>);
+
+impl BatchJob {
+    /// Takes the handle once the thread has finished, whether it returned or panicked.  Returns
+    /// `None` while the thread runs, and `Some(None)` if another copy already took the handle.
+    fn finished(&self) -> Option<Option<JoinHandle<BatchRecords>>> {
+        let mut handle = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        match handle.as_ref() {
+            Some(v) if !v.is_finished() => None,
+            _ => Some(handle.take()),
+        }
+    }
+}
+
+impl PartialEq for BatchJob {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// The `Batch` struct holds the batch geocoding widget, which locates the addresses in a csv file
+/// without coordinates by matching them against a dataset.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Batch {
+    /// The id of the dataset to match against in the [`Catalog`](crate::catalog::Catalog).
+    pub dataset: Option<uuid::Uuid>,
+    /// The `input` field holds the csv file to geocode.  It is read again rather than saved, as
+    /// the results hold each row.
+    #[serde(skip)]
+    pub input: Option<Arc<BatchInput>>,
+    /// The `headers` field holds the column names of the input, for export with the results.
+    pub headers: Vec<String>,
+    /// The `path` field holds the file the input was read from.
+    pub path: Option<String>,
+    /// The `column` field holds the index of the input column holding the address.
+    pub column: usize,
+    pub table: Option<TableView<BatchRecords, BatchRecord, String>>,
+    /// Packages the located records for delivery to the map window.
+    pub package: Option<CandidatePoints>,
+    /// The `message` field holds the error from the last file read or run, if any.
+    pub message: Option<String>,
+    /// The `focus` field holds the latitude and longitude of a point the map should zoom to.
+    #[serde(skip)]
+    pub focus: Option<(f64, f64)>,
+    /// The `geocoder` field indexes the addresses of the dataset, with the key it was built for.
+    #[serde(skip)]
+    geocoder: Option<(GeocoderKey, Arc<Geocoder>)>,
+    /// The `pending` field holds the thread of a run geocoding in the background.
+    #[serde(skip)]
+    pending: Option<BatchJob>,
+    pub visible: bool,
+}
+
+impl Batch {
+    pub fn combo(
+        &mut self,
+        ui: &mut egui::Ui,
+        parent_tree: &mut focus::Tree,
+        data: &data::Data,
+        master: &Master,
+    ) {
+        let mut tree = focus::Tree::new();
+        let parent_node = tree.with_new_window();
+        ui.horizontal(|ui| {
+            let open = ui.add_enabled(!self.running(), egui::Button::new("Open CSV"));
+            tree.with_new_leaf(parent_node, &open);
+            // Register button with focus tree.
+            tree.focusable(&open);
+            if open.clicked() {
+                self.open();
+            }
+            if let Some(path) = &self.path {
+                ui.label(path);
+            }
+            if parent_tree.enter.is_some() {
+                if let Some(id) = parent_tree.current_leaf() {
+                    if id == open.id {
+                        tracing::info!("Open batch button in focus.");
+                        self.open();
+                        // Clear the `enter` field after taking action.
+                        parent_tree.enter = None;
+                    }
+                }
+            }
+        });
+        if let Some(message) = &self.message {
+            ui.colored_label(egui::Color32::RED, message);
+        }
+        if let Some(input) = &self.input {
+            let selected = input.headers.get(self.column).cloned().unwrap_or_default();
+            egui::ComboBox::from_label("Address column")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    for (i, header) in input.headers.iter().enumerate() {
+                        ui.selectable_value(&mut self.column, i, header);
+                    }
+                });
+        }
+        ui.horizontal(|ui| {
+            data.catalog.combo(ui, "Match against", &mut self.dataset);
+
+            let ready = self.input.is_some() && !self.running();
+            let run = ui.add_enabled(ready, egui::Button::new("Geocode"));
+            tree.with_new_leaf(parent_node, &run);
+            // Register button with focus tree.
+            tree.focusable(&run);
+            if run.clicked() {
+                self.run(data, master);
+            }
+
+            let export = ui.add_enabled(self.table.is_some(), egui::Button::new("Export"));
+            tree.with_new_leaf(parent_node, &export);
+            // Register button with focus tree.
+            tree.focusable(&export);
+            if export.clicked() {
+                self.export();
+            }
+
+            let selected = self.table.as_ref().and_then(|t| t.row_select);
+            let zoom = ui.add_enabled(selected.is_some(), egui::Button::new("Zoom to Match"));
+            tree.with_new_leaf(parent_node, &zoom);
+            // Register button with focus tree.
+            tree.focusable(&zoom);
+            if zoom.clicked() {
+                self.zoom();
+            }
+            if parent_tree.enter.is_some() {
+                if let Some(id) = parent_tree.current_leaf() {
+                    if id == run.id {
+                        tracing::info!("Geocode button in focus.");
+                        self.run(data, master);
+                        // Clear the `enter` field after taking action.
+                        parent_tree.enter = None;
+                    }
+                    if id == export.id {
+                        tracing::info!("Export batch button in focus.");
+                        self.export();
+                        // Clear the `enter` field after taking action.
+                        parent_tree.enter = None;
+                    }
+                    if id == zoom.id {
+                        tracing::info!("Zoom to match button in focus.");
+                        self.zoom();
+                        // Clear the `enter` field after taking action.
+                        parent_tree.enter = None;
+                    }
+                }
+            }
+        });
+        if self.running() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Geocoding...");
+            });
+        }
+        self.filter_panel(ui);
+        if let Some(t) = &mut self.table {
+            ui.push_id("batch", |ui| {
+                t.table(ui);
+            });
+        }
+        if parent_tree.contains_new(&tree) {
+            parent_tree.graft(tree);
+            tracing::info!("Batch tree added.");
+        }
+    }
+
+    /// Displays the match counts, with a filter by match type.
+    pub fn filter_panel(&mut self, ui: &mut egui::Ui) {
+        if let Some(t) = &mut self.table {
+            let counts = MatchKind::iter()
+                .map(|v| format!("{} {}", t.data.count(v), v))
+                .collect::<Vec<String>>();
+            ui.separator();
+            ui.heading(format!("Batch: {}", counts.join(", ")));
+            ui.horizontal_wrapped(|ui| {
+                ui.label("Filter:");
+                for kind in MatchKind::iter() {
+                    let name = kind.to_string();
+                    if ui
+                        .radio_value(&mut t.filter, Some(name.clone()), &name)
+                        .clicked()
+                    {
+                        t.view = t.data.clone().filter(&name);
+                        self.package = Some(CandidatePoints::from(&t.view));
+                    }
+                }
+                if ui.radio_value(&mut t.filter, None, "None").clicked() {
+                    t.view = t.data.clone();
+                    self.package = Some(CandidatePoints::from(&t.view));
+                }
+            });
+        }
+    }
+
+    /// Reads a csv file of addresses selected by the user.
+    pub fn open(&mut self) {
+        if self.running() {
+            return;
+        }
+        let path = env::current_dir().expect("Could not read current directory.");
+        let file = rfd::FileDialog::new()
+            .add_filter("csv", &["csv"])
+            .set_directory(&path)
+            .pick_file();
+        let Some(path) = file else {
+            return;
+        };
+        match BatchInput::from_csv(&path) {
+            Ok(input) => {
+                info!("Read {} addresses to geocode.", input.rows.len());
+                self.column = input.address_column();
+                self.headers = input.headers.clone();
+                self.input = Some(Arc::new(input));
+                self.path = Some(path.display().to_string());
+                self.table = None;
+                self.message = None;
+            }
+            Err(e) => self.message = Some(e.to_string()),
+        }
+    }
+
+    /// Geocodes the input against the selected dataset on a background thread, so a long list
+    /// does not freeze the window.  The results are shown by [`Self::receive`].
+    pub fn run(&mut self, data: &data::Data, master: &Master) {
+        if self.running() {
+            info!("Wait for the current batch to finish.");
+            return;
+        }
+        let Some(dataset) = self.dataset.and_then(|id| data.catalog.get(&id)) else {
+            info!("Select a dataset to match against.");
+            return;
+        };
+        let geocoder = Geocoder::cached(
+            &mut self.geocoder,
+            dataset,
+            &master.streets,
+            master.revision,
+        );
+        let Some(input) = self.input.clone() else {
+            info!("Open a csv file to geocode.");
+            return;
+        };
+        info!("Geocoding {} addresses.", input.rows.len());
+        let column = self.column;
+        let handle = std::thread::spawn(move || input.geocode(column, &geocoder));
+        self.message = None;
+        self.pending = Some(BatchJob(Arc::new(Mutex::new(Some(handle)))));
+    }
+
+    /// Returns true while a run is geocoding in the background.
+    pub fn running(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Shows the records of the background run, once it finishes.  Called each frame, whether or
+    /// not the window is open.
+    pub fn receive(&mut self) {
+        let Some(job) = &self.pending else {
+            return;
+        };
+        let Some(handle) = job.finished() else {
+            return;
+        };
+        self.pending = None;
+        let Some(handle) = handle else {
+            return;
+        };
+        let records = match handle.join() {
+            Ok(records) => records,
+            Err(_) => {
+                tracing::warn!("Batch geocoding thread panicked.");
+                self.message = Some("Batch geocoding failed.  Check the input file.".to_string());
+                return;
+            }
+        };
+        let config = TableConfig::new().with_search().striped().with_slider();
+        self.package = Some(CandidatePoints::from(&records));
+        self.table = Some(TableView::with_config(records, config));
+    }
+
+    /// Requests the map zoom to the selected record, if it was located.
+    pub fn zoom(&mut self) {
+        let Some(t) = &self.table else {
+            return;
+        };
+        if let Some(record) = t
+            .row_select
+            .and_then(|id| t.data.iter().find(|v| v.id == id))
+        {
+            match (record.latitude, record.longitude) {
+                (Some(latitude), Some(longitude)) => self.focus = Some((latitude, longitude)),
+                _ => info!("{} was not located.", record.input),
+            }
+        }
+    }
+
+    /// Saves the filtered records to a csv file on the local machine, with the columns of the
+    /// input followed by the results.
+    pub fn export(&self) {
+        let Some(t) = &self.table else {
+            return;
+        };
+        let path = env::current_dir().expect("Could not read current directory.");
+        let file = rfd::FileDialog::new()
+            .add_filter("csv", &["csv"])
+            .set_directory(&path)
+            .set_file_name("geocoded.csv")
+            .save_file();
+        if let Some(path) = file {
+            info!("Saving geocoded addresses.");
+            match t.view.to_csv(&path, &self.headers) {
+                Ok(()) => {
+                    let summary = format!(
+                        "Exported {} geocoded addresses to {}.",
+                        t.view.len(),
+                        path.display()
+                    );
+                    audit::record(AuditKind::Export, None, &summary);
+                }
+                Err(e) => tracing::warn!("Could not save geocoded addresses: {}", e.to_string()),
+            }
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+}
+
+/// The `Reverse` struct holds the reverse geocoding widget, which describes a location picked on
+/// the map or typed as coordinates by the nearest addresses, the parcel and the boundaries there.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Reverse {
+    /// The id of the dataset to search in the [`Catalog`](crate::catalog::Catalog).
+    pub dataset: Option<uuid::Uuid>,
+    /// The `latitude` field holds the latitude typed by the user.
+    pub latitude: String,
+    /// The `longitude` field holds the longitude typed by the user.
+    pub longitude: String,
+    /// The `picking` field indicates the pick tool is active, so a click on the map sets the
+    /// location.
+    pub picking: bool,
+    /// The `result` field holds the description of the last location.
+    pub result: Option<ReverseGeocode>,
+    pub table: Option<TableView<NearbyAddresses, NearbyAddress, String>>,
+    /// Packages the location and nearest address for delivery to the map window.
+    pub package: Option<CandidatePoints>,
+    /// The `focus` field holds the latitude and longitude of a point the map should zoom to.
+    #[serde(skip)]
+    pub focus: Option<(f64, f64)>,
+    /// The `pending` field indicates a location was picked on the map and awaits lookup.
+    #[serde(skip)]
+    pending: bool,
+    /// The `points` field holds the address points of the dataset.
+    #[serde(skip)]
+    points: Option<Arc<AddressPoints>>,
+    /// The dataset and record count held in `points`.
+    #[serde(skip)]
+    indexed: Option<(uuid::Uuid, usize)>,
+    /// The `boundaries` field holds the city limits and the boundaries of the export profiles.
+    #[serde(skip)]
+    boundaries: Option<Arc<Vec<Boundary>>>,
+    pub visible: bool,
+}
+
+impl Reverse {
+    pub fn combo(
+        &mut self,
+        ui: &mut egui::Ui,
+        parent_tree: &mut focus::Tree,
+        data: &data::Data,
+        parcels: Option<&Parcels>,
+    ) {
+        let mut tree = focus::Tree::new();
+        let parent_node = tree.with_new_window();
+        if self.dataset.is_none() {
+            self.dataset = data.catalog.first().map(|v| v.id);
+        }
+        data.catalog.combo(ui, "Address dataset", &mut self.dataset);
+        ui.horizontal(|ui| {
+            ui.label("Latitude");
+            ui.add(egui::TextEdit::singleline(&mut self.latitude).desired_width(100.0));
+            ui.label("Longitude");
+            ui.add(egui::TextEdit::singleline(&mut self.longitude).desired_width(100.0));
+        });
+        ui.horizontal(|ui| {
+            let locate = ui.button("Locate");
+            tree.with_new_leaf(parent_node, &locate);
+            // Register button with focus tree.
+            tree.focusable(&locate);
+            if locate.clicked() {
+                self.pending = true;
+            }
+            if parent_tree.enter.is_some() {
+                if let Some(id) = parent_tree.current_leaf() {
+                    if id == locate.id {
+                        tracing::info!("Locate button in focus.");
+                        self.pending = true;
+                        // Clear the `enter` field after taking action.
+                        parent_tree.enter = None;
+                    }
+                }
+            }
+            if ui.selectable_label(self.picking, "Pick on Map").clicked() {
+                self.picking = !self.picking;
+            }
+            if self.picking {
+                ui.label("Click the map to pick a location.");
+            }
+        });
+        if self.pending {
+            self.pending = false;
+            self.locate(data, parcels);
+        }
+        if let Some(result) = &self.result {
+            ui.label(format!(
+                "Location: {:.6}, {:.6}",
+                result.latitude, result.longitude
+            ));
+            ui.label(format!(
+                "Parcel: {}",
+                result.parcel.as_deref().unwrap_or("None")
+            ));
+            let within = if result.boundaries.is_empty() {
+                "None".to_string()
+            } else {
+                result.boundaries.join(", ")
+            };
+            ui.label(format!("Within: {within}"));
+            if !result.estimated {
+                ui.label("Too few addresses nearby to place the location exactly, so the parcel and boundaries are those of the nearest address.");
+            }
+        }
+        if let Some(t) = &mut self.table {
+            let zoom = ui.button("Zoom to Address");
+            tree.with_new_leaf(parent_node, &zoom);
+            // Register button with focus tree.
+            tree.focusable(&zoom);
+            if zoom.clicked() {
+                self.focus = t
+                    .row_select
+                    .and_then(|id| t.data.iter().find(|v| v.id == id))
+                    .map(|v| (v.latitude, v.longitude));
+            }
+            ui.push_id("reverse", |ui| {
+                t.table(ui);
+            });
+        }
+        if parent_tree.contains_new(&tree) {
+            parent_tree.graft(tree);
+            tracing::info!("Reverse geocode tree added.");
+        }
+    }
+
+    /// Sets the location to a point picked on the map at `latitude` and `longitude`.
+    pub fn place(&mut self, latitude: f64, longitude: f64) {
+        self.latitude = format!("{latitude:.6}");
+        self.longitude = format!("{longitude:.6}");
+        self.pending = true;
+    }
+
+    /// Loads the address points of the selected dataset, unless they are current.
+    fn index(&mut self, data: &data::Data) -> Option<Arc<AddressPoints>> {
+        let dataset = self.dataset.and_then(|id| data.catalog.get(&id))?;
+        let key = (dataset.id, dataset.records);
+        if self.indexed != Some(key) || self.points.is_none() {
+            self.points = Some(Arc::new(AddressPoints::from(&*dataset.addresses)));
+            self.indexed = Some(key);
+        }
+        self.points.clone()
+    }
+
+    /// Loads the city limits and the boundaries of the export profiles, once.
+    fn boundaries(&mut self) -> Arc<Vec<Boundary>> {
+        if let Some(boundaries) = &self.boundaries {
+            return boundaries.clone();
+        }
+        let mut boundaries = Vec::new();
+        match Boundary::load(CITY_LIMITS_PATH) {
+            Ok(boundary) => boundaries.push(boundary),
+            Err(e) => tracing::warn!("Could not load city limits: {}", e.to_string()),
+        }
+        match ProfileConfig::with_config() {
+            Ok(profiles) => {
+                for profile in profiles {
+                    match profile.boundary() {
+                        Ok(boundary) => boundaries.push(boundary),
+                        Err(e) => tracing::warn!(
+                            "Could not load boundary of {}: {}",
+                            profile.name,
+                            e.to_string()
+                        ),
+                    }
+                }
+            }
+            Err(e) => tracing::warn!("Could not read export profiles: {}", e.to_string()),
+        }
+        let boundaries = Arc::new(boundaries);
+        self.boundaries = Some(boundaries.clone());
+        boundaries
+    }
+
+    /// Describes the location in the coordinate fields.
+    pub fn locate(&mut self, data: &data::Data, parcels: Option<&Parcels>) {
+        let (Ok(latitude), Ok(longitude)) = (
+            self.latitude.trim().parse::<f64>(),
+            self.longitude.trim().parse::<f64>(),
+        ) else {
+            info!("Enter the latitude and longitude in decimal degrees.");
+            return;
+        };
+        let Some(points) = self.index(data) else {
+            info!("Select a dataset to search.");
+            return;
+        };
+        let boundaries = self.boundaries();
+        let result = points.reverse(latitude, longitude, NEARBY, parcels, &boundaries);
+        info!(
+            "Reverse geocoded {:.6}, {:.6} to {} nearby addresses.",
+            latitude,
+            longitude,
+            result.nearby.len()
+        );
+        let config = TableConfig::new().striped().with_slider();
+        self.table = Some(TableView::with_config(result.nearby.clone(), config));
+        self.package = Some(CandidatePoints::from(&result));
+        self.result = Some(result);
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+}
+
+/// The `Lexis` struct holds the range export widget.  LexisNexis was the first vendor to request
+/// a street-range table, and the boundary, columns and file format of each vendor table are now
+/// read from an [`ExportProfile`] in the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Lexis {
+    pub boundary: Boundary,
+    pub boundary_view: BoundaryView,
+    /// The id of the selected dataset in the [`Catalog`](crate::catalog::Catalog).
+    pub selected: Option<uuid::Uuid>,
+    /// Export profiles read from the config file.
+    #[serde(deserialize_with = "crate::project::v1::profiles")]
+    pub profiles: Vec<ExportProfile>,
+    /// The index of the active profile in `profiles`.
+    pub profile: usize,
+    pub view: Option<TableView<LexisNexis, LexisNexisItem, String>>,
+    pub package: Option<TableView<LexisNexis, LexisNexisItem, String>>,
+    pub address_pkg: Option<Vec<SpatialAddresses>>,
+    pub boundary_pkg: Option<BoundaryView>,
+    /// Addresses inside the boundary from the last run, used to validate the ranges.
+    pub inclusion: SpatialAddresses,
+    /// Addresses outside the boundary from the last run, used to validate the ranges.
+    pub exclusion: SpatialAddresses,
+    /// Holds the results of range validation for display.
+    pub qa: Option<TableView<Issues, Issue, String>>,
+    /// Packages range validation results for delivery to the map window.
+    pub qa_pkg: Option<Issues>,
+    visible: bool,
+}
+
+impl Lexis {
+    pub fn try_default() -> Clean<Self> {
+        let profiles = ProfileConfig::with_config()?;
+        let boundary = profiles[0].boundary()?;
+        if let Some(boundary_view) = BoundaryView::from_shp(&boundary) {
+            Ok(Self {
+                boundary,
+                boundary_view: boundary_view.clone(),
+                selected: Default::default(),
+                profiles,
+                profile: Default::default(),
+                view: None,
+                package: None,
+                address_pkg: None,
+                boundary_pkg: Some(boundary_view),
+                inclusion: Default::default(),
+                exclusion: Default::default(),
+                qa: None,
+                qa_pkg: None,
+                visible: false,
+            })
+        } else {
+            Err(Bandage::Hint(
+                "Could not load lexis boundary view.".to_string(),
+            ))
+        }
+    }
+
+    /// Sets the active export profile to the profile at `index`, loading the boundary associated
+    /// with the profile.
+    pub fn set_profile(&mut self, index: usize) -> Clean<()> {
+        let Some(profile) = self.profiles.get(index) else {
+            return Err(Bandage::Hint(format!(
+                "No export profile at index {index}."
+            )));
+        };
+        let boundary = profile.boundary()?;
+        if let Some(boundary_view) = BoundaryView::from_shp(&boundary) {
+            self.boundary = boundary;
+            self.boundary_view = boundary_view.clone();
+            // Package the new boundary for delivery to the map window.
+            self.boundary_pkg = Some(boundary_view);
+            self.profile = index;
+            // Ranges from the previous profile no longer apply.
+            self.view = None;
+            self.package = None;
+            self.qa = None;
+            self.inclusion = Default::default();
+            self.exclusion = Default::default();
+            // Send empty packages so the map clears the old points and issues.
+            self.address_pkg = Some(vec![Default::default(), Default::default()]);
+            self.qa_pkg = Some(Default::default());
+            info!("Export profile set to {}", self.active_name());
+            Ok(())
+        } else {
+            Err(Bandage::Hint(
+                "Could not load profile boundary view.".to_string(),
+            ))
+        }
+    }
+
+    /// The active export profile.  Falls back to the first profile if the persisted index is out
+    /// of range, as when profiles are removed from the config file.
+    pub fn active(&self) -> Option<&ExportProfile> {
+        self.profiles
+            .get(self.profile)
+            .or_else(|| self.profiles.first())
+    }
+
+    fn active_name(&self) -> String {
+        self.active().map(|v| v.name.clone()).unwrap_or_default()
+    }
+
+    pub fn combo(&mut self, ui: &mut egui::Ui, parent_tree: &mut focus::Tree, data: &data::Data) {
+        let mut tree = focus::Tree::new();
+        let parent_node = tree.with_new_window();
+        if self.profile >= self.profiles.len() {
+            self.profile = 0;
+        }
+        ui.push_id("profile", |ui| {
+            egui::ComboBox::from_label("Select export profile")
+                .selected_text(self.active_name())
+                .show_ui(ui, |ui| {
+                    let mut select = None;
+                    for (i, profile) in self.profiles.iter().enumerate() {
+                        if ui
+                            .selectable_label(i == self.profile, profile.name.clone())
+                            .clicked()
+                        {
+                            select = Some(i);
+                        }
+                    }
+                    if let Some(i) = select {
+                        if let Err(e) = self.set_profile(i) {
+                            tracing::warn!("Could not set profile: {}", e.to_string());
+                        }
+                    }
+                });
+        });
+        if data.catalog.is_empty() {
+            ui.label("No address data loaded.");
+        } else {
+            data.catalog
+                .combo(ui, "Select address dataset", &mut self.selected);
+            ui.horizontal(|ui| {
+                let run = ui.button("Run");
+                tree.with_new_leaf(parent_node, &run);
+                // Register button with focus tree.
+                tree.focusable(&run);
+                if run.clicked() {
+                    self.run(data);
+                }
+                let save = ui.button("Save");
+                tree.with_new_leaf(parent_node, &save);
+                // Register button with focus tree.
+                tree.focusable(&save);
+                if save.clicked() {
+                    self.save();
+                }
+                let validate = ui.add_enabled(self.view.is_some(), egui::Button::new("Validate"));
+                tree.with_new_leaf(parent_node, &validate);
+                // Register button with focus tree.
+                tree.focusable(&validate);
+                if validate.clicked() {
+                    self.validate();
+                }
+                if parent_tree.enter.is_some() {
+                    tracing::info!("Enter detected in lexis widget.");
+                    if let Some(id) = parent_tree.current_leaf() {
+                        tracing::info!("Current focus: {:?}", id);
+                        tracing::info!("Run lexis id: {:?}", run.id);
+                        if id == run.id {
+                            tracing::info!("Run lexis button in focus.");
+                            self.run(data);
+                            // Clear the `enter` field after taking action.
+                            parent_tree.enter = None;
+                        }
+                        if id == save.id {
+                            tracing::info!("Save lexis button in focus.");
+                            self.save();
+                            // Clear the `enter` field after taking action.
+                            parent_tree.enter = None;
+                        }
+                        if id == validate.id {
+                            tracing::info!("Validate lexis button in focus.");
+                            self.validate();
+                            // Clear the `enter` field after taking action.
+                            parent_tree.enter = None;
+                        }
+                    }
+                }
+            });
+        }
+        if let Some(view) = &mut self.view {
+            view.table(ui);
+        }
+        self.qa_panel(ui);
+        if parent_tree.contains_new(&tree) {
+            parent_tree.graft(tree);
+            tracing::info!("LexisNexis tree added.");
+        }
+    }
+
+    /// Functionality for the run button in the Lexis Nexis widget.
+    pub fn run(&mut self, data: &data::Data) {
+        let Some(dataset) = self.selected.and_then(|id| data.catalog.get(&id)) else {
+            info!("Select an address dataset.");
+            return;
+        };
+        tracing::info!("Running LexisNexis.");
+        // `records` and `other` will hold addresses within and without the LexisNexis boundary.
+        // `records` are addresses inside City of Grants Pass service area.
+        let mut records = Vec::new();
+        // `other` are addresses outside the City of Grants Pass service area.
+        let mut other = Vec::new();
+        // `target` are the selected addresses to analzye.
+        let target = &*dataset.addresses;
+        // Convert to AddressPoints and then geo::geometry::Point type to access the spatial
+        // operation `contains` in the `geo` crate.
+        let ap = AddressPoints::from(target);
+        let gp = ap
+            .par_iter()
+            .map(|v| v.geo_point())
+            .collect::<Vec<geo::geometry::Point>>();
         // Use contains to determine whether each point is within the Lexis Nexis boundary.
         for (i, pt) in gp.iter().enumerate() {
             // info!("Point: {:#?}", pt);
@@ -480,6 +3697,11 @@ impl Lexis {
         tracing::info!("Exclusion records: {}", other.len());
         // Package the address point results for delivery to the map window.
         self.address_pkg = Some(vec![records.clone(), other.clone()]);
+        // Keep the split for range validation.
+        self.inclusion = records.clone();
+        self.exclusion = other.clone();
+        // Results of a previous validation no longer apply.
+        self.qa = None;
         // Build the Lexis Nexis table.
         let lexis = records.lexis_nexis(&other).unwrap();
         tracing::info!("LexisNexis records: {}", lexis.len());
@@ -491,29 +3713,81 @@ impl Lexis {
         self.package = view;
     }
 
-    /// Saves the Lexis Nexis table to a csv file on the local machine.
+    /// Functionality for the validate button in the Lexis Nexis widget.  Checks the ranges from the
+    /// last run for overlaps, swallowed exclusions, parity mix-ups and zip or community conflicts.
+    pub fn validate(&mut self) {
+        if let Some(view) = &self.view {
+            tracing::info!("Validating LexisNexis ranges.");
+            let issues = Issues::from_ranges(&view.data, &self.inclusion, &self.exclusion);
+            let config = TableConfig::new().with_search().striped();
+            self.qa = Some(TableView::with_config(issues.clone(), config));
+            // Package the issues for delivery to the map window.
+            self.qa_pkg = Some(issues);
+        }
+    }
+
+    /// Displays the range validation results, with a filter by type of issue.
+    pub fn qa_panel(&mut self, ui: &mut egui::Ui) {
+        if let Some(t) = &mut self.qa {
+            ui.separator();
+            ui.heading(format!("Range QA: {} issues", t.data.len()));
+            ui.horizontal(|ui| {
+                ui.label("Filter:");
+                for kind in IssueKind::iter() {
+                    let name = kind.to_string().to_lowercase();
+                    if ui
+                        .radio_value(&mut t.filter, Some(name.clone()), kind.to_string())
+                        .clicked()
+                    {
+                        t.view = t.data.clone().filter(&name);
+                        self.qa_pkg = Some(t.view.clone());
+                    }
+                }
+                if ui.radio_value(&mut t.filter, None, "None").clicked() {
+                    t.view = t.data.clone();
+                    self.qa_pkg = Some(t.view.clone());
+                }
+            });
+            ui.push_id("range_qa", |ui| {
+                t.table(ui);
+            });
+        }
+    }
+
+    /// Saves the range table to a file on the local machine, using the column layout and format
+    /// of the active export profile.
     pub fn save(&self) {
+        let Some(profile) = self.active() else {
+            info!("No export profile selected.");
+            return;
+        };
+        let extension = profile.format.extension();
         // Get path to current working directory.
         let path = env::current_dir().expect("Could not read current directory.");
         // Use the `rfd` crate to manage the file dialog.
         let file = rfd::FileDialog::new()
-            // Restrict visible files to type "csv".
-            .add_filter("csv", &["csv"])
+            // Restrict visible files to the extension of the profile format.
+            .add_filter(extension, &[extension])
             // Start the dialog view in the current working directory.
             .set_directory(&path)
-            // Start with the default save name as "lexisnexis.csv".
-            .set_file_name("lexisnexis.csv")
+            // Start with the default save name from the profile.
+            .set_file_name(profile.default_file_name())
             .save_file();
         // From the file handle defined by the dialog...
         if let Some(path) = file {
-            if let Some(mut view) = self.view.clone() {
-                info!("Saving Lexis Nexis table.");
+            if let Some(view) = &self.view {
+                info!("Saving {} table.", profile.name);
+                let summary = format!(
+                    "Exported {} range table to {}.",
+                    profile.name,
+                    path.display()
+                );
                 // The `data` field in a `TableView` holds the complete table data, without
                 // filters.
-                view.data
-                    // Write the LexisNexis table to a csv file.
-                    .to_csv(path)
-                    .expect("Could not save LexisNexis table to csv.");
+                profile
+                    .write(&view.data, path)
+                    .expect("Could not save range table.");
+                audit::record(AuditKind::Export, self.selected, &summary);
             }
         }
     }
@@ -535,6 +3809,10 @@ impl Tabular<LexisNexisItem> for LexisNexis {
     fn rows(&self) -> Vec<LexisNexisItem> {
         self.to_vec()
     }
+
+    fn set_rows(&mut self, rows: Vec<LexisNexisItem>) {
+        **self = rows;
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter, Deserialize, Serialize)]
@@ -564,22 +3842,19 @@ impl fmt::Display for LexisNexisColumns {
 }
 
 impl Columnar for LexisNexisItem {
-    fn values(&self) -> Vec<String> {
-        let number_from = format!("{}", self.address_number_from);
-        let number_to = format!("{}", self.address_number_to);
+    fn cells(&self) -> Vec<Cell> {
         let mut directional = "".to_string();
         if let Some(dir) = &self.street_name_pre_directional {
             directional.push_str(dir);
         }
-        let zip = format!("{}", self.zip_code);
         vec![
-            number_from,
-            number_to,
-            directional,
-            self.street_name.clone(),
-            self.street_name_post_type.clone(),
-            self.postal_community.clone(),
-            zip,
+            Cell::Integer(self.address_number_from),
+            Cell::Integer(self.address_number_to),
+            Cell::Text(directional),
+            Cell::Text(self.street_name.clone()),
+            Cell::Text(self.street_name_post_type.clone()),
+            Cell::Text(self.postal_community.clone()),
+            Cell::Integer(self.zip_code),
         ]
     }
 
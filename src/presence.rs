@@ -0,0 +1,385 @@
+//! The `presence` module compares any number of address sources at once.  Each unique address
+//! becomes a row of the [`PresenceMatrix`], recording which sources contain the address and which
+//! fields disagree between the sources.
+use crate::table::{Cell, Columnar, Filtration, Tabular};
+use crate::utils::distance;
+use address::prelude::{Address, SpatialAddress, SpatialAddresses};
+use aid::prelude::{Bandage, Clean};
+use derive_more::{Deref, DerefMut};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// Distance in meters beyond which the locations of the same address are said to disagree.
+pub const LOCATION_TOLERANCE: f64 = 30.0;
+
+/// The `Source` struct names an address source in the [`PresenceMatrix`].  Sources are told
+/// apart by the id of their dataset, since labels can be edited and need not be unique.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+pub struct Source {
+    /// The id of the dataset in the [`Catalog`](crate::catalog::Catalog).
+    pub id: uuid::Uuid,
+    /// The dataset label, for display.
+    pub name: String,
+}
+
+impl Source {
+    pub fn new(id: uuid::Uuid, name: &str) -> Self {
+        Self {
+            id,
+            name: name.to_string(),
+        }
+    }
+}
+
+/// The `PresenceFilter` enum selects the rows of the [`PresenceMatrix`] on display.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum PresenceFilter {
+    /// Records present in more than one source with differing fields.
+    #[default]
+    Disagreements,
+    /// Records missing from the source with the given dataset id.
+    Missing(uuid::Uuid),
+}
+
+/// The `Presence` struct is a row in the [`PresenceMatrix`], representing a unique address.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Presence {
+    /// The `id` field holds a [`uuid::Uuid`] for use by the [`crate::table::TableView`].
+    pub id: uuid::Uuid,
+    /// The `label` field is the full address label.
+    pub label: String,
+    /// The `present` field lists the sources containing the address.
+    pub present: Vec<Source>,
+    /// The `missing` field lists the sources missing the address.
+    pub missing: Vec<Source>,
+    /// The `disagreements` field lists the fields that differ between the sources.
+    pub disagreements: Vec<String>,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Presence {
+    /// Creates a row from the versions of an address found in each source.  The `versions`
+    /// parameter holds the source and the address, if present.
+    pub fn new(label: &str, versions: &[(&Source, Option<&SpatialAddress>)]) -> Self {
+        let mut present = Vec::new();
+        let mut missing = Vec::new();
+        let mut found = Vec::new();
+        for (source, address) in versions {
+            match address {
+                Some(value) => {
+                    present.push((*source).clone());
+                    found.push(*value);
+                }
+                None => missing.push((*source).clone()),
+            }
+        }
+        let disagreements = disagreements(&found);
+        let (latitude, longitude) = match found.first() {
+            Some(value) => (value.latitude, value.longitude),
+            None => (0.0, 0.0),
+        };
+        Self {
+            id: uuid::Uuid::new_v4(),
+            label: label.to_string(),
+            present,
+            missing,
+            disagreements,
+            latitude,
+            longitude,
+        }
+    }
+
+    /// The combination of sources containing the address, as a display string.
+    pub fn combination(&self) -> String {
+        names(&self.present, " + ")
+    }
+
+    /// Returns true if the source with dataset id `id` contains the address.
+    pub fn is_present(&self, id: &uuid::Uuid) -> bool {
+        self.present.iter().any(|v| &v.id == id)
+    }
+
+    /// Returns true if the source with dataset id `id` is missing the address.
+    pub fn is_missing(&self, id: &uuid::Uuid) -> bool {
+        self.missing.iter().any(|v| &v.id == id)
+    }
+}
+
+/// Joins the names of `sources` with `separator`.
+fn names(sources: &[Source], separator: &str) -> String {
+    sources
+        .iter()
+        .map(|v| v.name.as_str())
+        .collect::<Vec<&str>>()
+        .join(separator)
+}
+
+/// Lists the fields that differ between versions of the same address.
+fn disagreements(found: &[&SpatialAddress]) -> Vec<String> {
+    let mut fields = Vec::new();
+    if let Some(first) = found.first() {
+        let rest = &found[1..];
+        if rest.iter().any(|v| v.zip() != first.zip()) {
+            fields.push("Zip".to_string());
+        }
+        if rest.iter().any(|v| v.status() != first.status()) {
+            fields.push("Status".to_string());
+        }
+        if rest
+            .iter()
+            .any(|v| v.postal_community.to_uppercase() != first.postal_community.to_uppercase())
+        {
+            fields.push("Community".to_string());
+        }
+        if rest
+            .iter()
+            .any(|v| v.subaddress_type() != first.subaddress_type())
+        {
+            fields.push("Subaddress".to_string());
+        }
+        if rest.iter().any(|v| {
+            distance(v.latitude, v.longitude, first.latitude, first.longitude) > LOCATION_TOLERANCE
+        }) {
+            fields.push("Location".to_string());
+        }
+    }
+    fields
+}
+
+#[derive(EnumIter, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PresenceColumns {
+    Address,
+    Sources,
+    Present,
+    Missing,
+    Disagreements,
+}
+
+impl PresenceColumns {
+    pub fn value(&self, record: &Presence) -> Cell {
+        match self {
+            Self::Address => Cell::Text(record.label.clone()),
+            Self::Sources => Cell::from(record.present.len()),
+            Self::Present => Cell::Text(names(&record.present, ", ")),
+            Self::Missing => Cell::Text(names(&record.missing, ", ")),
+            Self::Disagreements => Cell::Text(record.disagreements.join(", ")),
+        }
+    }
+}
+
+impl fmt::Display for PresenceColumns {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Address => write!(f, "Address"),
+            Self::Sources => write!(f, "Sources"),
+            Self::Present => write!(f, "Present In"),
+            Self::Missing => write!(f, "Missing From"),
+            Self::Disagreements => write!(f, "Disagreements"),
+        }
+    }
+}
+
+impl Columnar for Presence {
+    fn cells(&self) -> Vec<Cell> {
+        PresenceColumns::iter()
+            .map(|v| v.value(self))
+            .collect::<Vec<Cell>>()
+    }
+
+    fn id(&self) -> uuid::Uuid {
+        self.id
+    }
+}
+
+/// The `PresenceMatrix` struct holds one [`Presence`] row for each unique address across the
+/// compared sources.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize, Deref, DerefMut)]
+pub struct PresenceMatrix(Vec<Presence>);
+
+impl PresenceMatrix {
+    /// Compares the address sets in `sources`, where each entry pairs a source with the addresses
+    /// from that source.  Addresses are identified by their uppercase label.
+    pub fn compare(sources: &[(Source, &SpatialAddresses)]) -> Self {
+        // Index each source by label.
+        let indices = sources
+            .iter()
+            .map(|(_, addresses)| {
+                let mut index = HashMap::new();
+                for address in addresses.iter() {
+                    index
+                        .entry(address.label().to_uppercase())
+                        .or_insert(address);
+                }
+                index
+            })
+            .collect::<Vec<HashMap<String, &SpatialAddress>>>();
+        // The set of unique labels across all sources, in sorted order.
+        let mut labels = BTreeMap::new();
+        for (_, addresses) in sources {
+            for address in addresses.iter() {
+                labels
+                    .entry(address.label().to_uppercase())
+                    .or_insert(address.label());
+            }
+        }
+        let records = labels
+            .iter()
+            .map(|(key, label)| {
+                let versions = sources
+                    .iter()
+                    .zip(indices.iter())
+                    .map(|((source, _), index)| (source, index.get(key).copied()))
+                    .collect::<Vec<(&Source, Option<&SpatialAddress>)>>();
+                Presence::new(label, &versions)
+            })
+            .collect::<Vec<Presence>>();
+        tracing::info!("Unique addresses: {}", records.len());
+        Self(records)
+    }
+
+    /// Counts the addresses in each combination of sources, largest first, for an UpSet-style
+    /// breakdown.  Combinations are counted by dataset id, so sources sharing a label stay apart.
+    pub fn combinations(&self) -> PresenceSummary {
+        let mut counts = BTreeMap::new();
+        for record in self.iter() {
+            let key = record
+                .present
+                .iter()
+                .map(|v| v.id)
+                .collect::<Vec<uuid::Uuid>>();
+            counts.entry(key).or_insert((record.combination(), 0)).1 += 1;
+        }
+        let total = self.len().max(1) as f64;
+        let mut rows = counts
+            .into_values()
+            .map(|(sources, count)| Combination {
+                id: uuid::Uuid::new_v4(),
+                sources,
+                count,
+                share: count as f64 / total,
+            })
+            .collect::<Vec<Combination>>();
+        rows.sort_by(|a, b| b.count.cmp(&a.count));
+        PresenceSummary(rows)
+    }
+
+    /// Counts of addresses present in and missing from the source with dataset id `id`.
+    pub fn totals(&self, id: &uuid::Uuid) -> (usize, usize) {
+        let present = self.iter().filter(|v| v.is_present(id)).count();
+        (present, self.len() - present)
+    }
+
+    /// Writes the matrix to a csv file at `path`.
+    pub fn to_csv<P: AsRef<Path>>(&self, path: P) -> Clean<()> {
+        let mut wtr = csv::Writer::from_path(path).map_err(|e| Bandage::Hint(e.to_string()))?;
+        wtr.write_record(Self::headers())
+            .map_err(|e| Bandage::Hint(e.to_string()))?;
+        for record in self.iter() {
+            wtr.write_record(record.values())
+                .map_err(|e| Bandage::Hint(e.to_string()))?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+impl Tabular<Presence> for PresenceMatrix {
+    fn headers() -> Vec<String> {
+        PresenceColumns::iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>()
+    }
+
+    fn rows(&self) -> Vec<Presence> {
+        self.to_vec()
+    }
+
+    fn set_rows(&mut self, rows: Vec<Presence>) {
+        self.0 = rows;
+    }
+}
+
+impl Filtration<PresenceMatrix, PresenceFilter> for PresenceMatrix {
+    /// Filters to records missing from a source, or to records whose sources disagree.
+    fn filter(&mut self, filter: &PresenceFilter) -> Self {
+        let mut records = self.to_vec();
+        match filter {
+            PresenceFilter::Disagreements => records.retain(|v| !v.disagreements.is_empty()),
+            PresenceFilter::Missing(id) => records.retain(|v| v.is_missing(id)),
+        }
+        Self(records)
+    }
+}
+
+/// The `Combination` struct counts the addresses present in exactly the sources named in
+/// `sources`.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Combination {
+    pub id: uuid::Uuid,
+    pub sources: String,
+    pub count: usize,
+    pub share: f64,
+}
+
+impl Columnar for Combination {
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Text(self.sources.clone()),
+            Cell::from(self.count),
+            Cell::Float(self.share * 100.0, 1),
+        ]
+    }
+
+    fn id(&self) -> uuid::Uuid {
+        self.id
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize, Deref, DerefMut)]
+pub struct PresenceSummary(Vec<Combination>);
+
+impl Tabular<Combination> for PresenceSummary {
+    fn headers() -> Vec<String> {
+        vec![
+            "Sources".to_string(),
+            "Addresses".to_string(),
+            "Share (%)".to_string(),
+        ]
+    }
+
+    fn rows(&self) -> Vec<Combination> {
+        self.to_vec()
+    }
+
+    fn set_rows(&mut self, rows: Vec<Combination>) {
+        self.0 = rows;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::AddressPoint;
+
+    #[test]
+    fn sources_sharing_a_label_stay_apart() {
+        let first = Source::new(uuid::Uuid::new_v4(), "City (file)");
+        let second = Source::new(uuid::Uuid::new_v4(), "City (file)");
+        let both =
+            SpatialAddresses::from(&[AddressPoint::sample(100, "MAIN", 97526, 42.44, -123.33)][..]);
+        let one =
+            SpatialAddresses::from(&[AddressPoint::sample(102, "MAIN", 97526, 42.44, -123.33)][..]);
+        let mut matrix = PresenceMatrix::compare(&[(first.clone(), &both), (second.clone(), &one)]);
+        assert_eq!(matrix.totals(&first.id), (1, 1));
+        assert_eq!(matrix.totals(&second.id), (1, 1));
+        assert_eq!(matrix.combinations().len(), 2);
+        let missing = matrix.filter(&PresenceFilter::Missing(second.id));
+        assert_eq!(missing.len(), 1);
+        assert!(missing[0].is_present(&first.id));
+    }
+}
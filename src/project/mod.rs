@@ -0,0 +1,324 @@
+//! The `project` module defines the project file format.  A project file holds the tab layout of
+//! the application, and with it the loaded datasets, boundaries, table settings and operation
+//! results of each tab.
+//!
+//! Each file starts with the magic bytes [`PROJECT_MAGIC`] and a little-endian `u32` version,
+//! followed by the bincode-encoded [`Project`].  Bincode is positional, so adding, removing or
+//! reordering a field of any saved type changes the layout, and `#[serde(default)]` does not
+//! help.  When the layout changes, bump [`PROJECT_VERSION`], copy each changed type as it was
+//! into a module named for the old version, and implement `From` the old type for the new one.
+//! Fields holding a changed type pick the layout to read with `deserialize_with` and [`layout`],
+//! and [`Project::migrate`] decodes the old version through [`decode`].  See [`v1`].
+//!
+//! Headerless files, such as the `data/state.data` written by earlier builds, hold a single tab in
+//! a layout that is no longer kept, so they are rejected rather than misread.
+//!
+//! Versions:
+//!
+//! 1. The first layout with a header, saving each table view in full.  Read through [`v1`].
+//! 2. Saves only the data and [`TableConfig`](crate::table::TableConfig) of each table view, and
+//!    adds the sort keys, lifecycle dates and widgets since version 1.
+pub mod v1;
+
+use crate::state::lens::Lens;
+use aid::prelude::{Bandage, Clean};
+use chrono::{DateTime, Utc};
+use derive_more::{Deref, DerefMut};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::path::{Path, PathBuf};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// Magic bytes identifying a project file.
+pub const PROJECT_MAGIC: &[u8; 4] = b"AMSP";
+/// Version of the project layout written by this build.
+pub const PROJECT_VERSION: u32 = 2;
+/// File extension of project files.
+pub const PROJECT_EXTENSION: &str = "ams";
+/// Project saved on exit when the user has not chosen a project file.
+pub const DEFAULT_PROJECT: &str = "data/workspace.ams";
+/// Location of the recent projects list.
+pub const RECENT_PATH: &str = "data/recent.data";
+/// Number of projects kept in the recent projects list.
+pub const RECENT_LIMIT: usize = 10;
+/// Location of the autosaved session, removed on a clean exit.
+pub const RECOVERY_PATH: &str = "data/recovery.ams";
+/// Time between autosaves.
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+thread_local! {
+    /// Version of the layout being decoded on this thread.
+    static LAYOUT: Cell<u32> = const { Cell::new(PROJECT_VERSION) };
+}
+
+/// Version of the layout being decoded on this thread, [`PROJECT_VERSION`] outside [`decode`].
+pub fn layout() -> u32 {
+    LAYOUT.with(|v| v.get())
+}
+
+/// Decodes `body` as written with layout `version`, so that fields reading a changed type pick
+/// the old layout through [`layout`].
+pub fn decode<T: DeserializeOwned>(version: u32, body: &[u8]) -> Clean<T> {
+    let previous = LAYOUT.with(|v| v.replace(version));
+    let result = bincode::deserialize(body);
+    LAYOUT.with(|v| v.set(previous));
+    Ok(result?)
+}
+
+/// The `Project` struct holds the saved state of the application.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Project {
+    /// The `saved` field records when the project was last saved.
+    pub saved: DateTime<Utc>,
+    /// The `tabs` field holds the tab layout, including the state of each tab.
+    pub tabs: egui_dock::DockState<Lens>,
+}
+
+/// Borrowed form of [`Project`] with the same layout, so the tabs can be encoded without a clone.
+#[derive(Serialize)]
+struct ProjectRef<'a> {
+    saved: DateTime<Utc>,
+    tabs: &'a egui_dock::DockState<Lens>,
+}
+
+/// Encodes `body` behind the magic bytes and version header.
+fn with_header<T: Serialize>(body: &T) -> Clean<Vec<u8>> {
+    let mut bytes = PROJECT_MAGIC.to_vec();
+    bytes.extend_from_slice(&PROJECT_VERSION.to_le_bytes());
+    bytes.extend(bincode::serialize(body)?);
+    Ok(bytes)
+}
+
+impl Project {
+    pub fn new(tabs: egui_dock::DockState<Lens>) -> Self {
+        Self {
+            saved: Utc::now(),
+            tabs,
+        }
+    }
+
+    /// Encodes the project with the version header.
+    pub fn to_bytes(&self) -> Clean<Vec<u8>> {
+        with_header(self)
+    }
+
+    /// Encodes `tabs` as a project saved now, borrowing the tabs rather than cloning them.
+    pub fn encode(tabs: &egui_dock::DockState<Lens>) -> Clean<Vec<u8>> {
+        with_header(&ProjectRef {
+            saved: Utc::now(),
+            tabs,
+        })
+    }
+
+    /// Saves `tabs` as a project at `path`.
+    pub fn save_tabs<P: AsRef<Path>>(tabs: &egui_dock::DockState<Lens>, path: P) -> Clean<()> {
+        std::fs::write(&path, Self::encode(tabs)?)?;
+        info!("Project saved to {}.", path.as_ref().display());
+        Ok(())
+    }
+
+    /// Decodes a project, migrating from the version in the header if necessary.
+    pub fn from_bytes(bytes: &[u8]) -> Clean<Self> {
+        match bytes.strip_prefix(&PROJECT_MAGIC[..]) {
+            Some(rest) if rest.len() >= 4 => {
+                let mut version = [0u8; 4];
+                version.copy_from_slice(&rest[..4]);
+                Self::migrate(u32::from_le_bytes(version), &rest[4..])
+            }
+            _ => Err(Bandage::Hint(
+                "Not an AMS project file.  State files from earlier builds cannot be read; \
+                 reload the source data and save a new project."
+                    .to_string(),
+            )),
+        }
+    }
+
+    /// Decodes the body of a project file written with layout `version`.
+    pub fn migrate(version: u32, body: &[u8]) -> Clean<Self> {
+        match version {
+            PROJECT_VERSION => decode(PROJECT_VERSION, body),
+            1 => Ok(decode::<v1::Project>(1, body)?.into()),
+            v if v > PROJECT_VERSION => Err(Bandage::Hint(format!(
+                "Project version {v} was written by a newer version of AMS."
+            ))),
+            v => Err(Bandage::Hint(format!("Unknown project version {v}."))),
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&mut self, path: P) -> Clean<()> {
+        self.saved = Utc::now();
+        std::fs::write(&path, self.to_bytes()?)?;
+        info!("Project saved to {}.", path.as_ref().display());
+        Ok(())
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> Clean<Self> {
+        let bytes = std::fs::read(&path)?;
+        let project = Self::from_bytes(&bytes)?;
+        info!("Project opened from {}.", path.as_ref().display());
+        Ok(project)
+    }
+}
+
+/// The `RecentProjects` struct holds the paths of recently opened projects, most recent first.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize, Deref, DerefMut)]
+pub struct RecentProjects(Vec<PathBuf>);
+
+impl RecentProjects {
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Clean<()> {
+        address::prelude::save(self, path)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Clean<Self> {
+        let vec: Vec<u8> = std::fs::read(path)?;
+        let recent: Self = bincode::deserialize(&vec[..])?;
+        Ok(recent)
+    }
+
+    /// Loads the list at [`RECENT_PATH`], or an empty list if none exists.
+    pub fn local() -> Self {
+        Self::load(RECENT_PATH).unwrap_or_default()
+    }
+
+    /// Moves `path` to the front of the list and saves the list to [`RECENT_PATH`].
+    pub fn add<P: AsRef<Path>>(&mut self, path: P) {
+        let path = path.as_ref().to_path_buf();
+        self.retain(|v| *v != path);
+        self.insert(0, path);
+        self.truncate(RECENT_LIMIT);
+        if let Err(e) = self.save(RECENT_PATH) {
+            tracing::warn!("Could not save recent projects: {}", e.to_string());
+        }
+    }
+}
+
+/// The `Autosave` struct periodically writes the workspace to [`RECOVERY_PATH`], so that a crash
+/// does not lose the session.  The tabs are cloned on the UI thread, sharing the address data of
+/// each dataset through its `Arc`, then encoded and written to disk on a background thread.
+#[derive(Debug)]
+pub struct Autosave {
+    last: Instant,
+    pending: Option<JoinHandle<Clean<()>>>,
+}
+
+impl Autosave {
+    /// The time of the next autosave.
+    pub fn next(&self) -> Instant {
+        self.last + AUTOSAVE_INTERVAL
+    }
+
+    /// Returns true if the interval since the last autosave has elapsed.
+    pub fn elapsed(&self) -> bool {
+        Instant::now() >= self.next()
+    }
+
+    /// Returns true while the previous write is still in flight.
+    pub fn busy(&self) -> bool {
+        match &self.pending {
+            Some(handle) => !handle.is_finished(),
+            None => false,
+        }
+    }
+
+    /// Puts off the next autosave by a full interval, so a skipped save does not leave the
+    /// deadline in the past and wake the event loop continuously.
+    pub fn defer(&mut self) {
+        self.last = Instant::now();
+    }
+
+    /// Writes a snapshot of `tabs` to the recovery file in the background.
+    pub fn save(&mut self, tabs: &egui_dock::DockState<Lens>) {
+        self.last = Instant::now();
+        if let Some(handle) = self.pending.take() {
+            match handle.join() {
+                Ok(Err(e)) => tracing::warn!("Autosave failed: {}", e.to_string()),
+                Err(_) => tracing::warn!("Autosave thread panicked."),
+                Ok(Ok(_)) => {}
+            }
+        }
+        let tabs = tabs.clone();
+        self.pending = Some(std::thread::spawn(move || {
+            let bytes = Project::encode(&tabs)?;
+            // Write to a temporary file first, so a crash mid-write leaves the last autosave intact.
+            let temp = format!("{RECOVERY_PATH}.tmp");
+            std::fs::write(&temp, bytes)?;
+            std::fs::rename(&temp, RECOVERY_PATH)?;
+            tracing::trace!("Session autosaved.");
+            Ok(())
+        }));
+    }
+
+    /// Reads the autosaved session left by a previous run, if any.
+    pub fn recover() -> Option<Project> {
+        if !Path::new(RECOVERY_PATH).exists() {
+            return None;
+        }
+        match Project::open(RECOVERY_PATH) {
+            Ok(project) => Some(project),
+            Err(e) => {
+                tracing::warn!("Could not read autosaved session: {}", e.to_string());
+                None
+            }
+        }
+    }
+
+    /// Removes the recovery file, after a clean exit or once the user declines to restore it.
+    pub fn clear(&mut self) {
+        if let Some(handle) = self.pending.take() {
+            let _ = handle.join();
+        }
+        if let Err(e) = std::fs::remove_file(RECOVERY_PATH) {
+            tracing::trace!("No recovery file removed: {}", e.to_string());
+        }
+    }
+}
+
+impl Default for Autosave {
+    fn default() -> Self {
+        Self {
+            last: Instant::now(),
+            pending: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::Validation;
+    use crate::table::SortKey;
+
+    #[test]
+    fn version_one_validation_migrates() {
+        let bytes = include_bytes!("../../tests/fixtures/validation_v1.bin");
+        let validation: Validation = decode::<v1::Validation>(1, bytes).unwrap().into();
+        // Layout 1 is only in effect while decoding.
+        assert_eq!(layout(), PROJECT_VERSION);
+        assert_eq!(
+            validation.dataset,
+            Some(uuid::Uuid::from_u128(
+                0x6f1c2d3e_4a5b_4c6d_8e7f_901a2b3c4d5e
+            ))
+        );
+        assert_eq!(validation.enabled.get("Street type"), Some(&true));
+        assert_eq!(validation.enabled.get("Service zip code"), Some(&false));
+        let table = validation.table.as_ref().unwrap();
+        assert_eq!(table.data.len(), 2);
+        assert_eq!(
+            table.config.sort,
+            vec![SortKey {
+                column: 1,
+                descending: true
+            }]
+        );
+        assert_eq!(validation.package.as_ref().map(|v| v.len()), Some(2));
+        assert!(validation.visible);
+        // The migrated widget survives a round trip through the current layout.
+        let bytes = bincode::serialize(&validation).unwrap();
+        let current: Validation = decode(PROJECT_VERSION, &bytes).unwrap();
+        assert_eq!(current, validation);
+    }
+}
@@ -0,0 +1,215 @@
+//! The `v1` module holds the types of project layout 1 that differ from the current layout, and
+//! converts each to its current form.  Bincode is positional, so a file can only be read with the
+//! exact types that wrote it.  Types that have not changed since version 1 are read as they are,
+//! and the fields holding a changed type read it through [`layout`] to pick the version.
+//!
+//! Layout 1 is the layout written by the last build to save version 1 files.  Earlier builds
+//! wrote version 1 headers over other layouts, and those files fail to decode.
+use crate::controls::focus;
+use crate::export::{self, RangeField};
+use crate::ops;
+use crate::prelude::{Columnar, Filtration, Tabular};
+use crate::presence::{Combination, PresenceSummary};
+use crate::project::layout;
+use crate::rules::{RuleIssue, RuleIssues, Rules};
+use crate::table::{self, CellEdit, TableConfig};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// The `Project` struct is a project in layout 1.  The tabs have the same shape in both layouts,
+/// so they are read with the current types while [`layout`] is 1.
+#[derive(Debug, Deserialize)]
+pub struct Project {
+    pub saved: DateTime<Utc>,
+    pub tabs: egui_dock::DockState<crate::state::lens::Lens>,
+}
+
+impl From<Project> for super::Project {
+    fn from(project: Project) -> Self {
+        Self {
+            saved: project.saved,
+            tabs: project.tabs,
+        }
+    }
+}
+
+/// The `TableView` struct is a table view in layout 1, which saved every field of the view.
+/// Only the data and configuration carry over, as in the current layout.
+#[derive(Debug, Deserialize)]
+pub struct TableView<T> {
+    pub data: T,
+    pub view: T,
+    pub package: Option<T>,
+    pub config: TableConfig,
+    pub tree: focus::Tree,
+    pub search: String,
+    pub selection: HashSet<Uuid>,
+    pub checks: HashMap<Uuid, bool>,
+    /// Every table filtered by a string in layout 1.
+    pub filter: Option<String>,
+    pub target: usize,
+    pub row_select: Option<Uuid>,
+    pub row_focus: Option<Uuid>,
+    pub edit: Option<CellEdit>,
+    pub edits: Vec<CellEdit>,
+    pub row_index: Option<usize>,
+    pub row_ids: Vec<Uuid>,
+    pub loaded: bool,
+    pub leaves: Vec<egui::Id>,
+}
+
+impl<T, U, V> From<TableView<T>> for table::TableView<T, U, V>
+where
+    T: Tabular<U> + Filtration<T, V> + Clone + Default,
+    U: Columnar + Default,
+    V: Default,
+{
+    fn from(table: TableView<T>) -> Self {
+        Self::with_config(table.data, table.config)
+    }
+}
+
+/// The `ExportColumn` struct is an export column in layout 1, before abbreviations moved from
+/// the profile to the column.
+#[derive(Debug, Deserialize)]
+pub struct ExportColumn {
+    pub field: RangeField,
+    pub header: String,
+}
+
+/// The `ExportProfile` struct is an export profile in layout 1, with one set of abbreviations
+/// for every column.
+#[derive(Debug, Deserialize)]
+pub struct ExportProfile {
+    pub name: String,
+    pub boundary: PathBuf,
+    pub file_name: String,
+    pub format: export::ExportFormat,
+    pub columns: Vec<ExportColumn>,
+    pub uppercase: bool,
+    pub abbreviations: BTreeMap<String, String>,
+}
+
+impl From<ExportProfile> for export::ExportProfile {
+    /// The abbreviations of layout 1 were written for directionals, so they move to the
+    /// directional column and no longer touch street names.
+    fn from(profile: ExportProfile) -> Self {
+        let columns = profile
+            .columns
+            .into_iter()
+            .map(|v| export::ExportColumn {
+                abbreviations: match v.field {
+                    RangeField::Directional => profile.abbreviations.clone(),
+                    _ => BTreeMap::new(),
+                },
+                field: v.field,
+                header: v.header,
+            })
+            .collect::<Vec<export::ExportColumn>>();
+        Self {
+            name: profile.name,
+            boundary: profile.boundary,
+            file_name: profile.file_name,
+            format: profile.format,
+            columns,
+            uppercase: profile.uppercase,
+        }
+    }
+}
+
+/// The `Presence` struct is a row of the presence matrix in layout 1, naming sources by label.
+#[derive(Debug, Deserialize)]
+pub struct Presence {
+    pub id: Uuid,
+    pub label: String,
+    pub present: Vec<String>,
+    pub missing: Vec<String>,
+    pub disagreements: Vec<String>,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+/// The `Matrix` struct is the N-way comparison widget in layout 1.
+#[derive(Debug, Deserialize)]
+pub struct Matrix {
+    pub excluded: HashSet<Uuid>,
+    pub names: Vec<String>,
+    pub table: Option<TableView<Vec<Presence>>>,
+    pub summary: Option<table::TableView<PresenceSummary, Combination, String>>,
+    pub visible: bool,
+}
+
+impl From<Matrix> for ops::Matrix {
+    /// Sources are now told apart by dataset id, which layout 1 did not record, so the results
+    /// are dropped and the comparison runs again on demand.  The excluded datasets carry over.
+    fn from(matrix: Matrix) -> Self {
+        Self {
+            excluded: matrix.excluded,
+            visible: matrix.visible,
+            ..Default::default()
+        }
+    }
+}
+
+/// The `Validation` struct is the attribute validation widget in layout 1, which saved the rules.
+#[derive(Debug, Deserialize)]
+pub struct Validation {
+    pub dataset: Option<Uuid>,
+    pub rules: Rules,
+    pub table: Option<table::TableView<RuleIssues, RuleIssue, String>>,
+    pub package: Option<RuleIssues>,
+    pub inspect: Option<String>,
+    pub visible: bool,
+}
+
+impl From<Validation> for ops::Validation {
+    /// The rules are read again from the config file, keeping whether each was switched on.
+    fn from(validation: Validation) -> Self {
+        let enabled = validation
+            .rules
+            .iter()
+            .map(|v| (v.name.clone(), v.enabled))
+            .collect::<BTreeMap<String, bool>>();
+        Self {
+            dataset: validation.dataset,
+            enabled,
+            table: validation.table,
+            package: validation.package,
+            inspect: validation.inspect,
+            visible: validation.visible,
+            ..Default::default()
+        }
+    }
+}
+
+/// Reads the export profiles of the range export widget in the layout being decoded.
+pub fn profiles<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<export::ExportProfile>, D::Error> {
+    match layout() {
+        1 => Ok(Vec::<ExportProfile>::deserialize(deserializer)?
+            .into_iter()
+            .map(export::ExportProfile::from)
+            .collect()),
+        _ => Vec::<export::ExportProfile>::deserialize(deserializer),
+    }
+}
+
+/// Reads the N-way comparison widget in the layout being decoded.
+pub fn matrix<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ops::Matrix, D::Error> {
+    match layout() {
+        1 => Ok(Matrix::deserialize(deserializer)?.into()),
+        _ => ops::Matrix::deserialize(deserializer),
+    }
+}
+
+/// Reads the attribute validation widget in the layout being decoded.
+pub fn validation<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ops::Validation, D::Error> {
+    match layout() {
+        1 => Ok(Validation::deserialize(deserializer)?.into()),
+        _ => ops::Validation::deserialize(deserializer),
+    }
+}
@@ -0,0 +1,604 @@
+//! The `qa` module holds the issue tables produced by quality assurance checks, and the map
+//! symbology used to highlight the location of each issue.
+use crate::table::{Cell, Columnar, Filtration, Tabular};
+use address::prelude::{Address, LexisNexis, LexisNexisItem, SpatialAddress, SpatialAddresses};
+use derive_more::{Deref, DerefMut};
+use galileo::galileo_types::cartesian::CartesianPoint3d;
+use galileo::galileo_types::geo::impls::GeoPoint2d;
+use galileo::galileo_types::geo::{GeoPoint, NewGeoPoint};
+use galileo::galileo_types::geometry::Geom;
+use galileo::galileo_types::geometry_type::{GeoSpace2d, GeometryType, PointGeometryType};
+use galileo::galileo_types::impls::{Contour, Polygon};
+use galileo::layer::feature_layer::symbol::Symbol;
+use galileo::layer::feature_layer::Feature;
+use galileo::render::point_paint::PointPaint;
+use galileo::render::render_bundle::RenderPrimitive;
+use galileo::Color;
+use num_traits::AsPrimitive;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// The `IssueKind` enum classifies the problems reported by quality assurance checks.
+#[derive(
+    Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter, Deserialize, Serialize,
+)]
+pub enum IssueKind {
+    /// Two ranges on the same street share address numbers.
+    #[default]
+    Overlap,
+    /// An address excluded from the boundary falls within an included range.
+    Exclusion,
+    /// The range endpoints share a parity, but member addresses have both odd and even numbers.
+    Parity,
+    /// A member address has a different zip code than the range.
+    Zip,
+    /// A member address has a different postal community than the range.
+    Community,
+}
+
+impl fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match self {
+            Self::Overlap => "Overlap",
+            Self::Exclusion => "Exclusion",
+            Self::Parity => "Parity",
+            Self::Zip => "Zip",
+            Self::Community => "Community",
+        };
+        write!(f, "{}", msg)
+    }
+}
+
+/// The `Issue` struct represents a single problem found by a quality assurance check.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Issue {
+    /// The `id` field holds a [`uuid::Uuid`] for use by the [`crate::table::TableView`].
+    pub id: uuid::Uuid,
+    /// The `kind` field classifies the issue.
+    pub kind: IssueKind,
+    /// The `label` field identifies the street range or address with the issue.
+    pub label: String,
+    /// The `detail` field provides a user-readable description of the problem.
+    pub detail: String,
+    /// The `latitude` field locates the issue on the map, if a location is known.
+    pub latitude: Option<f64>,
+    /// The `longitude` field locates the issue on the map, if a location is known.
+    pub longitude: Option<f64>,
+}
+
+impl Issue {
+    /// Creates a new `Issue` of type `kind` without a location.
+    pub fn new(kind: IssueKind, label: &str, detail: &str) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4(),
+            kind,
+            label: label.to_owned(),
+            detail: detail.to_owned(),
+            latitude: None,
+            longitude: None,
+        }
+    }
+
+    /// Sets the location of the issue to the location of `address`.
+    pub fn at(mut self, address: &SpatialAddress) -> Self {
+        self.latitude = Some(address.latitude);
+        self.longitude = Some(address.longitude);
+        self
+    }
+
+    /// Sets the location of the issue to the location of `address`, if present.
+    pub fn at_some(self, address: Option<&SpatialAddress>) -> Self {
+        match address {
+            Some(value) => self.at(value),
+            None => self,
+        }
+    }
+}
+
+#[derive(EnumIter, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IssueColumns {
+    Kind,
+    Label,
+    Detail,
+    Latitude,
+    Longitude,
+}
+
+impl IssueColumns {
+    pub fn value(&self, issue: &Issue) -> Cell {
+        match self {
+            Self::Kind => Cell::variant(&issue.kind),
+            Self::Label => Cell::Text(issue.label.clone()),
+            Self::Detail => Cell::Text(issue.detail.clone()),
+            Self::Latitude => issue.latitude.map_or(Cell::Empty, Cell::Coordinate),
+            Self::Longitude => issue.longitude.map_or(Cell::Empty, Cell::Coordinate),
+        }
+    }
+}
+
+impl fmt::Display for IssueColumns {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Kind => write!(f, "Issue"),
+            Self::Label => write!(f, "Label"),
+            Self::Detail => write!(f, "Detail"),
+            Self::Latitude => write!(f, "Latitude"),
+            Self::Longitude => write!(f, "Longitude"),
+        }
+    }
+}
+
+impl Columnar for Issue {
+    fn cells(&self) -> Vec<Cell> {
+        IssueColumns::iter()
+            .map(|v| v.value(self))
+            .collect::<Vec<Cell>>()
+    }
+
+    fn id(&self) -> uuid::Uuid {
+        self.id
+    }
+}
+
+/// The `Issues` struct is a wrapper around a vector of type [`Issue`], for display in a
+/// [`crate::table::TableView`].
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize, Deref, DerefMut)]
+pub struct Issues(Vec<Issue>);
+
+impl Issues {
+    /// Validates the street ranges in `ranges` against the included addresses `records` and the
+    /// excluded addresses `other` used to build them.
+    pub fn from_ranges(
+        ranges: &LexisNexis,
+        records: &SpatialAddresses,
+        other: &SpatialAddresses,
+    ) -> Self {
+        // Group the ranges and the addresses by street for lookup.
+        let mut streets = BTreeMap::new();
+        for item in ranges.iter() {
+            streets
+                .entry(item_street(item))
+                .or_insert(Vec::new())
+                .push(item);
+        }
+        let members = group_addresses(records);
+
+        let mut issues = Vec::new();
+        for (street, items) in streets.iter_mut() {
+            items.sort_by_key(|v| v.address_number_from);
+            let addresses = members.get(street);
+            issues.extend(overlaps(street, items, addresses));
+            for item in items.iter() {
+                issues.extend(membership(street, item, addresses));
+            }
+        }
+
+        // Excluded addresses should not fall within any range on the same street.
+        for address in other.iter() {
+            let street = address_street(address);
+            if let Some(items) = streets.get(&street) {
+                for item in items {
+                    if contains(item, address) {
+                        let detail = format!(
+                            "Excluded address falls in range {}-{}.",
+                            item.address_number_from, item.address_number_to
+                        );
+                        issues.push(
+                            Issue::new(IssueKind::Exclusion, &address.label(), &detail).at(address),
+                        );
+                    }
+                }
+            }
+        }
+        tracing::info!("Range issues found: {}", issues.len());
+        Self(issues)
+    }
+
+    /// Returns only the issues of type `kind`.
+    pub fn kind(&self, kind: &IssueKind) -> Self {
+        let issues = self
+            .iter()
+            .filter(|v| &v.kind == kind)
+            .cloned()
+            .collect::<Vec<Issue>>();
+        Self(issues)
+    }
+}
+
+impl Tabular<Issue> for Issues {
+    fn headers() -> Vec<String> {
+        IssueColumns::iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>()
+    }
+
+    fn rows(&self) -> Vec<Issue> {
+        self.to_vec()
+    }
+
+    fn set_rows(&mut self, rows: Vec<Issue>) {
+        self.0 = rows;
+    }
+}
+
+impl Filtration<Issues, String> for Issues {
+    /// Filters issues by the lowercase name of the [`IssueKind`].
+    fn filter(&mut self, filter: &String) -> Self {
+        let mut issues = self.to_vec();
+        issues.retain(|v| v.kind.to_string().to_lowercase() == filter.to_lowercase());
+        Self(issues)
+    }
+}
+
+/// Flags ranges on the same street that share address numbers.  Expects `items` sorted by the
+/// start of the range.  Each range is compared to the earlier range reaching furthest, so a range
+/// nested inside a longer range is caught even when other ranges fall between them.
+fn overlaps(
+    street: &str,
+    items: &[&LexisNexisItem],
+    addresses: Option<&Vec<&SpatialAddress>>,
+) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let Some((first, rest)) = items.split_first() else {
+        return issues;
+    };
+    let mut reach = *first;
+    for b in rest.iter().copied() {
+        let a = reach;
+        if b.address_number_to > a.address_number_to {
+            reach = b;
+        }
+        if b.address_number_from <= a.address_number_to {
+            let label = format!(
+                "{}-{} {}",
+                a.address_number_from, a.address_number_to, street
+            );
+            let detail = format!(
+                "Overlaps range {}-{}.",
+                b.address_number_from, b.address_number_to
+            );
+            // Locate the issue at the first address in the shared numbers.
+            let end = a.address_number_to.min(b.address_number_to);
+            let location = addresses.and_then(|v| {
+                v.iter()
+                    .find(|x| x.number() >= b.address_number_from && x.number() <= end)
+                    .copied()
+            });
+            issues.push(Issue::new(IssueKind::Overlap, &label, &detail).at_some(location));
+        }
+    }
+    issues
+}
+
+/// Checks the member addresses of range `item` for parity, zip code and postal community
+/// conflicts.
+fn membership(
+    street: &str,
+    item: &LexisNexisItem,
+    addresses: Option<&Vec<&SpatialAddress>>,
+) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let members = match addresses {
+        Some(values) => values
+            .iter()
+            .filter(|v| contains(item, v))
+            .copied()
+            .collect::<Vec<&SpatialAddress>>(),
+        None => return issues,
+    };
+    let label = format!(
+        "{}-{} {}",
+        item.address_number_from, item.address_number_to, street
+    );
+
+    let odd = members.iter().find(|v| v.number() % 2 != 0);
+    let even = members.iter().find(|v| v.number() % 2 == 0);
+    let same_parity = item.address_number_from % 2 == item.address_number_to % 2;
+    if same_parity {
+        if let (Some(_), Some(_)) = (odd, even) {
+            // Report the first member with parity opposite the range endpoints.
+            let location = if item.address_number_from % 2 == 0 {
+                odd
+            } else {
+                even
+            };
+            issues.push(
+                Issue::new(
+                    IssueKind::Parity,
+                    &label,
+                    "Range endpoints share a parity, but members are odd and even.",
+                )
+                .at_some(location.copied()),
+            );
+        }
+    }
+
+    let zips = members
+        .iter()
+        .filter(|v| v.zip() != item.zip_code)
+        .collect::<Vec<&&SpatialAddress>>();
+    if let Some(first) = zips.first() {
+        let detail = format!(
+            "{} members differ from range zip {}, such as {}.",
+            zips.len(),
+            item.zip_code,
+            first.zip()
+        );
+        issues.push(Issue::new(IssueKind::Zip, &label, &detail).at(first));
+    }
+
+    let community = item.postal_community.to_uppercase();
+    let communities = members
+        .iter()
+        .filter(|v| v.postal_community.to_uppercase() != community)
+        .collect::<Vec<&&SpatialAddress>>();
+    if let Some(first) = communities.first() {
+        let detail = format!(
+            "{} members differ from range community {}, such as {}.",
+            communities.len(),
+            item.postal_community,
+            first.postal_community
+        );
+        issues.push(Issue::new(IssueKind::Community, &label, &detail).at(first));
+    }
+    issues
+}
+
+/// Returns true if `address` is on the same street as range `item`, and within its numbers.
+fn contains(item: &LexisNexisItem, address: &SpatialAddress) -> bool {
+    address.number() >= item.address_number_from
+        && address.number() <= item.address_number_to
+        && address_street(address) == item_street(item)
+}
+
+/// Groups addresses by the normalized street name.
+fn group_addresses(records: &SpatialAddresses) -> BTreeMap<String, Vec<&SpatialAddress>> {
+    let mut streets = BTreeMap::new();
+    for address in records.iter() {
+        streets
+            .entry(address_street(address))
+            .or_insert(Vec::new())
+            .push(address);
+    }
+    streets
+}
+
+/// Normalized street name of a range, including the prefix directional and street type.
+pub fn item_street(item: &LexisNexisItem) -> String {
+    let directional = item
+        .street_name_pre_directional
+        .as_ref()
+        .map(|v| abbreviate_directional(v));
+    street_key(directional, &item.street_name, &item.street_name_post_type)
+}
+
+/// Normalized street name of an address, including the prefix directional and street type.
+pub fn address_street(address: &SpatialAddress) -> String {
+    let directional = address
+        .directional()
+        .map(|v| abbreviate_directional(&v.to_string()));
+    let street_type = match address.street_type() {
+        Some(value) => format!("{}", value.abbreviate()),
+        None => "".to_string(),
+    };
+    street_key(
+        directional,
+        &format!("{}", address.street_name()),
+        &street_type,
+    )
+}
+
+fn street_key(directional: Option<String>, name: &str, street_type: &str) -> String {
+    let mut street = String::new();
+    if let Some(value) = directional {
+        street.push_str(&value);
+        street.push(' ');
+    }
+    street.push_str(&name.to_uppercase());
+    street.push(' ');
+    street.push_str(&street_type.to_uppercase());
+    street.trim().to_string()
+}
+
+/// Reduces a directional to its postal abbreviation, so "Northeast" and "NE" compare equal.
+pub fn abbreviate_directional(value: &str) -> String {
+    let value = value.to_uppercase();
+    let abbr = match value.as_str() {
+        "NORTH" => "N",
+        "SOUTH" => "S",
+        "EAST" => "E",
+        "WEST" => "W",
+        "NORTHEAST" => "NE",
+        "NORTHWEST" => "NW",
+        "SOUTHEAST" => "SE",
+        "SOUTHWEST" => "SW",
+        other => other,
+    };
+    abbr.to_string()
+}
+
+/// The `IssuePoint` struct is a map feature marking the location of an [`Issue`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct IssuePoint {
+    issue: Issue,
+    geo_point: GeoPoint2d,
+}
+
+impl IssuePoint {
+    /// Creates an `IssuePoint` from `issue` if the issue has a location.
+    pub fn new(issue: &Issue) -> Option<Self> {
+        if let (Some(lat), Some(lon)) = (issue.latitude, issue.longitude) {
+            let geo_point = GeoPoint2d::latlon(lat, lon);
+            Some(Self {
+                issue: issue.clone(),
+                geo_point,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl GeoPoint for IssuePoint {
+    type Num = f64;
+
+    fn lat(&self) -> Self::Num {
+        self.geo_point.lat()
+    }
+
+    fn lon(&self) -> Self::Num {
+        self.geo_point.lon()
+    }
+}
+
+impl GeometryType for IssuePoint {
+    type Type = PointGeometryType;
+    type Space = GeoSpace2d;
+}
+
+impl Feature for IssuePoint {
+    type Geom = GeoPoint2d;
+
+    fn geometry(&self) -> &Self::Geom {
+        &self.geo_point
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct IssuePoints {
+    pub records: Vec<IssuePoint>,
+}
+
+impl From<&Issues> for IssuePoints {
+    fn from(issues: &Issues) -> Self {
+        let records = issues
+            .iter()
+            .filter_map(IssuePoint::new)
+            .collect::<Vec<IssuePoint>>();
+        Self { records }
+    }
+}
+
+pub struct IssueSymbol {}
+
+impl Symbol<IssuePoint> for IssueSymbol {
+    fn render<'a, N, P>(
+        &self,
+        feature: &IssuePoint,
+        geometry: &'a Geom<P>,
+        _min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N> + Clone,
+    {
+        let size = 11.0 as f32;
+        let mut primitives = Vec::new();
+        let Geom::Point(point) = geometry else {
+            return primitives;
+        };
+        let color = match &feature.issue.kind {
+            IssueKind::Overlap => Color::from_hex("#db00d4"),
+            IssueKind::Exclusion => Color::from_hex("#ad0000"),
+            IssueKind::Parity => Color::from_hex("#db6e00"),
+            IssueKind::Zip => Color::from_hex("#dbc200"),
+            IssueKind::Community => Color::from_hex("#00a6db"),
+        };
+        primitives.push(RenderPrimitive::new_point_ref(
+            point,
+            PointPaint::circle(color, size),
+        ));
+        primitives
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::AddressPoint;
+
+    fn range(from: i64, to: i64, zip: i64) -> LexisNexisItem {
+        LexisNexisItem {
+            address_number_from: from,
+            address_number_to: to,
+            street_name_pre_directional: None,
+            street_name: "MAIN".to_string(),
+            street_name_post_type: "".to_string(),
+            postal_community: "".to_string(),
+            zip_code: zip,
+        }
+    }
+
+    fn address(number: i64, zip: i64) -> SpatialAddress {
+        AddressPoint::sample(number, "MAIN", zip, 42.44, -123.33)
+    }
+
+    #[test]
+    fn overlaps_catch_nested_ranges() {
+        let long = range(100, 900, 97526);
+        let short = range(200, 300, 97526);
+        let later = range(400, 500, 97526);
+        let items = vec![&long, &short, &later];
+        let issues = overlaps("MAIN", &items, None);
+        // Both shorter ranges fall inside the long one, though they do not overlap each other.
+        assert_eq!(issues.len(), 2);
+        assert!(issues.iter().all(|v| v.label == "100-900 MAIN"));
+
+        let low = range(100, 198, 97526);
+        let high = range(200, 298, 97526);
+        assert!(overlaps("MAIN", &[&low, &high], None).is_empty());
+    }
+
+    #[test]
+    fn overlaps_locate_the_shared_numbers() {
+        let low = range(100, 300, 97526);
+        let high = range(200, 400, 97526);
+        let records = [address(150, 97526), address(250, 97526)];
+        let members = records.iter().collect::<Vec<&SpatialAddress>>();
+        let issues = overlaps("MAIN", &[&low, &high], Some(&members));
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].latitude, Some(records[1].latitude));
+    }
+
+    #[test]
+    fn excluded_addresses_in_a_range() {
+        let mut ranges = LexisNexis::default();
+        ranges.push(range(100, 198, 97526));
+        let records = SpatialAddresses::from(&[address(100, 97526)][..]);
+        let other = SpatialAddresses::from(&[address(150, 97526), address(250, 97526)][..]);
+        let issues = Issues::from_ranges(&ranges, &records, &other).kind(&IssueKind::Exclusion);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].label, other[0].label());
+    }
+
+    #[test]
+    fn membership_flags_mixed_parity() {
+        let item = range(100, 198, 97526);
+        let records = [address(102, 97526), address(105, 97526)];
+        let members = records.iter().collect::<Vec<&SpatialAddress>>();
+        let issues = membership("MAIN", &item, Some(&members));
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, IssueKind::Parity);
+
+        // Endpoints of opposite parity cover both sides of the street.
+        let item = range(100, 199, 97526);
+        assert!(membership("MAIN", &item, Some(&members)).is_empty());
+    }
+
+    #[test]
+    fn membership_flags_zip_and_community() {
+        let item = range(100, 198, 97526);
+        let mut town = address(104, 97526);
+        town.postal_community = "MERLIN".to_string();
+        let records = [address(102, 97527), town, address(106, 97526)];
+        let members = records.iter().collect::<Vec<&SpatialAddress>>();
+        let issues = membership("MAIN", &item, Some(&members));
+        let kinds = issues.iter().map(|v| v.kind.clone()).collect::<Vec<_>>();
+        assert_eq!(kinds, vec![IssueKind::Zip, IssueKind::Community]);
+        assert!(issues[0].detail.starts_with("1 members"));
+        assert_eq!(issues[1].detail.matches("MERLIN").count(), 1);
+    }
+}
@@ -0,0 +1,496 @@
+//! The `query` module holds the query language of the table search box.  A query is a list of
+//! terms, combined with `AND`, `OR`, `NOT` and parentheses, where adjacent terms must both match:
+//!
+//! `zip:97526 status:pending street:~"^NE" number:100..500`
+//!
+//! A term is either a bare word, matching any column, or a column filter of the form `column:value`
+//! (substring), `column:~pattern` (regular expression) or `column:low..high` (numeric range, with
+//! either end optional).  Values containing spaces or parentheses are quoted.  Column names match
+//! the table headers ignoring case, spaces and underscores, and may be shortened to a word of the
+//! header, so `street` finds "Street Name".
+use crate::table::Cell;
+use aid::prelude::{Bandage, Clean};
+use nom::branch::alt;
+use nom::bytes::complete::{escaped_transform, is_not, tag, tag_no_case, take_while1};
+use nom::character::complete::{char, digit1, multispace0, multispace1};
+use nom::combinator::{all_consuming, map, not, opt, recognize, value, verify};
+use nom::multi::many0;
+use nom::sequence::{delimited, pair, preceded, separated_pair, terminated, tuple};
+use nom::IResult;
+use regex::{Regex, RegexBuilder};
+
+/// The `Test` enum is the comparison a [`Term`] makes against a cell value.
+#[derive(Debug, Clone)]
+pub enum Test {
+    /// The value contains the string.
+    Contains(String),
+    /// The value matches the regular expression.
+    Pattern(Regex),
+    /// The value is a number between the bounds, inclusive.  Text cells match if they parse as
+    /// a number.
+    Range(Option<f64>, Option<f64>),
+}
+
+impl Test {
+    fn matches(&self, cell: &Cell, case_sensitive: bool) -> bool {
+        match self {
+            Self::Contains(text) => {
+                let value = cell.to_string();
+                if case_sensitive {
+                    value.contains(text)
+                } else {
+                    value.to_lowercase().contains(&text.to_lowercase())
+                }
+            }
+            Self::Pattern(pattern) => pattern.is_match(&cell.to_string()),
+            Self::Range(low, high) => {
+                let number = match cell {
+                    Cell::Text(value) => value.trim().replace(',', "").parse::<f64>().ok(),
+                    _ => cell.as_f64(),
+                };
+                match number {
+                    Some(number) => {
+                        low.iter().all(|v| number >= *v) && high.iter().all(|v| number <= *v)
+                    }
+                    None => false,
+                }
+            }
+        }
+    }
+}
+
+impl PartialEq for Test {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Contains(a), Self::Contains(b)) => a == b,
+            (Self::Pattern(a), Self::Pattern(b)) => a.as_str() == b.as_str(),
+            (Self::Range(a, b), Self::Range(c, d)) => a == c && b == d,
+            _ => false,
+        }
+    }
+}
+
+/// The `Term` struct tests the column at index `column`, or every column if `None`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Term {
+    pub column: Option<usize>,
+    pub test: Test,
+}
+
+impl Term {
+    fn matches(&self, cells: &[Cell], case_sensitive: bool) -> bool {
+        match self.column {
+            Some(column) => cells
+                .get(column)
+                .is_some_and(|v| self.test.matches(v, case_sensitive)),
+            None => cells.iter().any(|v| self.test.matches(v, case_sensitive)),
+        }
+    }
+}
+
+/// The `Expr` enum is a parsed query, with column names resolved to indices.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Term(Term),
+    Not(Box<Expr>),
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+}
+
+impl Expr {
+    /// Returns true if the `cells` of a row satisfy the expression.
+    pub fn matches(&self, cells: &[Cell], case_sensitive: bool) -> bool {
+        match self {
+            Self::Term(term) => term.matches(cells, case_sensitive),
+            Self::Not(expr) => !expr.matches(cells, case_sensitive),
+            Self::And(exprs) => exprs.iter().all(|v| v.matches(cells, case_sensitive)),
+            Self::Or(exprs) => exprs.iter().any(|v| v.matches(cells, case_sensitive)),
+        }
+    }
+}
+
+/// The `Operator` enum is the comparison written after a column name, before it is compiled
+/// into a [`Test`].
+#[derive(Debug, Clone, PartialEq)]
+enum Operator {
+    Contains(String),
+    Pattern(String),
+    Range(Option<String>, Option<String>),
+}
+
+/// The `Syntax` enum is the parse tree of a query, with column names as written.
+#[derive(Debug, Clone, PartialEq)]
+enum Syntax {
+    Word(String),
+    Filter(String, Operator),
+    Not(Box<Syntax>),
+    And(Vec<Syntax>),
+    Or(Vec<Syntax>),
+}
+
+/// The `TableQuery` struct holds a query typed into the search box of a
+/// [`crate::table::TableView`], compiled against the table headers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableQuery {
+    /// The `text` field holds the query as typed.
+    pub text: String,
+    pub expr: Expr,
+}
+
+impl TableQuery {
+    /// Returns true if `text` uses the query syntax: a column filter, quotes, parentheses, one
+    /// of the keywords `AND`, `OR` or `NOT` as a whole word in upper case, or a word negated with
+    /// a leading `-`.  Other searches keep the plain substring match, so a search for "ORANGE" or
+    /// "not" is not read as a query.
+    pub fn is_query(text: &str) -> bool {
+        text.contains([':', '"', '('])
+            || text
+                .split_whitespace()
+                .any(|v| matches!(v, "AND" | "OR" | "NOT") || (v.len() > 1 && v.starts_with('-')))
+    }
+
+    /// Parses `text`, resolving column names against `headers`.  Regular expressions ignore case
+    /// unless `case_sensitive` is true.
+    pub fn parse(text: &str, headers: &[String], case_sensitive: bool) -> Clean<Self> {
+        let (_, syntax) = all_consuming(delimited(multispace0, or_expr, multispace0))(text)
+            .map_err(|e| Bandage::Hint(format!("Could not parse query: {e}")))?;
+        let expr = compile(&syntax, headers, case_sensitive)?;
+        Ok(Self {
+            text: text.to_string(),
+            expr,
+        })
+    }
+
+    pub fn matches(&self, cells: &[Cell], case_sensitive: bool) -> bool {
+        self.expr.matches(cells, case_sensitive)
+    }
+}
+
+/// Reduces a column name or header to lower case letters and digits.
+fn column_key(name: &str) -> String {
+    name.chars()
+        .filter(|v| v.is_alphanumeric())
+        .flat_map(|v| v.to_lowercase())
+        .collect()
+}
+
+/// Finds the header named by `name`, preferring an exact match to a match on a word of the header.
+fn resolve(name: &str, headers: &[String]) -> Clean<usize> {
+    let key = column_key(name);
+    if let Some(index) = headers.iter().position(|v| column_key(v) == key) {
+        return Ok(index);
+    }
+    headers
+        .iter()
+        .position(|v| v.split_whitespace().any(|word| column_key(word) == key))
+        .ok_or_else(|| {
+            Bandage::Hint(format!(
+                "Unknown column {name}.  Columns are {}.",
+                headers.join(", ")
+            ))
+        })
+}
+
+fn bound(value: &Option<String>) -> Clean<Option<f64>> {
+    match value {
+        Some(text) => text
+            .replace(',', "")
+            .parse::<f64>()
+            .map(Some)
+            .map_err(|_| Bandage::Hint(format!("{text} is not a number."))),
+        None => Ok(None),
+    }
+}
+
+fn compile(syntax: &Syntax, headers: &[String], case_sensitive: bool) -> Clean<Expr> {
+    let expr = match syntax {
+        Syntax::Word(text) => Expr::Term(Term {
+            column: None,
+            test: Test::Contains(text.clone()),
+        }),
+        Syntax::Filter(name, operator) => {
+            let column = Some(resolve(name, headers)?);
+            let test = match operator {
+                Operator::Contains(text) => Test::Contains(text.clone()),
+                Operator::Pattern(text) => {
+                    let pattern = RegexBuilder::new(text)
+                        .case_insensitive(!case_sensitive)
+                        .build()
+                        .map_err(|e| Bandage::Hint(e.to_string()))?;
+                    Test::Pattern(pattern)
+                }
+                Operator::Range(low, high) => Test::Range(bound(low)?, bound(high)?),
+            };
+            Expr::Term(Term { column, test })
+        }
+        Syntax::Not(inner) => Expr::Not(Box::new(compile(inner, headers, case_sensitive)?)),
+        Syntax::And(items) => Expr::And(
+            items
+                .iter()
+                .map(|v| compile(v, headers, case_sensitive))
+                .collect::<Clean<Vec<Expr>>>()?,
+        ),
+        Syntax::Or(items) => Expr::Or(
+            items
+                .iter()
+                .map(|v| compile(v, headers, case_sensitive))
+                .collect::<Clean<Vec<Expr>>>()?,
+        ),
+    };
+    Ok(expr)
+}
+
+/// Matches one of the keywords `AND`, `OR` or `NOT` as a whole word.
+fn keyword<'a>(word: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+    terminated(
+        tag_no_case(word),
+        not(take_while1(|c: char| c.is_alphanumeric())),
+    )
+}
+
+fn is_bare(c: char) -> bool {
+    !c.is_whitespace() && !matches!(c, '(' | ')' | '"' | ':')
+}
+
+/// A run of characters other than whitespace, parentheses, quotes and colons.
+fn bare(input: &str) -> IResult<&str, String> {
+    map(take_while1(is_bare), String::from)(input)
+}
+
+/// A string in double quotes, with `\"` and `\\` escapes.
+fn quoted(input: &str) -> IResult<&str, String> {
+    delimited(
+        char('"'),
+        map(
+            opt(escaped_transform(
+                is_not("\\\""),
+                '\\',
+                alt((value("\\", tag("\\")), value("\"", tag("\"")))),
+            )),
+            Option::unwrap_or_default,
+        ),
+        char('"'),
+    )(input)
+}
+
+fn text(input: &str) -> IResult<&str, String> {
+    alt((quoted, bare))(input)
+}
+
+/// A number as written in a range, e.g. `100`, `1,500` or `-2.5`.
+fn number(input: &str) -> IResult<&str, String> {
+    map(
+        recognize(tuple((
+            opt(char('-')),
+            take_while1(|c: char| c.is_ascii_digit() || c == ','),
+            opt(pair(char('.'), digit1)),
+        ))),
+        String::from,
+    )(input)
+}
+
+fn range(input: &str) -> IResult<&str, Operator> {
+    map(
+        verify(
+            separated_pair(opt(number), tag(".."), opt(number)),
+            |(low, high): &(Option<String>, Option<String>)| low.is_some() || high.is_some(),
+        ),
+        |(low, high)| Operator::Range(low, high),
+    )(input)
+}
+
+fn operator(input: &str) -> IResult<&str, Operator> {
+    alt((
+        map(preceded(char('~'), text), Operator::Pattern),
+        // A range must consume the whole value, so `100..500a` is a substring instead.
+        terminated(range, not(take_while1(is_bare))),
+        map(text, Operator::Contains),
+    ))(input)
+}
+
+fn filter(input: &str) -> IResult<&str, Syntax> {
+    map(
+        separated_pair(take_while1(is_bare), char(':'), operator),
+        |(name, operator)| Syntax::Filter(name.to_string(), operator),
+    )(input)
+}
+
+fn word(input: &str) -> IResult<&str, Syntax> {
+    preceded(
+        not(alt((keyword("AND"), keyword("OR"), keyword("NOT")))),
+        map(text, Syntax::Word),
+    )(input)
+}
+
+fn atom(input: &str) -> IResult<&str, Syntax> {
+    alt((
+        delimited(
+            pair(char('('), multispace0),
+            or_expr,
+            pair(multispace0, char(')')),
+        ),
+        filter,
+        word,
+    ))(input)
+}
+
+fn unary(input: &str) -> IResult<&str, Syntax> {
+    alt((
+        map(
+            preceded(
+                alt((terminated(keyword("NOT"), multispace1), tag("-"))),
+                unary,
+            ),
+            |v| Syntax::Not(Box::new(v)),
+        ),
+        atom,
+    ))(input)
+}
+
+/// Terms joined by `AND`, or by whitespace alone.
+fn and_expr(input: &str) -> IResult<&str, Syntax> {
+    let separator = alt((
+        recognize(tuple((multispace1, keyword("AND"), multispace1))),
+        multispace1,
+    ));
+    map(
+        pair(unary, many0(preceded(separator, unary))),
+        |(first, rest)| join(first, rest, Syntax::And),
+    )(input)
+}
+
+fn or_expr(input: &str) -> IResult<&str, Syntax> {
+    let separator = tuple((multispace1, keyword("OR"), multispace1));
+    map(
+        pair(and_expr, many0(preceded(separator, and_expr))),
+        |(first, rest)| join(first, rest, Syntax::Or),
+    )(input)
+}
+
+fn join(first: Syntax, rest: Vec<Syntax>, group: fn(Vec<Syntax>) -> Syntax) -> Syntax {
+    if rest.is_empty() {
+        first
+    } else {
+        let mut items = vec![first];
+        items.extend(rest);
+        group(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers() -> Vec<String> {
+        ["Label", "Number", "Street Name", "Zip", "Status"]
+            .iter()
+            .map(|v| v.to_string())
+            .collect()
+    }
+
+    fn parse(text: &str) -> Clean<Expr> {
+        TableQuery::parse(text, &headers(), false).map(|v| v.expr)
+    }
+
+    fn term(column: Option<usize>, test: Test) -> Expr {
+        Expr::Term(Term { column, test })
+    }
+
+    fn word(text: &str) -> Expr {
+        term(None, Test::Contains(text.to_string()))
+    }
+
+    #[test]
+    fn detects_query_syntax() {
+        for text in [
+            "zip:97526",
+            "\"NE 5TH\"",
+            "(A)",
+            "A OR B",
+            "NOT A",
+            "A AND B",
+            "-OAK",
+        ] {
+            assert!(TableQuery::is_query(text), "{text}");
+        }
+        for text in [
+            "ORANGE",
+            "1200 NOTTINGHAM",
+            "ANDERSON or",
+            "not",
+            "-",
+            "A-B",
+        ] {
+            assert!(!TableQuery::is_query(text), "{text}");
+        }
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(
+            parse("a OR b c").unwrap(),
+            Expr::Or(vec![word("a"), Expr::And(vec![word("b"), word("c")])])
+        );
+        assert_eq!(
+            parse("(a OR b) AND c").unwrap(),
+            Expr::And(vec![Expr::Or(vec![word("a"), word("b")]), word("c")])
+        );
+        assert_eq!(
+            parse("NOT a -b").unwrap(),
+            Expr::And(vec![
+                Expr::Not(Box::new(word("a"))),
+                Expr::Not(Box::new(word("b")))
+            ])
+        );
+    }
+
+    #[test]
+    fn keywords_are_whole_words() {
+        assert_eq!(parse("ORANGE").unwrap(), word("ORANGE"));
+        assert_eq!(
+            parse("ORANGE OR NOTE").unwrap(),
+            Expr::Or(vec![word("ORANGE"), word("NOTE")])
+        );
+        assert_eq!(
+            parse("ANDERSON NOTTINGHAM").unwrap(),
+            Expr::And(vec![word("ANDERSON"), word("NOTTINGHAM")])
+        );
+    }
+
+    #[test]
+    fn quoted_values_keep_spaces_and_escapes() {
+        assert_eq!(
+            parse("street:\"NE 5TH\"").unwrap(),
+            term(Some(2), Test::Contains("NE 5TH".to_string()))
+        );
+        assert_eq!(parse("\"say \\\"OR\\\"\"").unwrap(), word("say \"OR\""));
+        assert_eq!(parse("\"\"").unwrap(), word(""));
+    }
+
+    #[test]
+    fn ranges_may_be_open() {
+        let range = |low, high| term(Some(1), Test::Range(low, high));
+        assert_eq!(parse("number:100..").unwrap(), range(Some(100.0), None));
+        assert_eq!(parse("number:..500").unwrap(), range(None, Some(500.0)));
+        assert_eq!(
+            parse("number:1,500..2000").unwrap(),
+            range(Some(1500.0), Some(2000.0))
+        );
+        // A range must be the whole value.
+        assert_eq!(
+            parse("number:100..500a").unwrap(),
+            term(Some(1), Test::Contains("100..500a".to_string()))
+        );
+        let query = TableQuery::parse("number:..1000", &headers(), false).unwrap();
+        assert!(query.matches(&[Cell::Empty, Cell::Text("1,000".to_string())], false));
+        assert!(!query.matches(&[Cell::Empty, Cell::Integer(1001)], false));
+    }
+
+    #[test]
+    fn reports_bad_input() {
+        assert!(parse("street:~\"(\"").is_err());
+        assert!(parse("unit:5").is_err());
+        assert!(parse("(a OR b").is_err());
+        assert!(parse("a OR").is_err());
+    }
+}
@@ -0,0 +1,690 @@
+//! The `rules` module validates address attributes against rules defined in the config file.
+//! Each [`Rule`] names a [`Check`] and a [`Severity`], and running the [`Rules`] over a set of
+//! addresses produces a table of [`RuleIssues`] that can be displayed on the map.
+use crate::catalog::Dataset;
+use crate::config;
+use crate::lifecycle::Lifecycle;
+use crate::master::StreetLookup;
+use crate::qa::abbreviate_directional;
+use crate::table::{Cell, Columnar, Filtration, Tabular};
+use address::prelude::{Address, AddressStatus, SpatialAddress};
+use aid::prelude::{Bandage, Clean};
+use chrono::NaiveDate;
+use derive_more::{Deref, DerefMut};
+use galileo::galileo_types::cartesian::CartesianPoint3d;
+use galileo::galileo_types::geo::impls::GeoPoint2d;
+use galileo::galileo_types::geo::{GeoPoint, NewGeoPoint};
+use galileo::galileo_types::geometry::Geom;
+use galileo::galileo_types::geometry_type::{GeoSpace2d, GeometryType, PointGeometryType};
+use galileo::galileo_types::impls::{Contour, Polygon};
+use galileo::layer::feature_layer::symbol::Symbol;
+use galileo::layer::feature_layer::Feature;
+use galileo::render::point_paint::PointPaint;
+use galileo::render::render_bundle::RenderPrimitive;
+use galileo::Color;
+use num_traits::AsPrimitive;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+use tracing::trace;
+
+/// The `Severity` enum ranks the importance of a rule violation.
+#[derive(
+    Debug,
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    EnumIter,
+    Deserialize,
+    Serialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    #[default]
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match self {
+            Self::Info => "Info",
+            Self::Warning => "Warning",
+            Self::Error => "Error",
+        };
+        write!(f, "{}", msg)
+    }
+}
+
+/// The `Check` enum lists the tests a [`Rule`] can apply to an address.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Check {
+    /// The street type is present and, if the rule lists `values`, is one of them.  Street names
+    /// listed in `exceptions` may omit the street type.
+    #[default]
+    StreetType,
+    /// The zip code is one of the `zips` listed in the rule.
+    ServiceZip,
+    /// The address has not been Pending for more than `days` days.  The address data carries no
+    /// status date, so the age counts from the status change or creation recorded in the editor,
+    /// and addresses never changed in the application are not checked.
+    PendingAge,
+    /// A subaddress id is accompanied by a subaddress type.
+    SubaddressType,
+    /// The label components do not repeat one another, as when the street name ends with the
+    /// street type.
+    Label,
+    /// The street appears in the street master list, as a canonical name or alias.
+    StreetMaster,
+}
+
+/// The `Rule` struct is a single validation rule, read from the `rules` table of the config file.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Rule {
+    /// The `name` field is the display name of the rule.
+    pub name: String,
+    pub check: Check,
+    #[serde(default)]
+    pub severity: Severity,
+    /// The `values` field lists the accepted street types, as postal abbreviations.
+    #[serde(default)]
+    pub values: Vec<String>,
+    /// The `exceptions` field lists street names that do not take a street type.
+    #[serde(default)]
+    pub exceptions: Vec<String>,
+    /// The `zips` field lists the zip codes in the service area.
+    #[serde(default)]
+    pub zips: Vec<i64>,
+    /// The `days` field sets the number of days an address may remain Pending.
+    #[serde(default)]
+    pub days: i64,
+    /// The `enabled` field indicates the rule runs during validation.
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl Rule {
+    /// Applies the rule to `address`, returning a description of the problem if the address
+    /// fails.  The `lifecycle` supplies the date the address with id `record` entered its current
+    /// status, and `streets` the names in the street master list.
+    pub fn check(
+        &self,
+        address: &SpatialAddress,
+        record: &uuid::Uuid,
+        lifecycle: &Lifecycle,
+        streets: &StreetLookup,
+        today: NaiveDate,
+    ) -> Option<String> {
+        match self.check {
+            Check::StreetType => match address.street_type() {
+                Some(value) => {
+                    let abbr = value.abbreviate().to_string().to_uppercase();
+                    if self.values.is_empty()
+                        || self.values.iter().any(|v| v.to_uppercase() == abbr)
+                    {
+                        None
+                    } else {
+                        Some(format!("Street type {abbr} is not an accepted suffix."))
+                    }
+                }
+                None => {
+                    let name = address.street_name().to_uppercase();
+                    if self.exceptions.iter().any(|v| v.to_uppercase() == name) {
+                        None
+                    } else {
+                        Some("Street type is missing.".to_string())
+                    }
+                }
+            },
+            Check::ServiceZip => {
+                if self.zips.contains(&address.zip()) {
+                    None
+                } else {
+                    Some(format!(
+                        "Zip {} is outside the service area.",
+                        address.zip()
+                    ))
+                }
+            }
+            Check::PendingAge => {
+                if address.status() != &AddressStatus::Pending {
+                    return None;
+                }
+                // Addresses without a recorded date are not flagged, since their age is unknown.
+                let since = lifecycle.dates(record).since?;
+                let days = (today - since).num_days();
+                if days > self.days {
+                    Some(format!("Pending for {days} days, since {since}."))
+                } else {
+                    None
+                }
+            }
+            Check::SubaddressType => match (address.subaddress_id(), address.subaddress_type()) {
+                (Some(id), None) => Some(format!("Subaddress id {id} has no subaddress type.")),
+                _ => None,
+            },
+            Check::Label => label_issue(address),
+            Check::StreetMaster => streets.unlisted(address),
+        }
+    }
+}
+
+/// Checks the label components of `address` for repeated parts.
+fn label_issue(address: &SpatialAddress) -> Option<String> {
+    let name = address.street_name().to_uppercase();
+    let words = name.split_whitespace().collect::<Vec<&str>>();
+    if let (Some(value), Some(last)) = (address.street_type(), words.last()) {
+        let full = value.to_string().to_uppercase();
+        let abbr = value.abbreviate().to_string().to_uppercase();
+        if words.len() > 1 && (*last == full || *last == abbr) {
+            return Some(format!(
+                "Street name {name} repeats the street type {abbr}."
+            ));
+        }
+    }
+    if let (Some(value), Some(first)) = (address.directional(), words.first()) {
+        let directional = abbreviate_directional(&value.to_string());
+        if words.len() > 1 && abbreviate_directional(first) == directional {
+            return Some(format!(
+                "Street name {name} repeats the directional {directional}."
+            ));
+        }
+    }
+    if let (Some(id), Some(subtype)) = (address.subaddress_id(), address.subaddress_type()) {
+        let id = id.to_uppercase();
+        let subtype = subtype.to_string().to_uppercase();
+        if id.starts_with(&subtype) {
+            return Some(format!(
+                "Subaddress id {id} repeats the subaddress type {subtype}."
+            ));
+        }
+    }
+    None
+}
+
+/// The `RuleConfig` struct reads the `rules` table from the config toml.  Other tables in the
+/// config file are ignored.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RuleConfig {
+    #[serde(default)]
+    pub rules: BTreeMap<String, Rule>,
+}
+
+/// The `Rules` struct holds the validation rules, sorted by their keys in the config file.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize, Deref, DerefMut)]
+pub struct Rules(Vec<Rule>);
+
+impl Rules {
+    /// Reads rules from `config.toml` in the working directory if present, falling back to the
+    /// config bundled with the application.
+    pub fn with_config() -> Clean<Self> {
+        let config: RuleConfig = config::read()?;
+        trace!("Rules read: {}", config.rules.len());
+        Self::from_config(config)
+    }
+
+    /// Collects the rules in `config`, refusing rules that cannot pass, such as a service zip
+    /// check without zips.
+    pub fn from_config(config: RuleConfig) -> Clean<Self> {
+        for (key, rule) in config.rules.iter() {
+            if rule.check == Check::ServiceZip && rule.zips.is_empty() {
+                return Err(Bandage::Hint(format!(
+                    "Rule {key} checks the service area but lists no zips."
+                )));
+            }
+        }
+        Ok(Self(config.rules.into_values().collect::<Vec<Rule>>()))
+    }
+
+    /// Switches rules on or off by name, as set by the user in `enabled`.  Rules not listed keep
+    /// the setting from the config file.
+    pub fn set_enabled(&mut self, enabled: &BTreeMap<String, bool>) {
+        for rule in self.iter_mut() {
+            if let Some(value) = enabled.get(&rule.name) {
+                rule.enabled = *value;
+            }
+        }
+    }
+
+    /// Applies the enabled rules to each address in `dataset`.
+    pub fn check(
+        &self,
+        dataset: &Dataset,
+        lifecycle: &Lifecycle,
+        streets: &StreetLookup,
+        today: NaiveDate,
+    ) -> RuleIssues {
+        let mut issues = Vec::new();
+        for (i, address) in dataset.addresses.iter().enumerate() {
+            let record = dataset.record_id(i);
+            for rule in self.iter().filter(|v| v.enabled) {
+                if let Some(detail) = rule.check(address, &record, lifecycle, streets, today) {
+                    issues.push(RuleIssue::new(rule, address, &detail));
+                }
+            }
+        }
+        tracing::info!("Rule issues found: {}", issues.len());
+        RuleIssues(issues)
+    }
+}
+
+/// The `RuleIssue` struct records an address that fails a [`Rule`].
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RuleIssue {
+    /// The `id` field holds a [`uuid::Uuid`] for use by the [`crate::table::TableView`].
+    pub id: uuid::Uuid,
+    pub severity: Severity,
+    /// The `rule` field holds the name of the failed rule.
+    pub rule: String,
+    /// The `address` field holds the label of the address, which links the issue to the record.
+    pub address: String,
+    pub detail: String,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl RuleIssue {
+    pub fn new(rule: &Rule, address: &SpatialAddress, detail: &str) -> Self {
+        Self::with_name(&rule.name, rule.severity, address, detail)
+    }
+
+    /// Creates an issue for a check named `rule` that is not read from the config file, such as
+    /// the spatial checks.
+    pub fn with_name(
+        rule: &str,
+        severity: Severity,
+        address: &SpatialAddress,
+        detail: &str,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4(),
+            severity,
+            rule: rule.to_string(),
+            address: address.label(),
+            detail: detail.to_string(),
+            latitude: address.latitude,
+            longitude: address.longitude,
+        }
+    }
+}
+
+#[derive(EnumIter, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RuleIssueColumns {
+    Severity,
+    Rule,
+    Address,
+    Detail,
+}
+
+impl RuleIssueColumns {
+    pub fn value(&self, issue: &RuleIssue) -> Cell {
+        match self {
+            Self::Severity => Cell::variant(&issue.severity),
+            Self::Rule => Cell::Text(issue.rule.clone()),
+            Self::Address => Cell::Text(issue.address.clone()),
+            Self::Detail => Cell::Text(issue.detail.clone()),
+        }
+    }
+}
+
+impl fmt::Display for RuleIssueColumns {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Severity => write!(f, "Severity"),
+            Self::Rule => write!(f, "Rule"),
+            Self::Address => write!(f, "Address"),
+            Self::Detail => write!(f, "Detail"),
+        }
+    }
+}
+
+impl Columnar for RuleIssue {
+    fn cells(&self) -> Vec<Cell> {
+        RuleIssueColumns::iter()
+            .map(|v| v.value(self))
+            .collect::<Vec<Cell>>()
+    }
+
+    fn id(&self) -> uuid::Uuid {
+        self.id
+    }
+}
+
+/// The `RuleIssues` struct is a wrapper around a vector of type [`RuleIssue`], for display in a
+/// [`crate::table::TableView`].
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize, Deref, DerefMut)]
+pub struct RuleIssues(Vec<RuleIssue>);
+
+impl RuleIssues {
+    /// Counts the issues of each severity.
+    pub fn totals(&self) -> BTreeMap<Severity, usize> {
+        let mut totals = BTreeMap::new();
+        for issue in self.iter() {
+            *totals.entry(issue.severity).or_insert(0) += 1;
+        }
+        totals
+    }
+
+    pub fn to_csv<P: AsRef<Path>>(&self, path: P) -> Clean<()> {
+        let mut wtr = csv::Writer::from_path(path).map_err(|e| Bandage::Hint(e.to_string()))?;
+        wtr.write_record(Self::headers())
+            .map_err(|e| Bandage::Hint(e.to_string()))?;
+        for issue in self.iter() {
+            wtr.write_record(issue.values())
+                .map_err(|e| Bandage::Hint(e.to_string()))?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+impl From<Vec<RuleIssue>> for RuleIssues {
+    fn from(issues: Vec<RuleIssue>) -> Self {
+        Self(issues)
+    }
+}
+
+impl Tabular<RuleIssue> for RuleIssues {
+    fn headers() -> Vec<String> {
+        RuleIssueColumns::iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>()
+    }
+
+    fn rows(&self) -> Vec<RuleIssue> {
+        self.to_vec()
+    }
+
+    fn set_rows(&mut self, rows: Vec<RuleIssue>) {
+        self.0 = rows;
+    }
+}
+
+impl Filtration<RuleIssues, String> for RuleIssues {
+    /// Filters issues by the name of the [`Severity`] or of the rule.
+    fn filter(&mut self, filter: &String) -> Self {
+        let mut issues = self.to_vec();
+        issues.retain(|v| v.severity.to_string() == *filter || v.rule == *filter);
+        Self(issues)
+    }
+}
+
+/// The `RulePoint` struct is a map feature marking the location of a [`RuleIssue`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RulePoint {
+    severity: Severity,
+    geo_point: GeoPoint2d,
+}
+
+impl From<&RuleIssue> for RulePoint {
+    fn from(issue: &RuleIssue) -> Self {
+        Self {
+            severity: issue.severity,
+            geo_point: GeoPoint2d::latlon(issue.latitude, issue.longitude),
+        }
+    }
+}
+
+impl GeoPoint for RulePoint {
+    type Num = f64;
+
+    fn lat(&self) -> Self::Num {
+        self.geo_point.lat()
+    }
+
+    fn lon(&self) -> Self::Num {
+        self.geo_point.lon()
+    }
+}
+
+impl GeometryType for RulePoint {
+    type Type = PointGeometryType;
+    type Space = GeoSpace2d;
+}
+
+impl Feature for RulePoint {
+    type Geom = GeoPoint2d;
+
+    fn geometry(&self) -> &Self::Geom {
+        &self.geo_point
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RulePoints {
+    pub records: Vec<RulePoint>,
+}
+
+impl From<&RuleIssues> for RulePoints {
+    fn from(issues: &RuleIssues) -> Self {
+        let records = issues
+            .iter()
+            .map(RulePoint::from)
+            .collect::<Vec<RulePoint>>();
+        Self { records }
+    }
+}
+
+pub struct RuleSymbol {}
+
+impl Symbol<RulePoint> for RuleSymbol {
+    fn render<'a, N, P>(
+        &self,
+        feature: &RulePoint,
+        geometry: &'a Geom<P>,
+        _min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N> + Clone,
+    {
+        let size = 9.0 as f32;
+        let mut primitives = Vec::new();
+        let Geom::Point(point) = geometry else {
+            return primitives;
+        };
+        let color = match feature.severity {
+            Severity::Info => Color::from_hex("#00a6db"),
+            Severity::Warning => Color::from_hex("#db6e00"),
+            Severity::Error => Color::from_hex("#ad0000"),
+        };
+        primitives.push(RenderPrimitive::new_point_ref(
+            point,
+            PointPaint::circle(color, size),
+        ));
+        primitives
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::{AddressColumns, AddressPoint};
+    use crate::master::{StreetMaster, StreetName};
+
+    /// Builds an address at 100 ELM with `columns` set over the sample values.
+    fn address(columns: &[(AddressColumns, &str)]) -> SpatialAddress {
+        let mut point = AddressPoint {
+            address: AddressPoint::sample(100, "ELM", 97526, 0.0, 0.0),
+            ..Default::default()
+        };
+        for (column, value) in columns {
+            point.set_column(column, value).unwrap();
+        }
+        point.address
+    }
+
+    fn rule(check: Check) -> Rule {
+        Rule {
+            name: format!("{check:?}"),
+            check,
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    fn day(value: &str) -> NaiveDate {
+        value.parse().unwrap()
+    }
+
+    /// Applies `rule` to `address` with no lifecycle dates or street master list.
+    fn check(rule: &Rule, address: &SpatialAddress) -> Option<String> {
+        let lookup = StreetMaster::default().lookup();
+        let record = uuid::Uuid::new_v4();
+        rule.check(
+            address,
+            &record,
+            &Lifecycle::default(),
+            &lookup,
+            day("2024-06-01"),
+        )
+    }
+
+    #[test]
+    fn street_type_accepts_listed_suffixes_and_exceptions() {
+        let mut rule = rule(Check::StreetType);
+        rule.values = vec!["ST".to_string(), "AVE".to_string()];
+        rule.exceptions = vec!["BROADWAY".to_string()];
+        assert!(check(&rule, &address(&[(AddressColumns::StreetType, "Street")])).is_none());
+        assert!(check(&rule, &address(&[(AddressColumns::StreetType, "Way")])).is_some());
+        assert!(check(&rule, &address(&[])).is_some());
+        let broadway = address(&[(AddressColumns::StreetName, "Broadway")]);
+        assert!(check(&rule, &broadway).is_none());
+    }
+
+    #[test]
+    fn service_zip_flags_zips_outside_the_list() {
+        let mut rule = rule(Check::ServiceZip);
+        rule.zips = vec![97526, 97527];
+        assert!(check(&rule, &address(&[])).is_none());
+        assert!(check(&rule, &address(&[(AddressColumns::Zip, "97530")])).is_some());
+    }
+
+    #[test]
+    fn service_zip_without_zips_is_a_config_error() {
+        let mut config = RuleConfig::default();
+        config
+            .rules
+            .insert("zips".to_string(), rule(Check::ServiceZip));
+        assert!(Rules::from_config(config.clone()).is_err());
+        if let Some(rule) = config.rules.get_mut("zips") {
+            rule.zips = vec![97526];
+        }
+        assert_eq!(Rules::from_config(config).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn pending_age_counts_from_the_recorded_date() {
+        let mut rule = rule(Check::PendingAge);
+        rule.days = 30;
+        let pending = address(&[(AddressColumns::Status, "Pending")]);
+        let record = uuid::Uuid::new_v4();
+        let lookup = StreetLookup::default();
+        let mut lifecycle = Lifecycle::default();
+        // Without a recorded date the age is unknown, so the address passes.
+        let today = day("2024-06-01");
+        assert!(rule
+            .check(&pending, &record, &lifecycle, &lookup, today)
+            .is_none());
+        lifecycle.record(
+            record,
+            "100 ELM",
+            AddressStatus::Current,
+            AddressStatus::Pending,
+            day("2024-04-01"),
+            "Test",
+        );
+        assert!(rule
+            .check(&pending, &record, &lifecycle, &lookup, today)
+            .is_some());
+        assert!(rule
+            .check(&pending, &record, &lifecycle, &lookup, day("2024-04-15"))
+            .is_none());
+        // Only Pending addresses are checked.
+        let current = address(&[(AddressColumns::Status, "Current")]);
+        assert!(rule
+            .check(&current, &record, &lifecycle, &lookup, today)
+            .is_none());
+    }
+
+    #[test]
+    fn subaddress_id_needs_a_type() {
+        let rule = rule(Check::SubaddressType);
+        let id = (AddressColumns::SubaddressId, "4");
+        assert!(check(&rule, &address(&[id.clone()])).is_some());
+        let typed = address(&[id, (AddressColumns::SubaddressType, "APT")]);
+        assert!(check(&rule, &typed).is_none());
+    }
+
+    #[test]
+    fn street_master_flags_unlisted_streets() {
+        let rule = rule(Check::StreetMaster);
+        let mut master = StreetMaster::default();
+        master.push(StreetName::new("ELM", &[]));
+        let lookup = master.lookup();
+        let record = uuid::Uuid::new_v4();
+        let lifecycle = Lifecycle::default();
+        let today = day("2024-06-01");
+        let elm = address(&[]);
+        assert!(rule
+            .check(&elm, &record, &lifecycle, &lookup, today)
+            .is_none());
+        let oak = address(&[(AddressColumns::StreetName, "OAK")]);
+        assert!(rule
+            .check(&oak, &record, &lifecycle, &lookup, today)
+            .is_some());
+        // An empty list flags nothing.
+        assert!(check(&rule, &oak).is_none());
+    }
+
+    #[test]
+    fn label_issue_finds_repeated_parts() {
+        let elm = address(&[(AddressColumns::StreetType, "Street")]);
+        assert!(label_issue(&elm).is_none());
+        let abbr = elm.street_type().unwrap().abbreviate().to_string();
+        let street = address(&[
+            (AddressColumns::StreetName, format!("ELM {abbr}").as_str()),
+            (AddressColumns::StreetType, "Street"),
+        ]);
+        assert!(label_issue(&street).unwrap().contains("street type"));
+        let directional = address(&[
+            (AddressColumns::StreetName, "NORTH ELM"),
+            (AddressColumns::Directional, "N"),
+        ]);
+        assert!(label_issue(&directional).unwrap().contains("directional"));
+        let apartment = address(&[(AddressColumns::SubaddressType, "APT")]);
+        let subtype = apartment.subaddress_type().unwrap().to_string();
+        let subaddress = address(&[
+            (AddressColumns::SubaddressType, "APT"),
+            (
+                AddressColumns::SubaddressId,
+                format!("{subtype} 4").as_str(),
+            ),
+        ]);
+        assert!(label_issue(&subaddress)
+            .unwrap()
+            .contains("subaddress type"));
+        // A one-word name that matches the street type is the name itself.
+        let court = address(&[
+            (AddressColumns::StreetName, abbr.as_str()),
+            (AddressColumns::StreetType, "Street"),
+        ]);
+        assert!(label_issue(&court).is_none());
+        let label = rule(Check::Label);
+        assert_eq!(check(&label, &street), label_issue(&street));
+    }
+}
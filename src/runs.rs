@@ -0,0 +1,307 @@
+//! The `runs` module keeps a local history of comparison runs.  Each [`Run`] records the sources,
+//! file names, time and counts by status of a comparison.  The records of the most recent runs are
+//! kept in separate files under [`RUNS_PATH`], so that they can be reloaded into the comparison
+//! table without loading every past run along with the history.
+use crate::matching::{CompareRecords, CompareStatus};
+use crate::table::{Cell, Columnar, Filtration, Tabular};
+use aid::prelude::{Bandage, Clean};
+use chrono::{DateTime, Local, Utc};
+use derive_more::{Deref, DerefMut};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// Directory holding the run history and the records of recent runs.
+pub const RUNS_PATH: &str = "data/runs";
+/// Location of the run history on the local machine.
+pub const HISTORY_PATH: &str = "data/runs/history.data";
+/// Number of runs kept in the history.  The oldest runs are dropped first.
+pub const RUN_LIMIT: usize = 500;
+/// Number of recent runs whose records are kept for reloading.
+pub const RECORDS_LIMIT: usize = 10;
+
+/// The `Run` struct records a single comparison run.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Run {
+    pub id: uuid::Uuid,
+    /// The `time` field records when the comparison was run.
+    pub time: DateTime<Utc>,
+    pub subject: String,
+    pub target: String,
+    /// The `subject_file` field holds the file the subject addresses were read from.
+    pub subject_file: String,
+    /// The `target_file` field holds the file the target addresses were read from.
+    pub target_file: String,
+    /// The `fuzzy` field indicates fuzzy matching was enabled for the run.
+    pub fuzzy: bool,
+    pub matching: usize,
+    pub divergent: usize,
+    pub probable: usize,
+    pub missing: usize,
+}
+
+impl Run {
+    pub fn new(
+        subject: &str,
+        target: &str,
+        subject_file: &str,
+        target_file: &str,
+        fuzzy: bool,
+        records: &CompareRecords,
+    ) -> Self {
+        let count = |status: CompareStatus| records.iter().filter(|v| v.status == status).count();
+        Self {
+            id: uuid::Uuid::new_v4(),
+            time: Utc::now(),
+            subject: subject.to_string(),
+            target: target.to_string(),
+            subject_file: subject_file.to_string(),
+            target_file: target_file.to_string(),
+            fuzzy,
+            matching: count(CompareStatus::Matching),
+            divergent: count(CompareStatus::Divergent),
+            probable: count(CompareStatus::Probable),
+            missing: count(CompareStatus::Missing),
+        }
+    }
+
+    /// The file holding the records of the run.
+    pub fn records_path(&self) -> PathBuf {
+        Path::new(RUNS_PATH).join(format!("{}.data", self.id))
+    }
+
+    /// Loads the records of the run.  Only the most recent runs keep their records.
+    pub fn records(&self) -> Clean<CompareRecords> {
+        let path = self.records_path();
+        if !path.exists() {
+            return Err(Bandage::Hint(
+                "The records of this run are no longer kept.".to_string(),
+            ));
+        }
+        let vec: Vec<u8> = std::fs::read(path)?;
+        let records: CompareRecords = bincode::deserialize(&vec[..])?;
+        Ok(records)
+    }
+
+    /// The subject and target sources compared, as a display string.
+    pub fn sources(&self) -> String {
+        format!("{} → {}", self.subject, self.target)
+    }
+}
+
+/// The `RunHistory` struct holds past comparison runs, oldest first.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize, Deref, DerefMut)]
+pub struct RunHistory(Vec<Run>);
+
+impl RunHistory {
+    /// Writes the history to `path`, through a temporary file so a failed write leaves the
+    /// previous history intact.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Clean<()> {
+        tracing::info!("Serializing run history to binary.");
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let temp = path.with_extension("tmp");
+        std::fs::write(&temp, bincode::serialize(self)?)?;
+        std::fs::rename(&temp, path)?;
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Clean<Self> {
+        tracing::info!("Deserializing run history from binary.");
+        let vec: Vec<u8> = std::fs::read(path)?;
+        let history: Self = bincode::deserialize(&vec[..])?;
+        Ok(history)
+    }
+
+    /// Loads the history at [`HISTORY_PATH`], or an empty history if none exists.  A history
+    /// that exists but cannot be read is an error, so that it is not replaced by an empty one.
+    pub fn local() -> Clean<Self> {
+        if !Path::new(HISTORY_PATH).exists() {
+            tracing::info!("No run history found.");
+            return Ok(Self::default());
+        }
+        Self::load(HISTORY_PATH)
+    }
+
+    /// Appends `run` to the history at [`HISTORY_PATH`], dropping the oldest runs beyond
+    /// [`RUN_LIMIT`].  The `records` of the run are written before returning, through a temporary
+    /// file so that a reload never reads a partial file, and the records of runs older than the
+    /// last [`RECORDS_LIMIT`] are removed.
+    pub fn record(run: Run, records: &CompareRecords) -> Clean<Self> {
+        let mut history = Self::local()?;
+        let path = run.records_path();
+        history.push(run);
+        if history.len() > RUN_LIMIT {
+            let excess = history.len() - RUN_LIMIT;
+            history.drain(..excess);
+        }
+        history.save(HISTORY_PATH)?;
+        let temp = path.with_extension("tmp");
+        let written = std::fs::write(&temp, bincode::serialize(records)?)
+            .and_then(|_| std::fs::rename(&temp, &path));
+        if let Err(e) = written {
+            tracing::warn!("Could not save run records: {}", e.to_string());
+        }
+        let expired = history
+            .iter()
+            .rev()
+            .skip(RECORDS_LIMIT)
+            .map(|v| v.records_path())
+            .collect::<Vec<PathBuf>>();
+        for path in expired.iter().filter(|v| v.exists()) {
+            if let Err(e) = std::fs::remove_file(path) {
+                tracing::warn!("Could not remove old run records: {}", e.to_string());
+            }
+        }
+        Ok(history)
+    }
+
+    /// Finds the run with id `id`.
+    pub fn run(&self, id: &uuid::Uuid) -> Option<&Run> {
+        self.iter().find(|v| v.id == *id)
+    }
+
+    /// Summarizes each run, with the change in counts from the previous run of the same sources.
+    pub fn trend(&self) -> RunTrend {
+        let mut rows = Vec::new();
+        for (i, run) in self.iter().enumerate() {
+            let previous = self[..i]
+                .iter()
+                .rev()
+                .find(|v| v.subject == run.subject && v.target == run.target);
+            rows.push(RunSummary::new(run, previous));
+        }
+        RunTrend(rows)
+    }
+}
+
+/// The `RunSummary` struct is a row in the trend view.  The `id` matches the summarized [`Run`].
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RunSummary {
+    pub id: uuid::Uuid,
+    pub time: String,
+    pub sources: String,
+    pub subject_file: String,
+    pub target_file: String,
+    pub matching: usize,
+    pub divergent: usize,
+    pub probable: usize,
+    pub missing: usize,
+    /// The `divergent_change` field holds the change in divergent records from the previous run.
+    pub divergent_change: Option<i64>,
+    /// The `missing_change` field holds the change in missing records from the previous run.
+    pub missing_change: Option<i64>,
+}
+
+impl RunSummary {
+    pub fn new(run: &Run, previous: Option<&Run>) -> Self {
+        Self {
+            id: run.id,
+            time: run
+                .time
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+            sources: run.sources(),
+            subject_file: run.subject_file.clone(),
+            target_file: run.target_file.clone(),
+            matching: run.matching,
+            divergent: run.divergent,
+            probable: run.probable,
+            missing: run.missing,
+            divergent_change: previous.map(|v| run.divergent as i64 - v.divergent as i64),
+            missing_change: previous.map(|v| run.missing as i64 - v.missing as i64),
+        }
+    }
+}
+
+#[derive(EnumIter, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RunColumns {
+    Time,
+    Sources,
+    SubjectFile,
+    TargetFile,
+    Matching,
+    Divergent,
+    DivergentChange,
+    Probable,
+    Missing,
+    MissingChange,
+}
+
+impl RunColumns {
+    pub fn value(&self, record: &RunSummary) -> Cell {
+        match self {
+            Self::Time => Cell::Text(record.time.clone()),
+            Self::Sources => Cell::Text(record.sources.clone()),
+            Self::SubjectFile => Cell::Text(record.subject_file.clone()),
+            Self::TargetFile => Cell::Text(record.target_file.clone()),
+            Self::Matching => Cell::from(record.matching),
+            Self::Divergent => Cell::from(record.divergent),
+            Self::DivergentChange => Cell::optional(record.divergent_change),
+            Self::Probable => Cell::from(record.probable),
+            Self::Missing => Cell::from(record.missing),
+            Self::MissingChange => Cell::optional(record.missing_change),
+        }
+    }
+}
+
+impl std::fmt::Display for RunColumns {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Time => write!(f, "Time"),
+            Self::Sources => write!(f, "Sources"),
+            Self::SubjectFile => write!(f, "Subject File"),
+            Self::TargetFile => write!(f, "Target File"),
+            Self::Matching => write!(f, "Matching"),
+            Self::Divergent => write!(f, "Divergent"),
+            Self::DivergentChange => write!(f, "Δ Divergent"),
+            Self::Probable => write!(f, "Probable"),
+            Self::Missing => write!(f, "Missing"),
+            Self::MissingChange => write!(f, "Δ Missing"),
+        }
+    }
+}
+
+impl Columnar for RunSummary {
+    fn cells(&self) -> Vec<Cell> {
+        RunColumns::iter()
+            .map(|v| v.value(self))
+            .collect::<Vec<Cell>>()
+    }
+
+    fn id(&self) -> uuid::Uuid {
+        self.id
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize, Deref, DerefMut)]
+pub struct RunTrend(Vec<RunSummary>);
+
+impl Tabular<RunSummary> for RunTrend {
+    fn headers() -> Vec<String> {
+        RunColumns::iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>()
+    }
+
+    fn rows(&self) -> Vec<RunSummary> {
+        self.to_vec()
+    }
+
+    fn set_rows(&mut self, rows: Vec<RunSummary>) {
+        self.0 = rows;
+    }
+}
+
+impl Filtration<RunTrend, String> for RunTrend {
+    /// Filters runs to the source pair `filter`, as given by [`Run::sources`].
+    fn filter(&mut self, filter: &String) -> Self {
+        let mut records = self.to_vec();
+        records.retain(|v| v.sources == *filter);
+        Self(records)
+    }
+}
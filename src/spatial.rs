@@ -0,0 +1,420 @@
+//! The `spatial` module holds quality assurance checks on the location of address points, rather
+//! than their attributes.  The checks find different addresses stacked on the same coordinate,
+//! points far from the rest of their street, active city addresses outside the city limits, and
+//! zip codes that disagree with the zip codes of neighboring points.  Results are reported as
+//! [`RuleIssues`] so they share the table and map layer of the attribute rules.
+use crate::address::AddressPoint;
+use crate::boundaries::Boundary;
+use crate::qa::address_street;
+use crate::rules::{RuleIssue, RuleIssues, Severity};
+use crate::utils::{distance, mercator_scale, web_mercator};
+use address::prelude::{Address, AddressStatus, SpatialAddress, SpatialAddresses};
+use geo::algorithm::contains::Contains;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use strum_macros::EnumIter;
+
+/// Path to the city limits boundary used by the [`SpatialCheck::Outside`] check.
+pub const CITY_LIMITS_PATH: &str = "data/city_limits.data";
+/// Width in meters of the grid cells used to find neighboring points.
+const CELL_SIZE: f64 = 250.0;
+
+/// The `SpatialCheck` enum lists the spatial quality checks.
+#[derive(
+    Debug,
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    EnumIter,
+    Deserialize,
+    Serialize,
+)]
+pub enum SpatialCheck {
+    /// Different addresses share the exact same coordinate.
+    #[default]
+    Stacked,
+    /// The point is far from every other address on its street in the same zip code and community.
+    Outlier,
+    /// An active address with the city as its postal community falls outside the city limits.
+    Outside,
+    /// Most of the nearest neighbors of the point use a different zip code.
+    ZipArea,
+}
+
+impl SpatialCheck {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::Outside => Severity::Error,
+            _ => Severity::Warning,
+        }
+    }
+
+    fn issue(&self, address: &SpatialAddress, detail: &str) -> RuleIssue {
+        RuleIssue::with_name(&self.to_string(), self.severity(), address, detail)
+    }
+}
+
+impl fmt::Display for SpatialCheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match self {
+            Self::Stacked => "Stacked points",
+            Self::Outlier => "Street outlier",
+            Self::Outside => "Outside city limits",
+            Self::ZipArea => "Zip area",
+        };
+        write!(f, "{}", msg)
+    }
+}
+
+/// The `SpatialConfig` struct holds the settings of the spatial checks.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SpatialConfig {
+    /// The `checks` field lists the checks to run.
+    pub checks: HashSet<SpatialCheck>,
+    /// The `outlier` field is the distance in meters to the nearest other address on the same
+    /// street beyond which a point is an outlier.
+    pub outlier: f64,
+    /// The `neighbors` field is the number of nearest points that vote on the expected zip code.
+    pub neighbors: usize,
+    /// The `agreement` field is the share of neighbors that must agree on a different zip code
+    /// before the point is flagged.
+    pub agreement: f64,
+    /// The `community` field is the postal community of the city.  Only addresses in this
+    /// community are checked against the city limits, since county addresses lie outside them by
+    /// design.
+    pub community: String,
+}
+
+impl Default for SpatialConfig {
+    fn default() -> Self {
+        Self {
+            checks: HashSet::from([
+                SpatialCheck::Stacked,
+                SpatialCheck::Outlier,
+                SpatialCheck::Outside,
+                SpatialCheck::ZipArea,
+            ]),
+            outlier: 1000.0,
+            neighbors: 8,
+            agreement: 0.75,
+            community: "GRANTS PASS".to_string(),
+        }
+    }
+}
+
+impl SpatialConfig {
+    /// Runs the selected checks over `addresses`.  The [`SpatialCheck::Outside`] check is skipped
+    /// if `limits` is `None`.
+    pub fn check(&self, addresses: &SpatialAddresses, limits: Option<&Boundary>) -> RuleIssues {
+        let mut issues = Vec::new();
+        if self.checks.contains(&SpatialCheck::Stacked) {
+            issues.extend(stacked(addresses));
+        }
+        if self.checks.contains(&SpatialCheck::Outlier) {
+            issues.extend(outliers(addresses, self.outlier));
+        }
+        if self.checks.contains(&SpatialCheck::Outside) {
+            match limits {
+                Some(boundary) => issues.extend(outside(addresses, boundary, &self.community)),
+                None => tracing::warn!("City limits not loaded, skipping jurisdiction check."),
+            }
+        }
+        if self.checks.contains(&SpatialCheck::ZipArea) {
+            issues.extend(zip_areas(addresses, self.neighbors, self.agreement));
+        }
+        tracing::info!("Spatial issues found: {}", issues.len());
+        RuleIssues::from(issues)
+    }
+}
+
+/// Flags different addresses on the exact same coordinate.  Units of one building commonly share a
+/// point, so addresses differing only by subaddress are not flagged.
+pub fn stacked(addresses: &SpatialAddresses) -> Vec<RuleIssue> {
+    let mut locations = HashMap::new();
+    for address in addresses.iter() {
+        locations
+            .entry((address.latitude.to_bits(), address.longitude.to_bits()))
+            .or_insert(Vec::new())
+            .push(address);
+    }
+    let mut issues = Vec::new();
+    for group in locations.values() {
+        let streets = group
+            .iter()
+            .map(|v| format!("{} {}", v.number(), address_street(v)))
+            .collect::<Vec<String>>();
+        let distinct = streets.iter().collect::<HashSet<&String>>();
+        if distinct.len() < 2 {
+            continue;
+        }
+        for (address, street) in group.iter().zip(streets.iter()) {
+            let mut others = distinct
+                .iter()
+                .filter(|v| **v != street)
+                .map(|v| v.to_string())
+                .collect::<Vec<String>>();
+            others.sort();
+            let detail = format!("Shares a location with {}.", others.join(", "));
+            issues.push(SpatialCheck::Stacked.issue(address, &detail));
+        }
+    }
+    issues
+}
+
+/// Flags points more than `meters` from the nearest other address on their street.  Streets are
+/// grouped by zip code and postal community as well as name, since the same street name recurs in
+/// different towns.  Groups with fewer than three addresses are skipped, as a pair of distant
+/// addresses gives no sign which of the two is misplaced.
+pub fn outliers(addresses: &SpatialAddresses, meters: f64) -> Vec<RuleIssue> {
+    let mut streets = BTreeMap::new();
+    for address in addresses.iter() {
+        let key = (
+            address_street(address),
+            address.zip(),
+            address.postal_community.to_uppercase(),
+        );
+        streets.entry(key).or_insert(Vec::new()).push(address);
+    }
+    let mut issues = Vec::new();
+    for ((street, zip, _), members) in streets.iter() {
+        if members.len() < 3 {
+            continue;
+        }
+        for (i, address) in members.iter().enumerate() {
+            let nearest = members
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, v)| {
+                    distance(address.latitude, address.longitude, v.latitude, v.longitude)
+                })
+                .fold(f64::INFINITY, f64::min);
+            if nearest > meters {
+                let detail = format!(
+                    "{:.0} m from the nearest of {} other addresses on {} in {}.",
+                    nearest,
+                    members.len() - 1,
+                    street,
+                    zip
+                );
+                issues.push(SpatialCheck::Outlier.issue(address, &detail));
+            }
+        }
+    }
+    issues
+}
+
+/// Flags addresses with an active status and the postal `community` of the city that fall
+/// outside the city `limits`.
+pub fn outside(addresses: &SpatialAddresses, limits: &Boundary, community: &str) -> Vec<RuleIssue> {
+    let community = community.trim().to_uppercase();
+    let mut issues = Vec::new();
+    for address in addresses.iter() {
+        if matches!(
+            address.status(),
+            AddressStatus::Retired | AddressStatus::Other
+        ) {
+            continue;
+        }
+        if address.postal_community.to_uppercase() != community {
+            continue;
+        }
+        if !limits
+            .geometry
+            .contains(&AddressPoint::from(address).geo_point())
+        {
+            let detail = format!(
+                "{} {} address lies outside {}.",
+                address.status(),
+                address.postal_community,
+                limits.name
+            );
+            issues.push(SpatialCheck::Outside.issue(address, &detail));
+        }
+    }
+    issues
+}
+
+/// Flags points whose zip code differs from the zip code shared by at least `agreement` of their
+/// `neighbors` nearest points.
+pub fn zip_areas(addresses: &SpatialAddresses, neighbors: usize, agreement: f64) -> Vec<RuleIssue> {
+    let grid = Grid::new(addresses);
+    let mut issues = Vec::new();
+    for (i, address) in addresses.iter().enumerate() {
+        let nearest = grid.nearest(i, neighbors);
+        if nearest.len() < neighbors {
+            continue;
+        }
+        let mut votes = BTreeMap::new();
+        for j in nearest.iter() {
+            *votes.entry(addresses[*j].zip()).or_insert(0) += 1;
+        }
+        if let Some((zip, count)) = votes.iter().max_by_key(|(_, count)| **count) {
+            let share = *count as f64 / nearest.len() as f64;
+            if *zip != address.zip() && share >= agreement {
+                let detail = format!(
+                    "Zip {} lies where {} of {} nearest addresses use {}.",
+                    address.zip(),
+                    count,
+                    nearest.len(),
+                    zip
+                );
+                issues.push(SpatialCheck::ZipArea.issue(address, &detail));
+            }
+        }
+    }
+    issues
+}
+
+/// The `Grid` struct buckets points into square cells of [`CELL_SIZE`] meters, so the neighbors
+/// of a point can be found without comparing it to every other point.
+struct Grid {
+    points: Vec<(f64, f64)>,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+    // The cell width in web mercator units, scaled from meters at the mean latitude.
+    size: f64,
+}
+
+impl Grid {
+    fn new(addresses: &SpatialAddresses) -> Self {
+        let points = addresses
+            .iter()
+            .map(|v| web_mercator(v.latitude, v.longitude))
+            .collect::<Vec<(f64, f64)>>();
+        let latitude = match addresses.len() {
+            0 => 0.0,
+            n => addresses.iter().map(|v| v.latitude).sum::<f64>() / n as f64,
+        };
+        let size = CELL_SIZE * mercator_scale(latitude);
+        let mut cells = HashMap::new();
+        for (i, point) in points.iter().enumerate() {
+            cells
+                .entry(Self::cell(size, point))
+                .or_insert(Vec::new())
+                .push(i);
+        }
+        Self {
+            points,
+            cells,
+            size,
+        }
+    }
+
+    fn cell(size: f64, point: &(f64, f64)) -> (i64, i64) {
+        (
+            (point.0 / size).floor() as i64,
+            (point.1 / size).floor() as i64,
+        )
+    }
+
+    /// Returns the indices of up to `count` points nearest the point at `index`, excluding points
+    /// on the same coordinate.
+    fn nearest(&self, index: usize, count: usize) -> Vec<usize> {
+        let origin = self.points[index];
+        let (cx, cy) = Self::cell(self.size, &origin);
+        let mut candidates = Vec::new();
+        // Widen the search ring by ring.  Once enough candidates are found, search one more ring,
+        // since a point in a neighboring cell can be closer than one in the same cell.
+        let mut found_at = None;
+        for ring in 0..20_i64 {
+            for x in (cx - ring)..=(cx + ring) {
+                for y in (cy - ring)..=(cy + ring) {
+                    if (x - cx).abs() != ring && (y - cy).abs() != ring {
+                        continue;
+                    }
+                    if let Some(members) = self.cells.get(&(x, y)) {
+                        for j in members {
+                            let point = self.points[*j];
+                            if point != origin {
+                                let d = (point.0 - origin.0).powi(2) + (point.1 - origin.1).powi(2);
+                                candidates.push((d, *j));
+                            }
+                        }
+                    }
+                }
+            }
+            match found_at {
+                Some(at) if ring > at => break,
+                None if candidates.len() >= count => found_at = Some(ring),
+                _ => {}
+            }
+        }
+        candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+        candidates
+            .iter()
+            .take(count)
+            .map(|(_, j)| *j)
+            .collect::<Vec<usize>>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stacked_skips_units_of_one_building() {
+        let mut unit = AddressPoint::sample_at(100, "MAIN", 97526, 0.0, 0.0);
+        *unit.subaddress_id_mut() = Some("A".to_string());
+        let units = vec![AddressPoint::sample_at(100, "MAIN", 97526, 0.0, 0.0), unit];
+        assert!(stacked(&SpatialAddresses::from(&units[..])).is_empty());
+
+        let mut records = units.clone();
+        records.push(AddressPoint::sample_at(102, "MAIN", 97526, 0.0, 0.0));
+        records.push(AddressPoint::sample_at(104, "MAIN", 97526, 1.0, 0.0));
+        let issues = stacked(&SpatialAddresses::from(&records[..]));
+        assert_eq!(issues.len(), 3);
+        assert!(issues.iter().all(|v| !v.address.starts_with("104")));
+    }
+
+    #[test]
+    fn outliers_group_by_street_and_zip() {
+        let records = vec![
+            AddressPoint::sample_at(100, "MAIN", 97526, 0.0, 0.0),
+            AddressPoint::sample_at(102, "MAIN", 97526, 0.5, 0.0),
+            AddressPoint::sample_at(104, "MAIN", 97526, 1.0, 0.0),
+            AddressPoint::sample_at(106, "MAIN", 97526, 50.0, 0.0),
+            // The same street name in another zip code is another street.
+            AddressPoint::sample_at(100, "MAIN", 97527, 80.0, 0.0),
+            AddressPoint::sample_at(102, "MAIN", 97527, 80.5, 0.0),
+            AddressPoint::sample_at(104, "MAIN", 97527, 81.0, 0.0),
+            // Too few addresses to tell which is misplaced.
+            AddressPoint::sample_at(100, "OAK", 97526, 0.0, 0.0),
+            AddressPoint::sample_at(900, "OAK", 97526, 50.0, 0.0),
+        ];
+        let issues = outliers(&SpatialAddresses::from(&records[..]), 1000.0);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].address, records[3].label());
+    }
+
+    #[test]
+    fn zip_areas_flag_a_lone_zip() {
+        let mut records = Vec::new();
+        for north in 0..3 {
+            for east in 0..3 {
+                let zip = if (north, east) == (1, 1) {
+                    97527
+                } else {
+                    97526
+                };
+                let number = 100 + north * 10 + east * 2;
+                records.push(AddressPoint::sample_at(
+                    number,
+                    "MAIN",
+                    zip,
+                    north as f64,
+                    east as f64,
+                ));
+            }
+        }
+        let issues = zip_areas(&SpatialAddresses::from(&records[..]), 8, 0.75);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].address, records[4].label());
+    }
+}
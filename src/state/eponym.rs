@@ -1,10 +1,12 @@
 use crate::controls::{act, command};
 use crate::prelude::{
-    Action, EguiState, GalileoState, MatchPoints, WgpuFrame, KEY_BINDINGS, MOUSE_BINDINGS,
+    Action, CandidatePoints, EguiState, GalileoState, IssuePoints, MapLayer, MatchPoints,
+    RulePoints, SegmentViews, WgpuFrame, KEY_BINDINGS, MOUSE_BINDINGS,
 };
 use crate::state::lens;
 use crate::tab;
 use aid::prelude::Clean;
+use galileo::galileo_types::geo::GeoPoint;
 use std::{iter, sync::Arc};
 use winit::dpi::{PhysicalPosition, PhysicalSize};
 use winit::event::*;
@@ -149,8 +151,25 @@ impl State {
             self.galileo_state.handle_event(event);
         }
 
+        let click = self.galileo_state.take_click();
         // Get the current tab from the dock state.
         if let Some(tab) = self.tab.tab() {
+            // Place a new address where the map was clicked, if the add address tool is active.
+            if let Some(point) = click {
+                if tab.operations.editor.adding {
+                    tab.operations.editor.place(point.lat(), point.lon());
+                }
+                // Describe the location clicked, if the pick tool of the reverse geocoder is active.
+                if tab.operations.reverse.picking {
+                    tab.operations.reverse.place(point.lat(), point.lon());
+                }
+            }
+            // Load addresses added in the editor to galileo
+            if let Some(points) = tab.operations.editor.package.take() {
+                self.galileo_state.added = Some(points);
+                self.galileo_state.load_added().unwrap();
+            }
+
             // Get the table view for the data in the active tab.
             if let Some(table) = &mut tab.operations.compare.table {
                 // Retrieve any package data in the table view.
@@ -158,7 +177,7 @@ impl State {
                     tracing::trace!("Package taken.");
                     let points = MatchPoints::from(&package);
                     self.galileo_state.addresses = Some(points);
-                    self.galileo_state.load_addresses(1).unwrap();
+                    self.galileo_state.load_addresses().unwrap();
                     tracing::trace!("Records added to map.");
                 }
             }
@@ -170,7 +189,7 @@ impl State {
                     // Move layer to galileo_state
                     self.galileo_state.boundary = Some(pkg.clone());
                     // Load layer into display.
-                    self.galileo_state.load_boundary(1).unwrap();
+                    self.galileo_state.load_boundary().unwrap();
                 }
             }
             // Load address results to galileo
@@ -178,7 +197,68 @@ impl State {
                 // Move layer data to galileo_state
                 self.galileo_state.lexis = Some(view.clone());
                 // Load layer into display.
-                self.galileo_state.load_lexis(2).unwrap();
+                self.galileo_state.load_lexis().unwrap();
+            }
+            // Load range validation results to galileo
+            if let Some(issues) = &tab.operations.lexis.qa_pkg.take() {
+                // Move layer data to galileo_state
+                self.galileo_state.issues = Some(IssuePoints::from(issues));
+                self.galileo_state.load_issues().unwrap();
+            }
+            // Load attribute validation results to galileo
+            if let Some(issues) = &tab.operations.validation.package.take() {
+                self.galileo_state.rules = Some(RulePoints::from(issues));
+                self.galileo_state.load_rules(MapLayer::Validation).unwrap();
+            }
+            // Load spatial QA results to galileo.
+            if let Some(issues) = &tab.operations.spatial.package.take() {
+                self.galileo_state.rules = Some(RulePoints::from(issues));
+                self.galileo_state.load_rules(MapLayer::Spatial).unwrap();
+            }
+            // Load street centerline results to galileo.
+            if let Some(issues) = &tab.operations.streets.package.take() {
+                self.galileo_state.rules = Some(RulePoints::from(issues));
+                self.galileo_state.load_rules(MapLayer::Streets).unwrap();
+            }
+            // Load the street centerlines to galileo.
+            if let Some(centerlines) = tab.operations.streets.layer.take() {
+                self.galileo_state.centerlines = Some(SegmentViews::from(centerlines.as_ref()));
+                self.galileo_state.load_centerlines().unwrap();
+            }
+            // Load the address chosen in the search bar to galileo.
+            if let Some(candidates) = &tab.operations.geocode.package.take() {
+                self.galileo_state.candidates = Some(CandidatePoints::from(candidates));
+                self.galileo_state
+                    .load_candidates(MapLayer::Search)
+                    .unwrap();
+            }
+            if let Some((latitude, longitude)) = tab.operations.geocode.focus.take() {
+                self.galileo_state.zoom(latitude, longitude).unwrap();
+            }
+            // Load the reverse geocoded location to galileo.
+            if let Some(points) = tab.operations.reverse.package.take() {
+                self.galileo_state.candidates = Some(points);
+                self.galileo_state
+                    .load_candidates(MapLayer::Reverse)
+                    .unwrap();
+            }
+            if let Some((latitude, longitude)) = tab.operations.reverse.focus.take() {
+                self.galileo_state.zoom(latitude, longitude).unwrap();
+            }
+            // Load the batch geocoding results to galileo.
+            if let Some(points) = tab.operations.batch.package.take() {
+                self.galileo_state.candidates = Some(points);
+                self.galileo_state.load_candidates(MapLayer::Batch).unwrap();
+            }
+            if let Some((latitude, longitude)) = tab.operations.batch.focus.take() {
+                self.galileo_state.zoom(latitude, longitude).unwrap();
+            }
+            // Zoom to the issue selected in the spatial or street QA table.
+            if let Some((latitude, longitude)) = tab.operations.spatial.focus.take() {
+                self.galileo_state.zoom(latitude, longitude).unwrap();
+            }
+            if let Some((latitude, longitude)) = tab.operations.streets.focus.take() {
+                self.galileo_state.zoom(latitude, longitude).unwrap();
             }
         }
 
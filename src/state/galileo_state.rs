@@ -1,11 +1,13 @@
 use crate::prelude::{
-    AddressPoints, AddressSymbol, BoundarySymbol, BoundaryView, MatchPoints, MatchSymbol, WgpuFrame,
+    AddressPoint, AddressPoints, AddressSymbol, BoundarySymbol, BoundaryView, CandidatePoints,
+    CandidateSymbol, CenterlineSymbol, IssuePoints, IssueSymbol, MatchPoints, MatchSymbol,
+    RulePoints, RuleSymbol, SegmentViews, WgpuFrame,
 };
 use address::address::Address;
 use address::address_components::AddressStatus;
 use address::prelude::SpatialAddresses;
 use aid::prelude::Clean;
-use galileo::control::{EventPropagation, MouseEvent, UserEvent};
+use galileo::control::{EventPropagation, MouseButton, MouseEvent, UserEvent};
 use galileo::galileo_types::cartesian::Point2d;
 use galileo::galileo_types::cartesian::Size;
 use galileo::galileo_types::geo::impls::GeoPoint2d;
@@ -20,10 +22,38 @@ use galileo::{
     Map, MapBuilder, MapView, TileSchema,
 };
 use std::sync::{Arc, RwLock};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
 use wgpu::{Device, Queue, Surface, SurfaceConfiguration};
 use winit::dpi::PhysicalSize;
 use winit::window::Window;
 
+/// The `MapLayer` enum names the layers drawn above the base tiles, from bottom to top.  Each
+/// layer has a fixed slot on the map, so loading one replaces it in place and leaves the others.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter)]
+pub enum MapLayer {
+    Boundary,
+    Compare,
+    Included,
+    Excluded,
+    RangeIssues,
+    Added,
+    Validation,
+    Spatial,
+    Streets,
+    Centerlines,
+    Search,
+    Reverse,
+    Batch,
+}
+
+impl MapLayer {
+    /// Position of the layer on the map, counting the base tiles at 0.
+    pub fn index(&self) -> usize {
+        *self as usize + 1
+    }
+}
+
 pub struct GalileoState {
     input_handler: WinitInputHandler,
     event_processor: EventProcessor,
@@ -32,6 +62,17 @@ pub struct GalileoState {
     pub addresses: Option<MatchPoints>,
     pub boundary: Option<BoundaryView>,
     pub lexis: Option<Vec<SpatialAddresses>>,
+    pub issues: Option<IssuePoints>,
+    /// The `added` field holds addresses added by the editor.
+    pub added: Option<AddressPoints>,
+    /// The `rules` field holds the issues found by the attribute validation rules.
+    pub rules: Option<RulePoints>,
+    /// The `centerlines` field holds the street centerline layer.
+    pub centerlines: Option<SegmentViews>,
+    /// The `candidates` field holds the locations found by the geocoder.
+    pub candidates: Option<CandidatePoints>,
+    /// The location of the last left click on the map, awaiting the add address tool.
+    click: Arc<RwLock<Option<GeoPoint2d>>>,
     #[allow(dead_code)]
     pointer_position: Arc<RwLock<Point2d>>,
 }
@@ -66,6 +107,23 @@ impl GalileoState {
 
             EventPropagation::Propagate
         });
+        let click = Arc::new(RwLock::new(None));
+        let click_clone = click.clone();
+        event_processor.add_handler(move |ev: &UserEvent, map: &mut Map| {
+            if let UserEvent::Click(
+                MouseButton::Left,
+                MouseEvent {
+                    screen_pointer_position,
+                    ..
+                },
+            ) = ev
+            {
+                let position = map.view().screen_to_map_geo(*screen_pointer_position);
+                *click_clone.write().expect("poisoned lock") = position;
+            }
+
+            EventPropagation::Propagate
+        });
         event_processor.add_handler(MapController::default());
 
         let builder = MapBuilder::new();
@@ -99,11 +157,16 @@ impl GalileoState {
             TileSchema::web(18),
         ));
 
-        let map = Arc::new(RwLock::new(galileo::Map::new(
-            view,
-            vec![layer],
-            Some(messenger),
-        )));
+        let mut map = galileo::Map::new(view, vec![layer], Some(messenger));
+        // Hold each slot with an empty layer until its feature is loaded.
+        for _ in MapLayer::iter() {
+            map.layers_mut().push(FeatureLayer::new(
+                Vec::<AddressPoint>::new(),
+                AddressSymbol {},
+                Crs::WGS84,
+            ));
+        }
+        let map = Arc::new(RwLock::new(map));
 
         GalileoState {
             input_handler,
@@ -113,10 +176,21 @@ impl GalileoState {
             addresses: Default::default(),
             boundary: Default::default(),
             lexis: Default::default(),
+            issues: Default::default(),
+            added: Default::default(),
+            rules: Default::default(),
+            centerlines: Default::default(),
+            candidates: Default::default(),
+            click,
             pointer_position,
         }
     }
 
+    /// Takes the location of the last left click on the map, if any.
+    pub fn take_click(&self) -> Option<GeoPoint2d> {
+        self.click.write().expect("poisoned lock").take()
+    }
+
     pub fn about_to_wait(&self) {
         self.map.write().unwrap().animate();
     }
@@ -168,43 +242,41 @@ impl GalileoState {
     //     }
     // }
 
-    pub fn load_addresses(&mut self, index: usize) -> Clean<()> {
+    pub fn load_addresses(&mut self) -> Clean<()> {
         let mut map = self.map.write()?;
         let layers = map.layers_mut();
+        let index = MapLayer::Compare.index();
         if let Some(points) = &self.addresses {
-            while layers.len() > index {
-                layers.pop();
-            }
-            layers.push(FeatureLayer::new(
-                points.records.clone(),
-                MatchSymbol {},
-                Crs::WGS84,
-            ));
+            layers.remove(index);
+            layers.insert(
+                index,
+                FeatureLayer::new(points.records.clone(), MatchSymbol {}, Crs::WGS84),
+            );
             tracing::trace!("Layer pushed to map.");
         }
         Ok(())
     }
 
-    pub fn load_boundary(&mut self, index: usize) -> Clean<()> {
+    pub fn load_boundary(&mut self) -> Clean<()> {
         let mut map = self.map.write()?;
         let layers = map.layers_mut();
+        let index = MapLayer::Boundary.index();
         if let Some(view) = &self.boundary {
-            while layers.len() > index {
-                layers.pop();
-            }
-            layers.push(FeatureLayer::new(
-                vec![view.clone()],
-                BoundarySymbol {},
-                Crs::EPSG3857,
-            ));
+            layers.remove(index);
+            layers.insert(
+                index,
+                FeatureLayer::new(vec![view.clone()], BoundarySymbol {}, Crs::EPSG3857),
+            );
             tracing::trace!("Layer pushed to map.");
         }
         Ok(())
     }
 
-    pub fn load_lexis(&mut self, index: usize) -> Clean<()> {
+    pub fn load_lexis(&mut self) -> Clean<()> {
         let mut map = self.map.write()?;
         let layers = map.layers_mut();
+        let included = MapLayer::Included.index();
+        let excluded = MapLayer::Excluded.index();
         if let Some(lexis) = &self.lexis {
             let mut records = AddressPoints::from(&lexis[0]);
             records
@@ -216,23 +288,109 @@ impl GalileoState {
                 .iter_mut()
                 .map(|a| *a.address.status_mut() = AddressStatus::Retired)
                 .for_each(drop);
-            while layers.len() > index {
-                layers.pop();
-            }
-            layers.push(FeatureLayer::new(
-                records.to_vec(),
-                AddressSymbol {},
-                Crs::EPSG3857,
-            ));
+            layers.remove(included);
+            layers.insert(
+                included,
+                FeatureLayer::new(records.to_vec(), AddressSymbol {}, Crs::EPSG3857),
+            );
             tracing::trace!("Included addresses pushed to map.");
-            layers.push(FeatureLayer::new(
-                other.to_vec(),
-                AddressSymbol {},
-                Crs::EPSG3857,
-            ));
+            layers.remove(excluded);
+            layers.insert(
+                excluded,
+                FeatureLayer::new(other.to_vec(), AddressSymbol {}, Crs::EPSG3857),
+            );
             tracing::trace!("Excluded addresses pushed to map.");
         }
 
         Ok(())
     }
+
+    pub fn load_issues(&mut self) -> Clean<()> {
+        let mut map = self.map.write()?;
+        let layers = map.layers_mut();
+        let index = MapLayer::RangeIssues.index();
+        if let Some(points) = &self.issues {
+            layers.remove(index);
+            layers.insert(
+                index,
+                FeatureLayer::new(points.records.clone(), IssueSymbol {}, Crs::WGS84),
+            );
+            tracing::trace!("Issues pushed to map.");
+        }
+        Ok(())
+    }
+
+    pub fn load_added(&mut self) -> Clean<()> {
+        let mut map = self.map.write()?;
+        let layers = map.layers_mut();
+        let index = MapLayer::Added.index();
+        if let Some(points) = &self.added {
+            layers.remove(index);
+            layers.insert(
+                index,
+                FeatureLayer::new(points.to_vec(), AddressSymbol {}, Crs::WGS84),
+            );
+            tracing::trace!("Added addresses pushed to map.");
+        }
+        Ok(())
+    }
+
+    pub fn load_centerlines(&mut self) -> Clean<()> {
+        let mut map = self.map.write()?;
+        let layers = map.layers_mut();
+        let index = MapLayer::Centerlines.index();
+        if let Some(views) = &self.centerlines {
+            layers.remove(index);
+            layers.insert(
+                index,
+                FeatureLayer::new(views.records.clone(), CenterlineSymbol {}, Crs::EPSG3857),
+            );
+            tracing::trace!("Centerlines pushed to map.");
+        }
+        Ok(())
+    }
+
+    /// Loads the geocoded locations into `slot`, which differs for search, reverse and batch
+    /// geocoding.
+    pub fn load_candidates(&mut self, slot: MapLayer) -> Clean<()> {
+        let mut map = self.map.write()?;
+        let layers = map.layers_mut();
+        let index = slot.index();
+        if let Some(points) = &self.candidates {
+            layers.remove(index);
+            layers.insert(
+                index,
+                FeatureLayer::new(points.records.clone(), CandidateSymbol {}, Crs::WGS84),
+            );
+            tracing::trace!("Geocoded locations pushed to map.");
+        }
+        Ok(())
+    }
+
+    /// Centers the map on the point at `latitude` and `longitude`, at street level.
+    pub fn zoom(&mut self, latitude: f64, longitude: f64) -> Clean<()> {
+        let view = MapView::new(
+            &GeoPoint2d::latlon(latitude, longitude),
+            TileSchema::web(18).lod_resolution(17).unwrap(),
+        );
+        let mut map = self.map.write()?;
+        map.set_view(view);
+        Ok(())
+    }
+
+    /// Loads the rule issues into `slot`, which differs for validation, spatial and street QA.
+    pub fn load_rules(&mut self, slot: MapLayer) -> Clean<()> {
+        let mut map = self.map.write()?;
+        let layers = map.layers_mut();
+        let index = slot.index();
+        if let Some(points) = &self.rules {
+            layers.remove(index);
+            layers.insert(
+                index,
+                FeatureLayer::new(points.records.clone(), RuleSymbol {}, Crs::WGS84),
+            );
+            tracing::trace!("Rule issues pushed to map.");
+        }
+        Ok(())
+    }
 }
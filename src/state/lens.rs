@@ -1,6 +1,7 @@
+use crate::audit::{self, AuditKind};
 use crate::controls::{act, command, focus};
 use crate::prelude::{AddressPoint, AddressPoints, Parcels, TableConfig, TableView};
-use crate::{data, ops};
+use crate::{data, ops, undo};
 use aid::prelude::Clean;
 // use derive_more::{Deref, DerefMut};
 // use egui::{Context, Id, TextStyle};
@@ -22,6 +23,9 @@ pub struct Lens {
     pub parcels: Option<Arc<Parcels>>,
     pub operations: ops::Operations,
     pub data: data::Data,
+    /// The `history` field holds the undo and redo stacks, which are not saved with the project.
+    #[serde(skip)]
+    pub history: undo::History,
     notify: egui_notify::Toasts,
 }
 
@@ -78,6 +82,7 @@ impl Lens {
             parcels,
             operations: Default::default(),
             data: Default::default(),
+            history: Default::default(),
             notify: Default::default(),
         }
     }
@@ -115,10 +120,103 @@ impl Lens {
                 }
             }
             act::EguiAct::InspectTree => self.focus_tree.inspect(),
+            act::EguiAct::Undo => self.undo(),
+            act::EguiAct::Redo => self.redo(),
+            act::EguiAct::History => self.history.visible = !self.history.visible,
             act::EguiAct::Be => tracing::trace!("Taking no action."),
         }
     }
 
+    /// Merges the snapshot left by the last undo or redo into the undoable state of the tab.
+    fn restore(&mut self) {
+        if let Some(snapshot) = self.history.snapshot() {
+            snapshot.restore(
+                &mut self.address_table,
+                &mut self.operations,
+                &mut self.data.catalog,
+            );
+        }
+    }
+
+    pub fn undo(&mut self) {
+        let state = undo::SnapshotRef {
+            address_table: &self.address_table,
+            operations: &self.operations,
+            catalog: &self.data.catalog,
+        };
+        match self.history.undo(state) {
+            Some(label) => {
+                tracing::info!("Undoing last change.");
+                self.audit_revision("Undid", &label);
+                self.restore();
+            }
+            None => tracing::info!("Nothing to undo."),
+        }
+    }
+
+    pub fn redo(&mut self) {
+        let state = undo::SnapshotRef {
+            address_table: &self.address_table,
+            operations: &self.operations,
+            catalog: &self.data.catalog,
+        };
+        match self.history.redo(state) {
+            Some(label) => {
+                tracing::info!("Redoing last change.");
+                self.audit_revision("Redid", &label);
+                self.restore();
+            }
+            None => tracing::info!("Nothing to redo."),
+        }
+    }
+
+    /// Records an undo or redo in the audit log, so the log accounts for edits that were reverted.
+    /// Address edits are attributed to the dataset open in the editor.
+    fn audit_revision(&self, action: &str, label: &str) {
+        let dataset = if label == undo::EDIT_LABEL {
+            self.operations.editor.dataset
+        } else {
+            None
+        };
+        audit::record(AuditKind::Edit, dataset, &format!("{action} {label}."));
+    }
+
+    /// Logic for the history window, listing recent changes.  Clicking an entry undoes or redoes
+    /// changes until the state after that entry is restored.
+    pub fn history_widget(&mut self, ui: &mut egui::Ui) {
+        let mut visible = self.history.visible;
+        let mut steps = None;
+        egui::Window::new("History")
+            .open(&mut visible)
+            .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(0.0, 0.0))
+            .show(ui.ctx(), |ui| {
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(self.history.can_undo(), egui::Button::new("Undo"))
+                        .clicked()
+                    {
+                        steps = Some(-1);
+                    }
+                    if ui
+                        .add_enabled(self.history.can_redo(), egui::Button::new("Redo"))
+                        .clicked()
+                    {
+                        steps = Some(1);
+                    }
+                });
+                ui.separator();
+                if let Some(n) = self.history.show(ui) {
+                    steps = Some(n);
+                }
+            });
+        self.history.visible = visible;
+        match steps {
+            Some(n) if n < 0 => (0..-n).for_each(|_| self.undo()),
+            Some(n) => (0..n).for_each(|_| self.redo()),
+            None => {}
+        }
+    }
+
     /// Logic for the load widget.
     pub fn load_widget(&mut self, ui: &mut egui::Ui) {
         let mut tree = focus::Tree::new();
@@ -180,9 +278,12 @@ impl Lens {
             egui::Window::new("Compare")
                 .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 0.0))
                 .show(ui.ctx(), |ui| {
-                    self.operations
-                        .compare
-                        .combo(ui, &mut self.focus_tree, &mut self.data);
+                    self.operations.compare.combo(
+                        ui,
+                        &mut self.focus_tree,
+                        &mut self.data,
+                        &self.operations.master.streets,
+                    );
                 });
         }
         if self.focus_tree.contains_new(&tree) {
@@ -224,17 +325,55 @@ impl Lens {
             self.operations.toggle_duplicates();
         }
 
-        let lexis = ui.button("LexisNexis");
+        let matrix = ui.button("Matrix");
+        tree.with_new_leaf(parent_node, &matrix);
+        self.focus_tree.focusable(&matrix);
+
+        if matrix.clicked() {
+            tracing::info!("Matrix clicked.");
+            self.operations.toggle_matrix();
+        }
+
+        if self.operations.matrix_visible() {
+            egui::Window::new("Matrix")
+                .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 0.0))
+                .show(ui.ctx(), |ui| {
+                    self.operations
+                        .matrix
+                        .combo(ui, &mut self.focus_tree, &self.data);
+                });
+        }
+
+        let editor = ui.button("Edit Addresses");
+        tree.with_new_leaf(parent_node, &editor);
+        self.focus_tree.focusable(&editor);
+
+        if editor.clicked() {
+            tracing::info!("Edit addresses clicked.");
+            self.operations.toggle_editor();
+        }
+
+        if self.operations.editor_visible() {
+            egui::Window::new("Edit Addresses")
+                .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 0.0))
+                .show(ui.ctx(), |ui| {
+                    self.operations
+                        .editor
+                        .combo(ui, &mut self.focus_tree, &mut self.data);
+                });
+        }
+
+        let lexis = ui.button("Range Export");
         tree.with_new_leaf(parent_node, &lexis);
         self.focus_tree.focusable(&lexis);
 
         if lexis.clicked() {
-            tracing::info!("LexisNexis clicked.");
+            tracing::info!("Range export clicked.");
             self.operations.toggle_lexis();
         }
 
         if self.operations.lexis_visible() {
-            egui::Window::new("LexisNexis")
+            egui::Window::new("Range Export")
                 .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 0.0))
                 .show(ui.ctx(), |ui| {
                     self.operations
@@ -243,6 +382,154 @@ impl Lens {
                 });
         }
 
+        let validation = ui.button("Validate Addresses");
+        tree.with_new_leaf(parent_node, &validation);
+        self.focus_tree.focusable(&validation);
+
+        if validation.clicked() {
+            tracing::info!("Validate addresses clicked.");
+            self.operations.toggle_validation();
+        }
+
+        if self.operations.validation_visible() {
+            egui::Window::new("Validate Addresses")
+                .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 0.0))
+                .show(ui.ctx(), |ui| {
+                    self.operations.validation.combo(
+                        ui,
+                        &mut self.focus_tree,
+                        &self.data,
+                        &self.operations.editor.lifecycle,
+                        &self.operations.master.streets,
+                    );
+                });
+        }
+        if let Some(label) = self.operations.validation.inspect.take() {
+            self.operations.inspect(&label, &self.data);
+        }
+
+        let spatial = ui.button("Spatial QA");
+        tree.with_new_leaf(parent_node, &spatial);
+        self.focus_tree.focusable(&spatial);
+
+        if spatial.clicked() {
+            tracing::info!("Spatial QA clicked.");
+            self.operations.toggle_spatial();
+        }
+
+        if self.operations.spatial_visible() {
+            egui::Window::new("Spatial QA")
+                .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 0.0))
+                .show(ui.ctx(), |ui| {
+                    self.operations
+                        .spatial
+                        .combo(ui, &mut self.focus_tree, &self.data);
+                });
+        }
+
+        let streets = ui.button("Streets");
+        tree.with_new_leaf(parent_node, &streets);
+        self.focus_tree.focusable(&streets);
+
+        if streets.clicked() {
+            tracing::info!("Streets clicked.");
+            self.operations.toggle_streets();
+        }
+
+        if self.operations.streets_visible() {
+            egui::Window::new("Streets")
+                .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 0.0))
+                .show(ui.ctx(), |ui| {
+                    self.operations.streets.combo(
+                        ui,
+                        &mut self.focus_tree,
+                        &self.data,
+                        &self.operations.master.streets,
+                    );
+                });
+        }
+
+        let master = ui.button("Street Master");
+        tree.with_new_leaf(parent_node, &master);
+        self.focus_tree.focusable(&master);
+
+        if master.clicked() {
+            tracing::info!("Street master clicked.");
+            self.operations.toggle_master();
+        }
+
+        if self.operations.master_visible() {
+            egui::Window::new("Street Master")
+                .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 0.0))
+                .show(ui.ctx(), |ui| {
+                    self.operations
+                        .master
+                        .combo(ui, &mut self.focus_tree, &self.data);
+                });
+        }
+
+        let reverse = ui.button("Reverse Geocode");
+        tree.with_new_leaf(parent_node, &reverse);
+        self.focus_tree.focusable(&reverse);
+
+        if reverse.clicked() {
+            tracing::info!("Reverse geocode clicked.");
+            self.operations.toggle_reverse();
+        }
+
+        if self.operations.reverse_visible() {
+            egui::Window::new("Reverse Geocode")
+                .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 0.0))
+                .show(ui.ctx(), |ui| {
+                    self.operations.reverse.combo(
+                        ui,
+                        &mut self.focus_tree,
+                        &self.data,
+                        self.parcels.as_deref(),
+                    );
+                });
+        }
+
+        let batch = ui.button("Batch Geocode");
+        tree.with_new_leaf(parent_node, &batch);
+        self.focus_tree.focusable(&batch);
+
+        if batch.clicked() {
+            tracing::info!("Batch geocode clicked.");
+            self.operations.toggle_batch();
+        }
+
+        self.operations.batch.receive();
+        if self.operations.batch_visible() {
+            egui::Window::new("Batch Geocode")
+                .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 0.0))
+                .show(ui.ctx(), |ui| {
+                    self.operations.batch.combo(
+                        ui,
+                        &mut self.focus_tree,
+                        &self.data,
+                        &self.operations.master,
+                    );
+                });
+        }
+
+        let audit = ui.button("Audit Log");
+        tree.with_new_leaf(parent_node, &audit);
+        self.focus_tree.focusable(&audit);
+
+        if audit.clicked() {
+            tracing::info!("Audit log clicked.");
+            self.operations.toggle_audit();
+        }
+
+        if self.operations.audit_visible() {
+            egui::Window::new("Audit Log")
+                .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 0.0))
+                .show(ui.ctx(), |ui| {
+                    self.operations.audit.combo(ui, &mut self.focus_tree);
+                });
+        }
+
         if self.operations.duplicates_visible() {
             egui::Window::new("Duplicates")
                 .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 0.0))
@@ -254,6 +541,15 @@ impl Lens {
                 });
         }
 
+        let find = ui.button("Find Address");
+        tree.with_new_leaf(parent_node, &find);
+        self.focus_tree.focusable(&find);
+
+        if find.clicked() {
+            tracing::info!("Find address clicked.");
+            self.operations.toggle_geocode();
+        }
+
         egui::Window::new("Commands")
             .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(0.0, 0.0))
             .show(ui.ctx(), |ui| self.command_view.show(ui));
@@ -299,9 +595,40 @@ impl Lens {
             tracing::info!("Focus tree updated.");
         }
 
+        if self.history.visible {
+            self.history_widget(ui);
+        }
+        // Record any change made this frame, after all widgets have run.
+        let state = undo::SnapshotRef {
+            address_table: &self.address_table,
+            operations: &self.operations,
+            catalog: &self.data.catalog,
+        };
+        self.history.track(state);
+
         self.notify.show(ui.ctx());
     }
 
+    /// Shows the address search over the top right corner of the map, if it is open.  Call after
+    /// the side panel is laid out, so the space left to the map is known.
+    pub fn search_overlay(&mut self, ctx: &egui::Context) {
+        if !self.operations.geocode_visible() {
+            return;
+        }
+        egui::Window::new("Find Address")
+            .title_bar(false)
+            .resizable(false)
+            .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-8.0, 8.0))
+            .show(ctx, |ui| {
+                self.operations.geocode.combo(
+                    ui,
+                    &mut self.focus_tree,
+                    &self.data,
+                    &self.operations.master,
+                );
+            });
+    }
+
     pub fn save<P: AsRef<Path>>(&self, path: P) -> Clean<()> {
         address::utils::save(&self, path)?;
         Ok(())
@@ -6,5 +6,5 @@ pub mod lens;
 
 pub use egui_state::EguiState;
 pub use eponym::State;
-pub use galileo_state::GalileoState;
+pub use galileo_state::{GalileoState, MapLayer};
 pub use gpu::WgpuFrame;
@@ -0,0 +1,557 @@
+//! The `streets` module reads street centerlines and checks address points against them.  Each
+//! [`Segment`] holds the street name and the address ranges on its left and right sides, and
+//! [`Centerlines::check`] compares each address to its nearest segment for a matching street
+//! name, a number within the range, and a number on the side of the street with its parity.
+use crate::config;
+use crate::master::StreetLookup;
+use crate::qa::abbreviate_directional;
+use crate::rules::{RuleIssue, RuleIssues, Severity};
+use crate::utils::{mercator_scale, web_mercator};
+use address::prelude::{load_bin, Address, SpatialAddress, SpatialAddresses, StreetNamePostType};
+use aid::prelude::{Bandage, Clean};
+use galileo::galileo_types::cartesian::{CartesianPoint3d, Point2d};
+use galileo::galileo_types::geometry::Geom;
+use galileo::galileo_types::impls::{Contour, Polygon};
+use galileo::layer::feature_layer::{symbol, Feature};
+use galileo::render::render_bundle::RenderPrimitive;
+use galileo::Color;
+use num_traits::AsPrimitive;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use strum_macros::EnumIter;
+use tracing::{info, trace};
+
+/// Location of the binary copy of the imported centerlines.
+pub const CENTERLINES_PATH: &str = "data/centerlines.data";
+
+/// The `CenterlineFields` struct names the attribute fields of a centerline file, read from the
+/// `centerlines` table of the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CenterlineFields {
+    /// The `name` field holds the full street name, including directional and street type.
+    pub name: String,
+    pub left_from: String,
+    pub left_to: String,
+    pub right_from: String,
+    pub right_to: String,
+}
+
+impl Default for CenterlineFields {
+    fn default() -> Self {
+        Self {
+            name: "FULLNAME".to_string(),
+            left_from: "L_F_ADD".to_string(),
+            left_to: "L_T_ADD".to_string(),
+            right_from: "R_F_ADD".to_string(),
+            right_to: "R_T_ADD".to_string(),
+        }
+    }
+}
+
+/// Reads the `centerlines` table from the config toml.  Other tables in the config file are
+/// ignored.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+struct CenterlineConfig {
+    #[serde(default)]
+    centerlines: CenterlineFields,
+}
+
+impl CenterlineFields {
+    /// Reads the field names from `config.toml` in the working directory if present, falling back
+    /// to the config bundled with the application.
+    pub fn with_config() -> Clean<Self> {
+        let config: CenterlineConfig = config::read()?;
+        Ok(config.centerlines)
+    }
+}
+
+/// The `Side` enum names the side of a segment, facing from its start to its end.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Left => write!(f, "left"),
+            Self::Right => write!(f, "right"),
+        }
+    }
+}
+
+/// The `Segment` struct is a street centerline between two intersections.  Coordinates are in web
+/// mercator (EPSG:3857) meters, matching the map.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Segment {
+    /// The `name` field holds the street name, normalized to compare with address street names.
+    pub name: String,
+    /// The `left` field holds the from and to address numbers on the left side.
+    pub left: (i64, i64),
+    /// The `right` field holds the from and to address numbers on the right side.
+    pub right: (i64, i64),
+    pub points: Vec<(f64, f64)>,
+}
+
+impl Segment {
+    /// The address range on `side`, as (low, high).
+    pub fn range(&self, side: Side) -> (i64, i64) {
+        let (from, to) = match side {
+            Side::Left => self.left,
+            Side::Right => self.right,
+        };
+        (from.min(to), from.max(to))
+    }
+
+    /// The distance in web mercator units from `point` to the segment, and the side of the segment
+    /// the point lies on.
+    pub fn locate(&self, point: (f64, f64)) -> Option<(f64, Side)> {
+        let mut nearest: Option<(f64, Side)> = None;
+        for pair in self.points.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+            let length = dx * dx + dy * dy;
+            let t = if length > 0.0 {
+                (((point.0 - a.0) * dx + (point.1 - a.1) * dy) / length).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let (px, py) = (a.0 + t * dx, a.1 + t * dy);
+            let d = ((point.0 - px).powi(2) + (point.1 - py).powi(2)).sqrt();
+            // The sign of the cross product gives the side, facing along the segment.
+            let cross = dx * (point.1 - a.1) - dy * (point.0 - a.0);
+            let side = if cross > 0.0 { Side::Left } else { Side::Right };
+            if nearest.map(|(v, _)| d < v).unwrap_or(true) {
+                nearest = Some((d, side));
+            }
+        }
+        nearest
+    }
+
+    fn bounds(&self) -> ((f64, f64), (f64, f64)) {
+        let mut min = (f64::MAX, f64::MAX);
+        let mut max = (f64::MIN, f64::MIN);
+        for (x, y) in self.points.iter() {
+            min = (min.0.min(*x), min.1.min(*y));
+            max = (max.0.max(*x), max.1.max(*y));
+        }
+        (min, max)
+    }
+}
+
+/// Normalizes a centerline street name to the form produced by
+/// [`address_street`](crate::qa::address_street), abbreviating the directional and street type.
+pub fn normalize_street(name: &str) -> String {
+    let mut words = name
+        .split_whitespace()
+        .map(|v| v.to_uppercase())
+        .collect::<Vec<String>>();
+    if let Some(first) = words.first_mut() {
+        *first = abbreviate_directional(first);
+    }
+    if words.len() > 1 {
+        if let Some(last) = words.last_mut() {
+            if let Some(value) = StreetNamePostType::match_mixed(last) {
+                *last = value.abbreviate().to_string().to_uppercase();
+            }
+        }
+    }
+    words.join(" ")
+}
+
+/// The `StreetCheck` enum lists the checks of addresses against centerlines.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter)]
+pub enum StreetCheck {
+    /// No segment lies within the search distance of the address.
+    Unmatched,
+    /// The nearest segment has a different street name.
+    Name,
+    /// The address number falls outside the range of the nearest segment.
+    Range,
+    /// The address number has the parity of the opposite side of the street.
+    Side,
+}
+
+impl StreetCheck {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::Name => Severity::Error,
+            _ => Severity::Warning,
+        }
+    }
+
+    fn issue(&self, address: &SpatialAddress, detail: &str) -> RuleIssue {
+        RuleIssue::with_name(&self.to_string(), self.severity(), address, detail)
+    }
+}
+
+impl fmt::Display for StreetCheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match self {
+            Self::Unmatched => "No segment",
+            Self::Name => "Segment name",
+            Self::Range => "Segment range",
+            Self::Side => "Street side",
+        };
+        write!(f, "{}", msg)
+    }
+}
+
+/// The `Centerlines` struct holds the street segments of the centerline layer.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Centerlines {
+    pub records: Vec<Segment>,
+}
+
+impl Centerlines {
+    /// Reads centerlines from a shapefile or GeoJSON file, depending on the extension of `path`.
+    pub fn read<P: AsRef<Path>>(path: P, fields: &CenterlineFields) -> Clean<Self> {
+        let extension = path
+            .as_ref()
+            .extension()
+            .map(|v| v.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("shp") => Self::from_shp(path, fields),
+            Some("geojson") | Some("json") => Self::from_geojson(path, fields),
+            _ => Err(Bandage::Hint(
+                "Centerlines must be a shapefile or GeoJSON.".to_string(),
+            )),
+        }
+    }
+
+    /// Reads centerlines from a polyline shapefile.  Coordinates are expected in web mercator, as
+    /// with the boundary shapefiles.
+    pub fn from_shp<P: AsRef<Path>>(path: P, fields: &CenterlineFields) -> Clean<Self> {
+        let lines = shapefile::read_as::<_, shapefile::Polyline, shapefile::dbase::Record>(path)?;
+        let mut records = Vec::new();
+        for (line, record) in lines {
+            let name = match record.get(&fields.name) {
+                Some(shapefile::dbase::FieldValue::Character(Some(value))) => value.clone(),
+                _ => continue,
+            };
+            let number = |field: &str| match record.get(field) {
+                Some(shapefile::dbase::FieldValue::Numeric(Some(value))) => *value as i64,
+                Some(shapefile::dbase::FieldValue::Integer(value)) => *value as i64,
+                Some(shapefile::dbase::FieldValue::Double(value)) => *value as i64,
+                Some(shapefile::dbase::FieldValue::Character(Some(value))) => {
+                    value.trim().parse::<i64>().unwrap_or_default()
+                }
+                _ => 0,
+            };
+            let left = (number(&fields.left_from), number(&fields.left_to));
+            let right = (number(&fields.right_from), number(&fields.right_to));
+            for part in line.parts() {
+                let points = part.iter().map(|v| (v.x, v.y)).collect::<Vec<(f64, f64)>>();
+                records.push(Segment {
+                    name: normalize_street(&name),
+                    left,
+                    right,
+                    points,
+                });
+            }
+        }
+        info!("Segments read: {}", records.len());
+        Ok(Self { records })
+    }
+
+    /// Reads centerlines from a GeoJSON file of line strings in WGS84, projecting them to web
+    /// mercator.
+    pub fn from_geojson<P: AsRef<Path>>(path: P, fields: &CenterlineFields) -> Clean<Self> {
+        let text = std::fs::read_to_string(path)?;
+        let collection = text
+            .parse::<geojson::FeatureCollection>()
+            .map_err(|e| Bandage::Hint(e.to_string()))?;
+        let mut records = Vec::new();
+        for feature in collection.features {
+            let name = match feature.property(&fields.name).and_then(|v| v.as_str()) {
+                Some(value) => value.to_string(),
+                None => continue,
+            };
+            let number = |field: &str| match feature.property(field) {
+                Some(value) => value
+                    .as_i64()
+                    .or_else(|| value.as_f64().map(|v| v as i64))
+                    .or_else(|| value.as_str().and_then(|v| v.trim().parse::<i64>().ok()))
+                    .unwrap_or_default(),
+                None => 0,
+            };
+            let left = (number(&fields.left_from), number(&fields.left_to));
+            let right = (number(&fields.right_from), number(&fields.right_to));
+            let parts = match feature.geometry.as_ref().map(|v| &v.value) {
+                Some(geojson::Value::LineString(line)) => vec![line.clone()],
+                Some(geojson::Value::MultiLineString(lines)) => lines.clone(),
+                _ => continue,
+            };
+            for part in parts {
+                let points = part
+                    .iter()
+                    .filter(|v| v.len() >= 2)
+                    .map(|v| web_mercator(v[1], v[0]))
+                    .collect::<Vec<(f64, f64)>>();
+                records.push(Segment {
+                    name: normalize_street(&name),
+                    left,
+                    right,
+                    points,
+                });
+            }
+        }
+        info!("Segments read: {}", records.len());
+        Ok(Self { records })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Clean<Self> {
+        let records = load_bin(path)?;
+        let decode: Self = bincode::deserialize(&records[..])?;
+        Ok(decode)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Clean<()> {
+        address::prelude::save(self, path)
+    }
+
+    /// Checks each address in `addresses` against the nearest segment within `search` meters.
+    /// Street names are compared through `streets`, so a segment named with an alias matches.
+    pub fn check(
+        &self,
+        addresses: &SpatialAddresses,
+        search: f64,
+        streets: &StreetLookup,
+    ) -> RuleIssues {
+        // Search radii are in ground meters, which web mercator stretches away from the equator,
+        // so the cells are sized for the address farthest from it.
+        let widest = addresses
+            .iter()
+            .map(|v| mercator_scale(v.latitude))
+            .fold(1.0, f64::max);
+        let index = SegmentIndex::new(self, search * widest);
+        let mut issues = Vec::new();
+        for address in addresses.iter() {
+            let point = web_mercator(address.latitude, address.longitude);
+            let scale = mercator_scale(address.latitude);
+            let Some((segment, d, side)) = index.nearest(self, point, search * scale) else {
+                let detail = format!("No street segment within {search:.0} m.");
+                issues.push(StreetCheck::Unmatched.issue(address, &detail));
+                continue;
+            };
+            let d = d / scale;
+            if streets.address(address) != streets.street(&segment.name) {
+                let detail = format!("Nearest segment, {d:.0} m away, is {}.", segment.name);
+                issues.push(StreetCheck::Name.issue(address, &detail));
+                // Range and side only make sense on the same street.
+                continue;
+            }
+            let number = address.number();
+            let (low, high) = segment.range(side);
+            if (low, high) == (0, 0) {
+                continue;
+            }
+            if number < low || number > high {
+                let detail = format!(
+                    "Number {number} is outside the {side} range {low}-{high} of {}.",
+                    segment.name
+                );
+                issues.push(StreetCheck::Range.issue(address, &detail));
+            } else if number % 2 != low % 2 {
+                let parity = |v: i64| if v % 2 == 0 { "even" } else { "odd" };
+                let detail = format!(
+                    "{} number {number} sits on the {} {side} side of {}.",
+                    parity(number),
+                    parity(low),
+                    segment.name
+                );
+                issues.push(StreetCheck::Side.issue(address, &detail));
+            }
+        }
+        trace!("Street issues found: {}", issues.len());
+        RuleIssues::from(issues)
+    }
+}
+
+/// The `SegmentIndex` struct buckets segments into square cells at least the width of the search
+/// distance, in web mercator units, so the nearest segment to a point is found by checking the
+/// neighboring cells.
+struct SegmentIndex {
+    size: f64,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+}
+
+impl SegmentIndex {
+    fn new(centerlines: &Centerlines, size: f64) -> Self {
+        let mut cells = HashMap::new();
+        for (i, segment) in centerlines.records.iter().enumerate() {
+            let (min, max) = segment.bounds();
+            let (x0, y0) = Self::cell(size, min);
+            let (x1, y1) = Self::cell(size, max);
+            for x in x0..=x1 {
+                for y in y0..=y1 {
+                    cells.entry((x, y)).or_insert(Vec::new()).push(i);
+                }
+            }
+        }
+        Self { size, cells }
+    }
+
+    fn cell(size: f64, point: (f64, f64)) -> (i64, i64) {
+        (
+            (point.0 / size).floor() as i64,
+            (point.1 / size).floor() as i64,
+        )
+    }
+
+    /// Returns the nearest segment within `radius` of `point`, with its distance and the side of
+    /// the segment the point lies on.  The radius must not exceed the cell size.
+    fn nearest<'a>(
+        &self,
+        centerlines: &'a Centerlines,
+        point: (f64, f64),
+        radius: f64,
+    ) -> Option<(&'a Segment, f64, Side)> {
+        let (cx, cy) = Self::cell(self.size, point);
+        let mut nearest: Option<(&Segment, f64, Side)> = None;
+        for x in (cx - 1)..=(cx + 1) {
+            for y in (cy - 1)..=(cy + 1) {
+                let Some(members) = self.cells.get(&(x, y)) else {
+                    continue;
+                };
+                for i in members {
+                    let segment = &centerlines.records[*i];
+                    if let Some((d, side)) = segment.locate(point) {
+                        if d <= radius && nearest.map(|v| d < v.1).unwrap_or(true) {
+                            nearest = Some((segment, d, side));
+                        }
+                    }
+                }
+            }
+        }
+        nearest
+    }
+}
+
+/// The `SegmentView` struct is the map feature of a [`Segment`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentView {
+    pub geometry: Contour<Point2d>,
+}
+
+impl From<&Segment> for SegmentView {
+    fn from(segment: &Segment) -> Self {
+        let points = segment
+            .points
+            .iter()
+            .map(|(x, y)| Point2d::new(*x, *y))
+            .collect::<Vec<Point2d>>();
+        Self {
+            geometry: Contour::open(points),
+        }
+    }
+}
+
+impl Feature for SegmentView {
+    type Geom = Contour<Point2d>;
+
+    fn geometry(&self) -> &Self::Geom {
+        &self.geometry
+    }
+}
+
+/// The `SegmentViews` struct holds the map features of the centerline layer.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SegmentViews {
+    pub records: Vec<SegmentView>,
+}
+
+impl From<&Centerlines> for SegmentViews {
+    fn from(centerlines: &Centerlines) -> Self {
+        let records = centerlines
+            .records
+            .iter()
+            .map(SegmentView::from)
+            .collect::<Vec<SegmentView>>();
+        Self { records }
+    }
+}
+
+pub struct CenterlineSymbol {}
+
+impl symbol::Symbol<SegmentView> for CenterlineSymbol {
+    fn render<'a, N, P>(
+        &self,
+        _feature: &SegmentView,
+        geometry: &'a Geom<P>,
+        min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N> + Clone,
+    {
+        symbol::SimpleContourSymbol::new(Color::from_hex("#5a5a5a"), 2.0).render(
+            &(),
+            geometry,
+            min_resolution,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::AddressPoint;
+
+    #[test]
+    fn locate_reports_side_and_distance() {
+        let segment = Segment {
+            points: vec![(0.0, 0.0), (10.0, 0.0)],
+            ..Default::default()
+        };
+        assert_eq!(segment.locate((5.0, 3.0)), Some((3.0, Side::Left)));
+        assert_eq!(segment.locate((5.0, -4.0)), Some((4.0, Side::Right)));
+        // Past the end, the distance is to the end point.
+        assert_eq!(segment.locate((13.0, 4.0)), Some((5.0, Side::Left)));
+    }
+
+    #[test]
+    fn check_compares_range_and_parity_by_side() {
+        let (latitude, longitude) = (42.44, -123.33);
+        let centerlines = Centerlines {
+            records: vec![Segment {
+                name: "MAIN".to_string(),
+                left: (199, 101),
+                right: (100, 198),
+                points: vec![
+                    web_mercator(latitude, longitude),
+                    web_mercator(latitude, longitude + 0.002),
+                ],
+            }],
+        };
+        let north = latitude + 0.0002;
+        let south = latitude - 0.0002;
+        let records = vec![
+            AddressPoint::sample(151, "MAIN", 97526, north, longitude + 0.001),
+            AddressPoint::sample(150, "MAIN", 97526, south, longitude + 0.001),
+            AddressPoint::sample(150, "MAIN", 97526, north, longitude + 0.001),
+            AddressPoint::sample(251, "MAIN", 97526, north, longitude + 0.001),
+            // About 90 m away on the ground, though farther in web mercator units.
+            AddressPoint::sample(153, "MAIN", 97526, latitude + 0.0008, longitude + 0.001),
+        ];
+        let issues = centerlines.check(
+            &SpatialAddresses::from(&records[..]),
+            100.0,
+            &StreetLookup::default(),
+        );
+        let rules = issues
+            .iter()
+            .map(|v| (v.address.clone(), v.rule.clone()))
+            .collect::<Vec<(String, String)>>();
+        assert_eq!(
+            rules,
+            vec![
+                (records[2].label(), StreetCheck::Side.to_string()),
+                (records[3].label(), StreetCheck::Range.to_string()),
+            ]
+        );
+    }
+}
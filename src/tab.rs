@@ -1,6 +1,9 @@
 use crate::controls::act;
+use crate::project::{Autosave, Project, RecentProjects, DEFAULT_PROJECT, PROJECT_EXTENSION};
 use crate::state::lens;
+use aid::prelude::Clean;
 use egui_dock::{NodeIndex, SurfaceIndex};
+use std::path::{Path, PathBuf};
 
 pub type Tab = lens::Lens;
 
@@ -72,22 +75,281 @@ impl egui_dock::TabViewer for TabViewer<'_> {
     // }
 }
 
+/// The `Closing` enum tracks the prompt shown when the window is closed with a project open.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Closing {
+    #[default]
+    Open,
+    /// Waiting for the user to choose whether to save.
+    Asking,
+    /// Save the project, then exit.
+    Save,
+    /// Exit without saving.
+    Discard,
+}
+
 pub struct TabState {
     tree: egui_dock::DockState<Tab>,
     tab_index: usize,
     notify: egui_notify::Toasts,
+    /// The `project` field holds the path of the open project file, if any.
+    project: Option<PathBuf>,
+    /// The `recent` field holds the paths of recently opened projects.
+    recent: RecentProjects,
+    /// The `recovery` field holds an autosaved session awaiting the choice to restore it.
+    recovery: Option<Project>,
+    /// The `closing` field tracks the save prompt shown on close.
+    closing: Closing,
 }
 
 impl TabState {
     pub fn new(lens: lens::Lens) -> Self {
         // Create a `DockState` with an initial tab "tab1" in the main `Surface`'s root node.
         let tree = egui_dock::DockState::new(vec![lens]);
-        let tab_index = 0;
+        Self::from_tree(tree)
+    }
+
+    fn from_tree(tree: egui_dock::DockState<Tab>) -> Self {
+        let tab_index = tree.iter_all_tabs().count().saturating_sub(1);
         let notify = egui_notify::Toasts::default();
         Self {
             tree,
             tab_index,
             notify,
+            project: None,
+            recent: RecentProjects::local(),
+            recovery: None,
+            closing: Closing::Open,
+        }
+    }
+
+    /// Offers to restore the autosaved session `project` on the next frame.
+    pub fn offer_recovery(&mut self, project: Project) {
+        self.recovery = Some(project);
+    }
+
+    /// Returns true while the restore prompt is open.
+    pub fn recovery_pending(&self) -> bool {
+        self.recovery.is_some()
+    }
+
+    /// Returns true while an operation in any tab is running in the background.
+    pub fn busy(&self) -> bool {
+        self.tree
+            .iter_all_tabs()
+            .any(|(_, tab)| tab.operations.busy())
+    }
+
+    /// Prompts the user to restore or discard an autosaved session.
+    pub fn recovery_prompt(&mut self, ctx: &egui::Context) {
+        let Some(project) = &self.recovery else {
+            return;
+        };
+        let saved = project
+            .saved
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string();
+        let mut restore = None;
+        egui::Window::new("Restore Session")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label("AMS did not close normally.");
+                ui.label(format!("Restore the session autosaved at {saved}?"));
+                ui.horizontal(|ui| {
+                    if ui.button("Restore").clicked() {
+                        restore = Some(true);
+                    }
+                    if ui.button("Discard").clicked() {
+                        restore = Some(false);
+                    }
+                });
+            });
+        match restore {
+            Some(true) => {
+                if let Some(project) = self.recovery.take() {
+                    self.tree = project.tabs;
+                    self.tab_index = self.tree.iter_all_tabs().count().saturating_sub(1);
+                    self.notify.success("Session restored.");
+                }
+            }
+            Some(false) => {
+                self.recovery = None;
+                Autosave::default().clear();
+            }
+            None => {}
+        }
+    }
+
+    /// Asks whether to save the open project before closing.  With no project open, the session
+    /// goes to [`DEFAULT_PROJECT`] without asking.
+    pub fn request_close(&mut self) {
+        self.closing = match self.project {
+            Some(_) => Closing::Asking,
+            None => Closing::Save,
+        };
+    }
+
+    /// The answer to the close prompt, if the user has given one.
+    pub fn closing(&self) -> Closing {
+        self.closing
+    }
+
+    /// Prompts the user to save the open project before closing.
+    pub fn close_prompt(&mut self, ctx: &egui::Context) {
+        if self.closing != Closing::Asking {
+            return;
+        }
+        let name = self
+            .project
+            .as_ref()
+            .and_then(|v| v.file_name())
+            .map(|v| v.to_string_lossy().to_string())
+            .unwrap_or_default();
+        egui::Window::new("Close AMS")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label(format!("Save changes to {name} before closing?"));
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        self.closing = Closing::Save;
+                    }
+                    if ui.button("Don't Save").clicked() {
+                        self.closing = Closing::Discard;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.closing = Closing::Open;
+                    }
+                });
+            });
+    }
+
+    /// Restores the tab layout from `project`, read from the file at `path`.
+    pub fn from_project<P: AsRef<Path>>(project: Project, path: P) -> Self {
+        let mut state = Self::from_tree(project.tabs);
+        state.project = Some(path.as_ref().to_path_buf());
+        state
+    }
+
+    /// The tab layout, for saving.
+    pub fn tabs(&self) -> &egui_dock::DockState<Tab> {
+        &self.tree
+    }
+
+    /// Path of the open project file, if any.
+    pub fn project_path(&self) -> Option<&PathBuf> {
+        self.project.as_ref()
+    }
+
+    /// Opens the project at `path`, replacing the current tabs.
+    pub fn open_project<P: AsRef<Path>>(&mut self, path: P) -> Clean<()> {
+        let project = Project::open(&path)?;
+        self.tree = project.tabs;
+        self.tab_index = self.tree.iter_all_tabs().count().saturating_sub(1);
+        self.project = Some(path.as_ref().to_path_buf());
+        self.recent.add(&path);
+        Ok(())
+    }
+
+    /// Saves the tabs to the project at `path`, which becomes the open project.
+    pub fn save_project<P: AsRef<Path>>(&mut self, path: P) -> Clean<()> {
+        Project::save_tabs(&self.tree, &path)?;
+        self.project = Some(path.as_ref().to_path_buf());
+        self.recent.add(&path);
+        Ok(())
+    }
+
+    /// Saves the tabs to the open project, or to [`DEFAULT_PROJECT`] if no project is open.
+    pub fn save(&mut self) -> Clean<()> {
+        let path = match &self.project {
+            Some(path) => path.clone(),
+            None => PathBuf::from(DEFAULT_PROJECT),
+        };
+        self.save_project(path)
+    }
+
+    fn project_dialog(&self) -> rfd::FileDialog {
+        let dialog = rfd::FileDialog::new().add_filter("AMS project", &[PROJECT_EXTENSION]);
+        match self.project.as_ref().and_then(|v| v.parent()) {
+            Some(dir) => dialog.set_directory(dir),
+            None => match std::env::current_dir() {
+                Ok(dir) => dialog.set_directory(dir),
+                Err(_) => dialog,
+            },
+        }
+    }
+
+    /// Reports the result of a project operation with a toast.
+    fn report(&mut self, result: Clean<()>, success: &str) {
+        match result {
+            Ok(_) => {
+                self.notify.success(success);
+            }
+            Err(e) => {
+                tracing::warn!("{}", e.to_string());
+                self.notify.error(e.to_string());
+            }
+        }
+    }
+
+    /// File menu with project commands.
+    pub fn menu(&mut self, ui: &mut egui::Ui) {
+        egui::menu::bar(ui, |ui| {
+            ui.menu_button("File", |ui| {
+                if ui.button("Open…").clicked() {
+                    ui.close_menu();
+                    if let Some(path) = self.project_dialog().pick_file() {
+                        let result = self.open_project(path);
+                        self.report(result, "Project opened.");
+                    }
+                }
+                ui.menu_button("Open Recent", |ui| {
+                    if self.recent.is_empty() {
+                        ui.label("No recent projects.");
+                    }
+                    let mut open = None;
+                    for path in self.recent.iter() {
+                        if ui.button(path.display().to_string()).clicked() {
+                            open = Some(path.clone());
+                        }
+                    }
+                    if let Some(path) = open {
+                        ui.close_menu();
+                        let result = self.open_project(path);
+                        self.report(result, "Project opened.");
+                    }
+                });
+                ui.separator();
+                if ui.button("Save").clicked() {
+                    ui.close_menu();
+                    let result = match self.project.clone() {
+                        Some(path) => self.save_project(path),
+                        None => self.save_as(),
+                    };
+                    self.report(result, "Project saved.");
+                }
+                if ui.button("Save As…").clicked() {
+                    ui.close_menu();
+                    let result = self.save_as();
+                    self.report(result, "Project saved.");
+                }
+            });
+        });
+    }
+
+    /// Prompts for a file name and saves the project.  Does nothing if the dialog is cancelled.
+    fn save_as(&mut self) -> Clean<()> {
+        let file = self
+            .project_dialog()
+            .set_file_name(format!("project.{PROJECT_EXTENSION}"))
+            .save_file();
+        match file {
+            Some(path) => self.save_project(path),
+            None => Ok(()),
         }
     }
 
@@ -119,9 +381,18 @@ impl TabState {
     }
 
     pub fn run_ui(&mut self, ctx: &egui::Context) {
+        egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            self.menu(ui);
+        });
+        self.recovery_prompt(ctx);
+        self.close_prompt(ctx);
         egui::SidePanel::left("Menu").show(ctx, |ui| {
             self.ui(ui);
         });
+        // The map fills the space left by the panels, so overlays anchor to it from here.
+        if let Some(tab) = self.tab() {
+            tab.search_overlay(ctx);
+        }
     }
 
     pub fn act(&mut self, act: &act::EguiAct) {
@@ -141,14 +412,6 @@ impl TabState {
 
 impl Default for TabState {
     fn default() -> Self {
-        let lens = lens::Lens::new();
-        let tree = egui_dock::DockState::new(vec![lens]);
-        let tab_index = 0;
-        let notify = egui_notify::Toasts::default();
-        Self {
-            tree,
-            tab_index,
-            notify,
-        }
+        Self::new(lens::Lens::new())
     }
 }
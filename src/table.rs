@@ -1,12 +1,19 @@
 use crate::controls::focus;
+use crate::query::TableQuery;
 use egui::{Align, Layout, Sense, Slider, Ui};
 use egui_extras::{Column, TableBuilder};
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::marker::PhantomData;
+use strum::IntoEnumIterator;
 use uuid::Uuid;
 
-#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+/// The `TableView` struct displays a [`Tabular`] source as a table.  Only the source data and the
+/// [`TableConfig`] are saved with a project; the view, selection, focus and edits are rebuilt on
+/// load, so changes to them leave the project layout alone.
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct TableView<T: Tabular<U> + Filtration<T, V> + Clone + Default, U: Columnar, V: Default> {
     /// Data source for the table.
     pub data: T,
@@ -24,10 +31,6 @@ pub struct TableView<T: Tabular<U> + Filtration<T, V> + Clone + Default, U: Colu
     pub selection: HashSet<Uuid>,
     /// Tracks checked boxes for rows using `row_ids`.
     pub checks: HashMap<Uuid, bool>,
-    /// Tracks ordering button state in headings.
-    pub ord_flags: Vec<bool>,
-    /// Set to index of ord flags to refresh ordering of rows.
-    pub set_ord: Option<usize>,
     /// Holds filter selection for the filter widget.
     pub filter: Option<V>,
     /// Row target for the slider widget.
@@ -36,6 +39,19 @@ pub struct TableView<T: Tabular<U> + Filtration<T, V> + Clone + Default, U: Colu
     pub row_select: Option<Uuid>,
     /// The `row_focus` field signals a change in row focus.
     pub row_focus: Option<Uuid>,
+    /// The cell currently open for editing, if any.
+    pub edit: Option<CellEdit>,
+    /// Edits committed by the user, awaiting validation by the owner of the table.
+    pub edits: Vec<CellEdit>,
+    /// The `query_error` field holds the reason the search could not be read as a query, if any.
+    pub query_error: Option<String>,
+    // The search compiled as a query, if it uses the query syntax.
+    query: Option<TableQuery>,
+    // The search text last compiled into `query`.
+    query_text: String,
+    // The row count and first row id of `view` when last sorted, to sort again after the view
+    // is replaced.
+    sorted: Option<(usize, Option<Uuid>)>,
     // Current index associated with the id in `row_select`.
     row_index: Option<usize>,
     // The uuid associated with each row.
@@ -48,6 +64,32 @@ pub struct TableView<T: Tabular<U> + Filtration<T, V> + Clone + Default, U: Colu
     phantom: PhantomData<U>,
 }
 
+impl<T, U, V> Serialize for TableView<T, U, V>
+where
+    T: Tabular<U> + Filtration<T, V> + Clone + Default + Serialize,
+    U: Columnar,
+    V: Default,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (&self.data, &self.config).serialize(serializer)
+    }
+}
+
+impl<'de, T, U, V> Deserialize<'de> for TableView<T, U, V>
+where
+    T: Tabular<U> + Filtration<T, V> + Clone + Default + Deserialize<'de>,
+    U: Columnar + Default,
+    V: Default,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if crate::project::layout() == 1 {
+            return Ok(crate::project::v1::TableView::<T>::deserialize(deserializer)?.into());
+        }
+        let (data, config) = <(T, TableConfig)>::deserialize(deserializer)?;
+        Ok(Self::with_config(data, config))
+    }
+}
+
 impl<T: Tabular<U> + Default + Filtration<T, V> + Clone, U: Columnar + Default, V: Default>
     TableView<T, U, V>
 {
@@ -58,13 +100,10 @@ impl<T: Tabular<U> + Default + Filtration<T, V> + Clone, U: Columnar + Default,
         let view = data.clone();
         // Each time we create a new view, package a clone for the GIS.
         let package = Some(data.clone());
-        let cols = T::headers().len();
-        let ord_flags = vec![false; cols];
         Self {
             data,
             view,
             package,
-            ord_flags,
             ..Default::default()
         }
     }
@@ -95,17 +134,11 @@ impl<T: Tabular<U> + Default + Filtration<T, V> + Clone, U: Columnar + Default,
     pub fn with_config(data: T, config: TableConfig) -> Self {
         let view = data.clone();
         let package = Some(data.clone());
-        let mut cols = T::headers().len();
-        if config.checked {
-            cols += 1;
-        }
-        let ord_flags = vec![false; cols];
         Self {
             data,
             view,
             package,
             config,
-            ord_flags,
             ..Default::default()
         }
     }
@@ -125,8 +158,12 @@ impl<T: Tabular<U> + Default + Filtration<T, V> + Clone, U: Columnar + Default,
     pub fn search_panel(&mut self, ui: &mut Ui) {
         if self.config.search {
             ui.horizontal(|ui| {
-                let entry =
-                    ui.add(egui::TextEdit::singleline(&mut self.search).hint_text("Search"));
+                let entry = ui
+                    .add(egui::TextEdit::singleline(&mut self.search).hint_text("Search"))
+                    .on_hover_text(
+                        "Filter a column with column:value, column:~regex or column:low..high.  \
+                         Combine terms with AND, OR, NOT and parentheses.",
+                    );
                 let clear = ui.button("X");
                 if clear.clicked() {
                     self.search = Default::default();
@@ -140,6 +177,9 @@ impl<T: Tabular<U> + Default + Filtration<T, V> + Clone, U: Columnar + Default,
                     self.tree.with_leaf(node_id, clear.id);
                 }
             });
+            if let Some(error) = &self.query_error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
         }
     }
 
@@ -211,18 +251,62 @@ impl<T: Tabular<U> + Default + Filtration<T, V> + Clone, U: Columnar + Default,
         self.leaves = leaves;
     }
 
+    /// Adds the column at `column` to the sort order.  A click sorts by the column alone, or
+    /// reverses it if it is already the only key.  With `shift` held, the column is added as the
+    /// next key, or reversed if it is already a key.
+    pub fn sort_click(&mut self, column: usize, shift: bool) {
+        let sort = &mut self.config.sort;
+        match sort.iter().position(|v| v.column == column) {
+            Some(index) if shift || sort.len() == 1 => {
+                sort[index].descending = !sort[index].descending;
+            }
+            _ if shift => sort.push(SortKey::new(column)),
+            _ => *sort = vec![SortKey::new(column)],
+        }
+        tracing::info!("Sort order set to {:?}.", sort);
+        self.sorted = None;
+    }
+
+    /// Sorts the view by the sort order in `config`, if the view has changed since last sorted.
+    fn sort_view(&mut self, rows: &mut Vec<U>) {
+        if self.config.sort.is_empty() {
+            return;
+        }
+        let marker = (rows.len(), rows.first().map(|v| v.id()));
+        if self.sorted == Some(marker) {
+            return;
+        }
+        let keys = self.config.sort.clone();
+        self.view.sort_by_cols(&keys);
+        *rows = self.view.rows();
+        self.sorted = Some((rows.len(), rows.first().map(|v| v.id())));
+    }
+
+    /// Returns the label of the sort button for `column`, with the rank of the key when sorting
+    /// by more than one column.
+    fn sort_symbol(&self, column: usize) -> String {
+        let sort = &self.config.sort;
+        match sort.iter().position(|v| v.column == column) {
+            Some(index) => {
+                let arrow = if sort[index].descending { "⏷" } else { "⏶" };
+                if sort.len() > 1 {
+                    format!("{arrow}{}", index + 1)
+                } else {
+                    arrow.to_string()
+                }
+            }
+            None => "↕".to_string(),
+        }
+    }
+
     /// UI display for the table view.
     pub fn table(&mut self, ui: &mut Ui) {
         // Each row contains a string value for each column in the table.
         let mut rows = self.view.rows();
+        self.sort_view(&mut rows);
         if !self.search.is_empty() {
-            // the subset of rows containing the search term in any column
-            rows = self.contains(&self.search);
-        }
-        if let Some(column) = self.set_ord.take() {
-            tracing::info!("Column ordering requested for {}", column);
-            let flag = self.ord_flags[column];
-            self.view_mut().sort_by_col(column, flag);
+            // the subset of rows matching the search
+            rows = self.search_rows();
         }
         // Collect the ids of each row.
         self.row_ids = rows.iter().map(|v| v.id().clone()).collect::<Vec<Uuid>>();
@@ -275,25 +359,25 @@ impl<T: Tabular<U> + Default + Filtration<T, V> + Clone, U: Columnar + Default,
                     .map(|(i, v)| {
                         header.col(|ui| {
                             ui.horizontal(|ui| {
-                                ui.strong(v);
-                                let flag = if self.config.checked && i > 0 {
-                                    i - 1
-                                } else {
-                                    i
-                                };
-                                let symbol = match self.ord_flags[flag] {
-                                    true => "⏷",
-                                    false => "⏶",
-                                };
-                                if ui.button(symbol).clicked {
-                                    if self.config.checked && i > 0 {
-                                        self.set_ord = Some(i - 1);
-                                        self.ord_flags[i - 1] = !self.ord_flags[i - 1];
-                                    } else {
-                                        self.set_ord = Some(i);
-                                        self.ord_flags[i] = !self.ord_flags[i];
+                                let heading = ui.strong(v);
+                                // Summarize numeric columns on hover.
+                                if heading.hovered() && !(self.config.checked && i == 0) {
+                                    let column = if self.config.checked { i - 1 } else { i };
+                                    if let Some(stats) = Stats::from_rows(&rows, column) {
+                                        heading.on_hover_text(stats.to_string());
                                     }
-                                    tracing::info!("Ord flags set.");
+                                }
+                                // The check box column has no sort button.
+                                if self.config.checked && i == 0 {
+                                    return;
+                                }
+                                let column = if self.config.checked { i - 1 } else { i };
+                                let button = ui
+                                    .button(self.sort_symbol(column))
+                                    .on_hover_text("Click to sort, shift-click to add a sort key.");
+                                if button.clicked() {
+                                    let shift = ui.input(|input| input.modifiers.shift);
+                                    self.sort_click(column, shift);
                                 };
                             });
                         })
@@ -306,7 +390,7 @@ impl<T: Tabular<U> + Default + Filtration<T, V> + Clone, U: Columnar + Default,
                     let row_data = &rows[row_index];
                     let row_id = row_data.id();
                     row.set_selected(self.selection.contains(&row_id));
-                    let columns = row_data.values();
+                    let columns = row_data.cells();
 
                     if self.config.checked {
                         if !self.checks.contains_key(&row_id) {
@@ -327,9 +411,46 @@ impl<T: Tabular<U> + Default + Filtration<T, V> + Clone, U: Columnar + Default,
 
                     columns
                         .iter()
-                        .map(|v| {
+                        .enumerate()
+                        .map(|(i, cell)| {
+                            let v = cell.to_string();
                             row.col(|ui| {
-                                ui.label(v);
+                                if !(self.config.editable && T::editable(i)) {
+                                    if cell.is_numeric() {
+                                        // Align numbers on the right, so the digits line up.
+                                        ui.with_layout(
+                                            Layout::right_to_left(Align::Center),
+                                            |ui| {
+                                                ui.label(&v);
+                                            },
+                                        );
+                                    } else {
+                                        ui.label(&v);
+                                    }
+                                    return;
+                                }
+                                match &mut self.edit {
+                                    Some(edit) if edit.row == row_id && edit.column == i => {
+                                        let entry = ui.text_edit_singleline(&mut edit.value);
+                                        if entry.lost_focus() {
+                                            // Enter commits the edit, any other way out cancels it.
+                                            if ui.input(|input| input.key_pressed(egui::Key::Enter))
+                                            {
+                                                self.edits.push(edit.clone());
+                                            }
+                                            self.edit = None;
+                                        } else if !entry.has_focus() {
+                                            entry.request_focus();
+                                        }
+                                    }
+                                    _ => {
+                                        // Double click a cell to edit it.
+                                        let label = egui::Label::new(&v).sense(Sense::click());
+                                        if ui.add(label).double_clicked() {
+                                            self.edit = Some(CellEdit::new(row_id, i, &v));
+                                        }
+                                    }
+                                }
                             });
                         })
                         .for_each(drop);
@@ -338,6 +459,39 @@ impl<T: Tabular<U> + Default + Filtration<T, V> + Clone, U: Columnar + Default,
             });
     }
 
+    /// Returns the rows of the view matching the search.  A search using the query syntax of
+    /// [`TableQuery`] filters by column, otherwise rows containing the search in any column match.
+    /// A query that does not parse falls back to the plain search, with the error shown below the
+    /// search box.
+    pub fn search_rows(&mut self) -> Vec<U> {
+        if !TableQuery::is_query(&self.search) {
+            self.query_error = None;
+            return self.contains(&self.search);
+        }
+        if self.query_text != self.search {
+            self.query_text = self.search.clone();
+            match TableQuery::parse(&self.search, &T::headers(), self.config.case_sensitive) {
+                Ok(query) => {
+                    self.query = Some(query);
+                    self.query_error = None;
+                }
+                Err(e) => {
+                    self.query = None;
+                    self.query_error = Some(e.to_string());
+                }
+            }
+        }
+        match &self.query {
+            Some(query) => self
+                .view
+                .rows()
+                .into_iter()
+                .filter(|v| query.matches(&v.cells(), self.config.case_sensitive))
+                .collect::<Vec<U>>(),
+            None => self.contains(&self.search),
+        }
+    }
+
     pub fn contains(&self, fragment: &str) -> Vec<U> {
         let mut data = Vec::new();
         let rows = self.view.rows();
@@ -362,6 +516,21 @@ impl<T: Tabular<U> + Default + Filtration<T, V> + Clone, U: Columnar + Default,
         data
     }
 
+    /// Restores the search, filter, sort and checks of `other`, a saved copy of this table over
+    /// the same data, as when undoing a change.  The selection and focus are left as they are.
+    pub fn restore(&mut self, other: Self) {
+        self.view = other.view;
+        self.search = other.search;
+        self.filter = other.filter;
+        self.config.sort = other.config.sort;
+        self.checks = other.checks;
+        self.query = other.query;
+        self.query_text = other.query_text;
+        self.query_error = other.query_error;
+        self.sorted = other.sorted;
+        self.row_ids = other.row_ids;
+    }
+
     /// Returns the [`Uuid`] of the current row in focus.
     pub fn current_row(&self) -> Option<Uuid> {
         self.row_select
@@ -436,6 +605,30 @@ pub struct TableConfig {
     pub search: bool,
     pub slider: bool,
     pub striped: bool,
+    /// The `editable` field enables editing of the columns marked editable by [`Tabular::editable`].
+    pub editable: bool,
+    /// The `sort` field holds the sort keys of the table, primary key first.
+    pub sort: Vec<SortKey>,
+}
+
+/// The `SortKey` struct sorts a table by the column at index `column`, not counting the check box
+/// column.
+#[derive(
+    Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize,
+)]
+pub struct SortKey {
+    pub column: usize,
+    pub descending: bool,
+}
+
+impl SortKey {
+    /// Sorts by `column` in ascending order.
+    pub fn new(column: usize) -> Self {
+        Self {
+            column,
+            descending: false,
+        }
+    }
 }
 
 impl TableConfig {
@@ -472,20 +665,82 @@ impl TableConfig {
         self.case_sensitive = true;
         self
     }
+
+    pub fn editable(mut self) -> Self {
+        self.editable = true;
+        self
+    }
+}
+
+/// The `CellEdit` struct holds a new value entered by the user for a single cell.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct CellEdit {
+    /// The id of the edited row.
+    pub row: Uuid,
+    /// The index of the edited column, not counting the check box column.
+    pub column: usize,
+    pub value: String,
+}
+
+impl CellEdit {
+    pub fn new(row: Uuid, column: usize, value: &str) -> Self {
+        Self {
+            row,
+            column,
+            value: value.to_string(),
+        }
+    }
 }
 
 pub trait Tabular<T: Columnar> {
     // Required
     fn headers() -> Vec<String>;
     fn rows(&self) -> Vec<T>;
+    /// Replaces the rows with `rows`, e.g. after sorting.
+    fn set_rows(&mut self, rows: Vec<T>);
 
     // Optional
+
+    /// Sorts the rows by the typed cells in the column at `column_index`, in descending order if
+    /// `reverse` is true.
     fn sort_by_col(&mut self, column_index: usize, reverse: bool) {
-        tracing::info!(
-            "Sort by column not implemented for column_index {} and reverse {}.",
-            column_index,
-            reverse
-        );
+        let key = SortKey {
+            column: column_index,
+            descending: reverse,
+        };
+        self.sort_by_cols(&[key]);
+    }
+
+    /// Sorts the rows by each of `keys` in turn, comparing by later keys where earlier keys are
+    /// equal.  The sort is stable, so rows equal in every key keep their order.
+    fn sort_by_cols(&mut self, keys: &[SortKey]) {
+        let mut keyed = self
+            .rows()
+            .into_iter()
+            .map(|v| (v.cells(), v))
+            .collect::<Vec<(Vec<Cell>, T)>>();
+        let empty = Cell::Empty;
+        keyed.sort_by(|a, b| {
+            keys.iter()
+                .map(|key| {
+                    let x = a.0.get(key.column).unwrap_or(&empty);
+                    let y = b.0.get(key.column).unwrap_or(&empty);
+                    let order = x.compare(y);
+                    if key.descending {
+                        order.reverse()
+                    } else {
+                        order
+                    }
+                })
+                .find(|v| v.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+        self.set_rows(keyed.into_iter().map(|(_, v)| v).collect());
+    }
+
+    /// Returns true if the user may edit values in the column at `column_index`.
+    fn editable(_column_index: usize) -> bool {
+        false
     }
 
     // Provided
@@ -507,12 +762,159 @@ pub trait Tabular<T: Columnar> {
 //     }
 // }
 
+/// Implementors provide the typed [`Cell`] of each column through `cells`, and receive the
+/// display string of each column through `values`.  Tables of plain strings return
+/// [`Cell::Text`] cells.
 pub trait Columnar {
-    fn values(&self) -> Vec<String>;
+    // Required
+    fn cells(&self) -> Vec<Cell>;
+
+    // Provided
+    fn values(&self) -> Vec<String> {
+        self.cells().iter().map(|v| v.to_string()).collect()
+    }
+
     // Uuid is copy, so no need to pass a reference
     fn id(&self) -> Uuid;
 }
 
+/// The `Cell` enum is the typed value of a table cell, used to sort, filter, align and summarize
+/// columns by their values rather than their display strings.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub enum Cell {
+    Integer(i64),
+    /// A decimal number, displayed with the given number of decimal places.
+    Float(f64, usize),
+    /// A latitude or longitude in decimal degrees.
+    Coordinate(f64),
+    Text(String),
+    /// A variant of an enumeration, displayed by name and sorted by its place in the enumeration.
+    Enum(String, usize),
+    /// An optional value that is missing.  Sorts after every other value.
+    #[default]
+    Empty,
+}
+
+impl Cell {
+    /// Converts an optional value, with `None` as [`Cell::Empty`].
+    pub fn optional<T: Into<Cell>>(value: Option<T>) -> Self {
+        value.map_or(Self::Empty, Into::into)
+    }
+
+    /// Creates an [`Cell::Enum`] from `value`, ranked by its place in the enumeration.
+    pub fn variant<T: IntoEnumIterator + PartialEq + fmt::Display>(value: &T) -> Self {
+        let rank = T::iter().position(|v| v == *value).unwrap_or_default();
+        Self::Enum(value.to_string(), rank)
+    }
+
+    /// Returns the value of a numeric cell.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Integer(value) => Some(*value as f64),
+            Self::Float(value, _) | Self::Coordinate(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        self.as_f64().is_some()
+    }
+
+    /// Orders cells by value.  Numbers compare numerically and variants by rank, while other
+    /// cells compare by their display strings.  Empty cells come last.
+    pub fn compare(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Empty, Self::Empty) => Ordering::Equal,
+            (Self::Empty, _) => Ordering::Greater,
+            (_, Self::Empty) => Ordering::Less,
+            (Self::Enum(_, a), Self::Enum(_, b)) => a.cmp(b),
+            _ => match (self.as_f64(), other.as_f64()) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                _ => self.to_string().cmp(&other.to_string()),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Integer(value) => write!(f, "{value}"),
+            Self::Float(value, places) => write!(f, "{value:.places$}"),
+            Self::Coordinate(value) => write!(f, "{value:.6}"),
+            Self::Text(value) | Self::Enum(value, _) => write!(f, "{value}"),
+            Self::Empty => Ok(()),
+        }
+    }
+}
+
+impl From<i64> for Cell {
+    fn from(value: i64) -> Self {
+        Self::Integer(value)
+    }
+}
+
+impl From<usize> for Cell {
+    fn from(value: usize) -> Self {
+        Self::Integer(value as i64)
+    }
+}
+
+impl From<String> for Cell {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_string())
+    }
+}
+
+/// The `Stats` struct summarizes the numeric cells of a column.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Stats {
+    /// The `count` field holds the number of numeric cells.
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub sum: f64,
+    pub mean: f64,
+}
+
+impl Stats {
+    /// Summarizes the numeric cells in the column at `column` of `rows`, or returns `None` if the
+    /// column holds no numbers.
+    pub fn from_rows<U: Columnar>(rows: &[U], column: usize) -> Option<Self> {
+        let values = rows
+            .iter()
+            .filter_map(|v| v.cells().get(column).and_then(Cell::as_f64))
+            .collect::<Vec<f64>>();
+        if values.is_empty() {
+            return None;
+        }
+        let sum = values.iter().sum::<f64>();
+        Some(Self {
+            count: values.len(),
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            sum,
+            mean: sum / values.len() as f64,
+        })
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Count: {}\nMin: {}\nMax: {}\nSum: {}\nMean: {:.2}",
+            self.count, self.min, self.max, self.sum, self.mean
+        )
+    }
+}
+
 // impl Columnar for BeaDatum {
 //     fn names() -> Vec<String> {
 //         Self::names()
@@ -530,3 +932,98 @@ pub trait Filtration<T: Default, U> {
         T::default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    struct Row {
+        id: Uuid,
+        cells: Vec<Cell>,
+    }
+
+    impl Columnar for Row {
+        fn cells(&self) -> Vec<Cell> {
+            self.cells.clone()
+        }
+
+        fn id(&self) -> Uuid {
+            self.id
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct Rows(Vec<Row>);
+
+    impl Tabular<Row> for Rows {
+        fn headers() -> Vec<String> {
+            vec!["Group".to_string(), "Value".to_string()]
+        }
+
+        fn rows(&self) -> Vec<Row> {
+            self.0.clone()
+        }
+
+        fn set_rows(&mut self, rows: Vec<Row>) {
+            self.0 = rows;
+        }
+    }
+
+    #[test]
+    fn compare_orders_by_value() {
+        use Cell::*;
+        assert_eq!(Integer(9).compare(&Integer(10)), Ordering::Less);
+        assert_eq!(Float(2.5, 1).compare(&Integer(2)), Ordering::Greater);
+        assert_eq!(
+            Text("9".into()).compare(&Text("10".into())),
+            Ordering::Greater
+        );
+        // Variants sort by rank, not by name.
+        assert_eq!(
+            Enum("Retired".into(), 0).compare(&Enum("Current".into(), 1)),
+            Ordering::Less
+        );
+        assert_eq!(Empty.compare(&Integer(i64::MAX)), Ordering::Greater);
+        assert_eq!(Text("".into()).compare(&Empty), Ordering::Less);
+        assert_eq!(Empty.compare(&Empty), Ordering::Equal);
+    }
+
+    #[test]
+    fn sort_by_cols_is_stable() {
+        let rows = [(2, 0), (1, 1), (2, 2), (1, 3), (2, 4)]
+            .iter()
+            .map(|(group, order)| Row {
+                id: Uuid::from_u64_pair(0, *order),
+                cells: vec![Cell::Integer(*group), Cell::Empty],
+            })
+            .collect::<Vec<Row>>();
+        let mut table = Rows(rows);
+        let order = |table: &Rows| {
+            table
+                .0
+                .iter()
+                .map(|v| v.id.as_u64_pair().1)
+                .collect::<Vec<u64>>()
+        };
+
+        table.sort_by_col(0, false);
+        assert_eq!(order(&table), vec![1, 3, 0, 2, 4]);
+        // Equal rows keep their order in either direction.
+        table.sort_by_col(0, true);
+        assert_eq!(order(&table), vec![0, 2, 4, 1, 3]);
+        // A later key breaks ties, and an empty key leaves them as they were.
+        let keys = [
+            SortKey {
+                column: 1,
+                descending: false,
+            },
+            SortKey {
+                column: 0,
+                descending: false,
+            },
+        ];
+        table.sort_by_cols(&keys);
+        assert_eq!(order(&table), vec![1, 3, 0, 2, 4]);
+    }
+}
@@ -0,0 +1,648 @@
+//! The `undo` module keeps an undoable history of changes to a tab.  Rather than recording an
+//! inverse for every command, each [`Revision`] holds a [`Snapshot`] of the undoable state taken
+//! before the change.  A cheap [`Marker`] of that state is compared each frame to detect changes,
+//! so table filters, sorts, searches and checks, dataset edits and operation results are all
+//! covered without instrumenting every widget.  Snapshots copy only the state the marker covers,
+//! and share the records of loaded datasets rather than copying them.  Restoring one merges it
+//! into the live state, so settings outside the marker are never reverted.
+use crate::catalog::Catalog;
+use crate::changes::ChangeSet;
+use crate::lifecycle::Lifecycle;
+use crate::master::{StreetMaster, StreetName};
+use crate::matching::{CompareRecord, CompareRecords};
+use crate::ops::Operations;
+use crate::prelude::{
+    AddressPoint, AddressPoints, Columnar, Filtration, SortKey, TableView, Tabular,
+};
+use crate::presence::{
+    Combination, Presence, PresenceFilter, PresenceMatrix, PresenceSummary, Source,
+};
+use crate::qa::{Issue, Issues};
+use crate::rules::{RuleIssue, RuleIssues};
+use address::prelude::{LexisNexis, LexisNexisItem, SpatialAddresses};
+use chrono::{DateTime, Local, Utc};
+use std::fmt::Debug;
+
+/// Maximum number of revisions kept in the undo stack.
+pub const HISTORY_LIMIT: usize = 50;
+/// Repeated changes of the same kind within this many seconds merge into one revision, so typing
+/// in a search box undoes as a single step.
+pub const MERGE_SECONDS: i64 = 2;
+/// Label of revisions that edit address records in the editor.
+pub const EDIT_LABEL: &str = "Edit addresses";
+
+/// The `TableMarker` struct summarizes the user-controlled state of a [`TableView`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TableMarker {
+    pub search: String,
+    pub filter: String,
+    pub sort: Vec<SortKey>,
+    pub checks: Vec<(uuid::Uuid, bool)>,
+    /// The `rows` field holds the number of rows and the id of the first row in the view, which
+    /// change when an operation produces new results.
+    pub rows: (usize, Option<uuid::Uuid>),
+}
+
+impl TableMarker {
+    pub fn new<T, U, V>(table: &Option<TableView<T, U, V>>) -> Option<Self>
+    where
+        T: Tabular<U> + Filtration<T, V> + Clone + Default,
+        U: Columnar + Default,
+        V: Default + Debug,
+    {
+        let table = table.as_ref()?;
+        let ids = table.row_ids();
+        let mut checks = table
+            .checks
+            .iter()
+            .filter(|(_, v)| **v)
+            .map(|(k, v)| (*k, *v))
+            .collect::<Vec<(uuid::Uuid, bool)>>();
+        checks.sort();
+        Some(Self {
+            search: table.search.clone(),
+            filter: format!("{:?}", table.filter),
+            sort: table.config.sort.clone(),
+            checks,
+            rows: (ids.len(), ids.first().copied()),
+        })
+    }
+
+    /// Describes the change from `self` to `other`.
+    pub fn change(&self, other: &Self) -> &'static str {
+        if self.filter != other.filter {
+            "Filter"
+        } else if self.search != other.search {
+            "Search"
+        } else if self.sort != other.sort {
+            "Sort"
+        } else if self.rows != other.rows {
+            "Run"
+        } else if self.checks != other.checks {
+            "Check"
+        } else {
+            "Change"
+        }
+    }
+}
+
+/// The `Marker` struct summarizes the undoable state of a tab, and is cheap to compare each frame.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Marker {
+    /// Markers for each tracked table, with the name of the table.
+    pub tables: Vec<(&'static str, Option<TableMarker>)>,
+    /// The id, label and record count of each loaded dataset.
+    pub datasets: Vec<(uuid::Uuid, String, usize)>,
+    /// The number of attribute edits and lifecycle changes in the editor.  Snapshots keep the
+    /// change set rather than the editor table, so edits are tracked by count.
+    pub edits: usize,
+    /// The number of changes to the street master list.
+    pub streets: usize,
+}
+
+impl Marker {
+    pub fn new(snapshot: &SnapshotRef) -> Self {
+        let ops = snapshot.operations;
+        let tables = vec![
+            ("Addresses", TableMarker::new(snapshot.address_table)),
+            ("Compare", TableMarker::new(&ops.compare.table)),
+            ("Matrix", TableMarker::new(&ops.matrix.table)),
+            ("Range Export", TableMarker::new(&ops.lexis.view)),
+            ("Range QA", TableMarker::new(&ops.lexis.qa)),
+            ("Validation", TableMarker::new(&ops.validation.table)),
+            ("Spatial QA", TableMarker::new(&ops.spatial.table)),
+            ("Street QA", TableMarker::new(&ops.streets.table)),
+            ("Street Master", TableMarker::new(&ops.master.table)),
+            ("Unlisted Streets", TableMarker::new(&ops.master.report)),
+        ];
+        let datasets = snapshot
+            .catalog
+            .iter()
+            .map(|v| (v.id, v.label.clone(), v.records))
+            .collect::<Vec<(uuid::Uuid, String, usize)>>();
+        Self {
+            tables,
+            datasets,
+            edits: ops.editor.changes.len() + ops.editor.lifecycle.revision,
+            streets: ops.master.revision,
+        }
+    }
+
+    /// Describes the change from `self` to `other`, e.g. "Filter Compare".
+    pub fn change(&self, other: &Self) -> String {
+        if self.edits != other.edits {
+            return EDIT_LABEL.to_string();
+        }
+        if self.streets != other.streets {
+            return "Edit street master".to_string();
+        }
+        for ((name, a), (_, b)) in self.tables.iter().zip(other.tables.iter()) {
+            match (a, b) {
+                (Some(a), Some(b)) if a != b => return format!("{} {}", a.change(b), name),
+                (None, Some(_)) => return format!("Run {name}"),
+                (Some(_), None) => return format!("Clear {name}"),
+                _ => {}
+            }
+        }
+        if self.datasets.len() < other.datasets.len() {
+            "Load dataset".to_string()
+        } else if self.datasets.len() > other.datasets.len() {
+            "Remove dataset".to_string()
+        } else {
+            "Edit dataset".to_string()
+        }
+    }
+}
+
+/// The `SnapshotRef` struct borrows the undoable state of a tab.
+pub struct SnapshotRef<'a> {
+    pub address_table: &'a Option<TableView<AddressPoints, AddressPoint, String>>,
+    pub operations: &'a Operations,
+    pub catalog: &'a Catalog,
+}
+
+/// The `Snapshot` struct holds a copy of the state covered by the [`Marker`]: the tracked tables
+/// with the results shown beside them, the loaded datasets, the editor change set and lifecycle,
+/// and the street master list.  Restoring a snapshot merges it into the live state, so settings
+/// the marker does not cover, such as check options and rule toggles, are left as they are.
+#[derive(Debug, Default, Clone)]
+pub struct Snapshot {
+    pub address_table: Option<TableView<AddressPoints, AddressPoint, String>>,
+    pub compare: Option<TableView<CompareRecords, CompareRecord, String>>,
+    pub matrix: Option<TableView<PresenceMatrix, Presence, PresenceFilter>>,
+    pub matrix_summary: Option<TableView<PresenceSummary, Combination, String>>,
+    pub matrix_sources: Vec<Source>,
+    pub ranges: Option<TableView<LexisNexis, LexisNexisItem, String>>,
+    /// The addresses included in and excluded from the ranges, which the range table is built from.
+    pub range_split: (SpatialAddresses, SpatialAddresses),
+    pub range_qa: Option<TableView<Issues, Issue, String>>,
+    pub validation: Option<TableView<RuleIssues, RuleIssue, String>>,
+    pub spatial: Option<TableView<RuleIssues, RuleIssue, String>>,
+    pub streets: Option<TableView<RuleIssues, RuleIssue, String>>,
+    pub master: Option<TableView<StreetMaster, StreetName, String>>,
+    pub unlisted: Option<TableView<RuleIssues, RuleIssue, String>>,
+    pub street_list: StreetMaster,
+    pub street_revision: usize,
+    pub catalog: Catalog,
+    /// The id of the dataset open in the editor, with its change set and lifecycle.
+    pub editor: (Option<uuid::Uuid>, ChangeSet, Lifecycle),
+}
+
+impl Snapshot {
+    /// Copies the state covered by the [`Marker`] from `state`.
+    pub fn take(state: &SnapshotRef) -> Self {
+        let ops = state.operations;
+        Self {
+            address_table: state.address_table.clone(),
+            compare: ops.compare.table.clone(),
+            matrix: ops.matrix.table.clone(),
+            matrix_summary: ops.matrix.summary.clone(),
+            matrix_sources: ops.matrix.sources.clone(),
+            ranges: ops.lexis.view.clone(),
+            range_split: (ops.lexis.inclusion.clone(), ops.lexis.exclusion.clone()),
+            range_qa: ops.lexis.qa.clone(),
+            validation: ops.validation.table.clone(),
+            spatial: ops.spatial.table.clone(),
+            streets: ops.streets.table.clone(),
+            master: ops.master.table.clone(),
+            unlisted: ops.master.report.clone(),
+            street_list: ops.master.streets.clone(),
+            street_revision: ops.master.revision,
+            catalog: state.catalog.clone(),
+            editor: (
+                ops.editor.dataset,
+                ops.editor.changes.clone(),
+                ops.editor.lifecycle.clone(),
+            ),
+        }
+    }
+
+    /// Merges the snapshot into the live state.  Tables holding the same results as the snapshot
+    /// keep their selection and focus, and restored results are packaged again so the map layers
+    /// follow.
+    pub fn restore(
+        &self,
+        address_table: &mut Option<TableView<AddressPoints, AddressPoint, String>>,
+        ops: &mut Operations,
+        catalog: &mut Catalog,
+    ) {
+        merge(address_table, &self.address_table);
+        if let Some(t) = address_table {
+            t.package = Some(t.view.clone());
+        }
+        merge(&mut ops.compare.table, &self.compare);
+        if let Some(t) = &mut ops.compare.table {
+            t.package = Some(t.view.clone());
+        }
+        merge(&mut ops.matrix.table, &self.matrix);
+        merge(&mut ops.matrix.summary, &self.matrix_summary);
+        ops.matrix.sources = self.matrix_sources.clone();
+        merge(&mut ops.lexis.view, &self.ranges);
+        merge(&mut ops.lexis.qa, &self.range_qa);
+        (ops.lexis.inclusion, ops.lexis.exclusion) = self.range_split.clone();
+        if ops.lexis.view.is_some() {
+            ops.lexis.address_pkg = Some(vec![
+                ops.lexis.inclusion.clone(),
+                ops.lexis.exclusion.clone(),
+            ]);
+        }
+        ops.lexis.qa_pkg = Some(
+            ops.lexis
+                .qa
+                .as_ref()
+                .map(|t| t.view.clone())
+                .unwrap_or_default(),
+        );
+        merge(&mut ops.validation.table, &self.validation);
+        ops.validation.package = ops.validation.table.as_ref().map(|t| t.view.clone());
+        merge(&mut ops.spatial.table, &self.spatial);
+        ops.spatial.package = ops.spatial.table.as_ref().map(|t| t.view.clone());
+        merge(&mut ops.streets.table, &self.streets);
+        ops.streets.package = ops.streets.table.as_ref().map(|t| t.view.clone());
+        if ops.master.revision != self.street_revision {
+            ops.master.streets = self.street_list.clone();
+            ops.master.revision = self.street_revision;
+        }
+        merge(&mut ops.master.table, &self.master);
+        merge(&mut ops.master.report, &self.unlisted);
+        let (dataset, changes, lifecycle) = &self.editor;
+        ops.editor
+            .restore(*dataset, changes.clone(), lifecycle.clone());
+        *catalog = self.catalog.clone();
+    }
+}
+
+/// Merges the saved table `saved` into the live table `live`.  A saved table over the same data
+/// restores only its search, filter, sort and checks; otherwise it replaces the live table, as
+/// when undoing a run.
+fn merge<T, U, V>(live: &mut Option<TableView<T, U, V>>, saved: &Option<TableView<T, U, V>>)
+where
+    T: Tabular<U> + Filtration<T, V> + Clone + Default + PartialEq,
+    U: Columnar + Default,
+    V: Default + Clone,
+{
+    match (live.as_mut(), saved) {
+        (Some(table), Some(saved)) if table.data == saved.data => table.restore(saved.clone()),
+        _ => *live = saved.clone(),
+    }
+}
+
+/// The `Revision` struct is an entry in the history, holding the state before the change.
+#[derive(Debug, Clone)]
+pub struct Revision {
+    /// The `label` field describes the change.
+    pub label: String,
+    pub time: DateTime<Utc>,
+    pub snapshot: Snapshot,
+}
+
+/// The `History` struct holds the undo and redo stacks of a tab.
+#[derive(Debug, Default, Clone)]
+pub struct History {
+    undo: Vec<Revision>,
+    redo: Vec<Revision>,
+    /// The marker of the state after the last recorded change.  Cleared by an undo or redo, so the
+    /// restored state is marked on the next frame rather than recorded as a change.
+    marker: Option<Marker>,
+    /// A snapshot of the state after the last recorded change.  Taken when a revision is pushed,
+    /// and again once a run of merged changes settles, rather than on every change.
+    snapshot: Option<Snapshot>,
+    /// The `visible` field indicates the history window is visible.
+    pub visible: bool,
+}
+
+impl History {
+    /// Compares `state` to the last recorded state, recording a revision if it changed.
+    pub fn track(&mut self, state: SnapshotRef) {
+        self.track_at(state, Utc::now());
+    }
+
+    fn track_at(&mut self, state: SnapshotRef, now: DateTime<Utc>) {
+        let marker = Marker::new(&state);
+        let Some(last) = &self.marker else {
+            // First frame, or the first after an undo or redo, so there is nothing new to record.
+            self.marker = Some(marker);
+            if self.snapshot.is_none() {
+                self.snapshot = Some(Snapshot::take(&state));
+            }
+            return;
+        };
+        if *last == marker {
+            // Once the merge window closes, copy the settled state for the next revision.
+            if self.snapshot.is_none() && !self.merging(None, now) {
+                self.snapshot = Some(Snapshot::take(&state));
+            }
+            return;
+        }
+        let label = last.change(&marker);
+        match self.snapshot.take() {
+            Some(snapshot) if !self.merging(Some(&label), now) => {
+                tracing::trace!("Recording revision: {}", label);
+                self.undo.push(Revision {
+                    label,
+                    time: now,
+                    snapshot,
+                });
+                if self.undo.len() > HISTORY_LIMIT {
+                    self.undo.remove(0);
+                }
+                self.snapshot = Some(Snapshot::take(&state));
+            }
+            // A repeat of the last change merges into its revision.  So does any change before
+            // the state settles, since the state before it was not copied.
+            _ => {
+                if let Some(top) = self.undo.last_mut() {
+                    top.time = now;
+                }
+            }
+        }
+        self.redo.clear();
+        self.marker = Some(marker);
+    }
+
+    /// Returns true if a change at `now` falls in the merge window of the last revision.  With a
+    /// `label`, the change must also be of the same kind.
+    fn merging(&self, label: Option<&str>, now: DateTime<Utc>) -> bool {
+        match self.undo.last() {
+            Some(top) => {
+                label.map_or(true, |v| top.label == v)
+                    && (now - top.time).num_seconds() < MERGE_SECONDS
+            }
+            None => false,
+        }
+    }
+
+    /// Steps back one revision, returning its label.  The live `state` is copied to the redo
+    /// stack, and the state to restore is left in [`Self::snapshot`].
+    pub fn undo(&mut self, state: SnapshotRef) -> Option<String> {
+        let revision = self.undo.pop()?;
+        self.redo.push(Revision {
+            label: revision.label.clone(),
+            time: Utc::now(),
+            snapshot: Snapshot::take(&state),
+        });
+        self.marker = None;
+        self.snapshot = Some(revision.snapshot);
+        Some(revision.label)
+    }
+
+    /// Steps forward one revision, returning its label.  The live `state` is copied to the undo
+    /// stack, and the state to restore is left in [`Self::snapshot`].
+    pub fn redo(&mut self, state: SnapshotRef) -> Option<String> {
+        let revision = self.redo.pop()?;
+        self.undo.push(Revision {
+            label: revision.label.clone(),
+            time: Utc::now(),
+            snapshot: Snapshot::take(&state),
+        });
+        self.marker = None;
+        self.snapshot = Some(revision.snapshot);
+        Some(revision.label)
+    }
+
+    /// The snapshot of the state after the last recorded change, or the state restored by the
+    /// last undo or redo.
+    pub fn snapshot(&self) -> Option<&Snapshot> {
+        self.snapshot.as_ref()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Lists recent actions, with the number of steps to undo (negative) or redo (positive) to
+    /// reach the state after each action.
+    pub fn show(&self, ui: &mut egui::Ui) -> Option<i64> {
+        let mut steps = None;
+        let time = |v: &Revision| v.time.with_timezone(&Local).format("%H:%M:%S").to_string();
+        egui::ScrollArea::vertical()
+            .max_height(300.0)
+            .show(ui, |ui| {
+                // Redo entries are listed above the current state, greyed out.
+                for (i, revision) in self.redo.iter().enumerate() {
+                    let text =
+                        egui::RichText::new(format!("{}  {}", time(revision), revision.label))
+                            .weak();
+                    if ui.selectable_label(false, text).clicked() {
+                        steps = Some((self.redo.len() - i) as i64);
+                    }
+                }
+                for (i, revision) in self.undo.iter().rev().enumerate() {
+                    let text = format!("{}  {}", time(revision), revision.label);
+                    if ui.selectable_label(i == 0, text).clicked() {
+                        steps = Some(-(i as i64));
+                    }
+                }
+                if self.undo.is_empty() && self.redo.is_empty() {
+                    ui.label("No changes yet.");
+                }
+            });
+        steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn issues() -> RuleIssues {
+        let mut issues = RuleIssues::default();
+        for rule in ["Missing zip", "Duplicate"] {
+            issues.push(RuleIssue {
+                id: uuid::Uuid::new_v4(),
+                rule: rule.to_string(),
+                ..Default::default()
+            });
+        }
+        issues
+    }
+
+    fn operations() -> Operations {
+        let mut ops = Operations::default();
+        ops.validation.table = Some(TableView::new(issues()));
+        ops
+    }
+
+    fn track(history: &mut History, ops: &Operations, catalog: &Catalog, now: DateTime<Utc>) {
+        let state = SnapshotRef {
+            address_table: &None,
+            operations: ops,
+            catalog,
+        };
+        history.track_at(state, now);
+    }
+
+    fn search(ops: &mut Operations, text: &str) {
+        if let Some(table) = &mut ops.validation.table {
+            table.search = text.to_string();
+        }
+    }
+
+    fn undo(history: &mut History, ops: &mut Operations, catalog: &mut Catalog) -> Option<String> {
+        let state = SnapshotRef {
+            address_table: &None,
+            operations: ops,
+            catalog,
+        };
+        let label = history.undo(state)?;
+        history.snapshot().unwrap().restore(&mut None, ops, catalog);
+        Some(label)
+    }
+
+    fn redo(history: &mut History, ops: &mut Operations, catalog: &mut Catalog) -> Option<String> {
+        let state = SnapshotRef {
+            address_table: &None,
+            operations: ops,
+            catalog,
+        };
+        let label = history.redo(state)?;
+        history.snapshot().unwrap().restore(&mut None, ops, catalog);
+        Some(label)
+    }
+
+    fn searched(ops: &Operations) -> String {
+        ops.validation.table.as_ref().unwrap().search.clone()
+    }
+
+    #[test]
+    fn track_records_a_revision_per_change() {
+        let mut history = History::default();
+        let mut ops = operations();
+        let catalog = Catalog::default();
+        let start = Utc::now();
+        track(&mut history, &ops, &catalog, start);
+        assert!(!history.can_undo());
+        // An unchanged frame records nothing.
+        track(&mut history, &ops, &catalog, start + Duration::seconds(1));
+        assert!(!history.can_undo());
+        search(&mut ops, "zip");
+        track(&mut history, &ops, &catalog, start + Duration::seconds(10));
+        assert_eq!(history.undo.len(), 1);
+        assert_eq!(history.undo[0].label, "Search Validation");
+        ops.validation.table.as_mut().unwrap().config.sort = vec![SortKey::new(1)];
+        track(&mut history, &ops, &catalog, start + Duration::seconds(11));
+        assert_eq!(history.undo.len(), 2);
+        assert_eq!(history.undo[1].label, "Sort Validation");
+    }
+
+    #[test]
+    fn repeated_changes_merge_until_the_state_settles() {
+        let mut history = History::default();
+        let mut ops = operations();
+        let catalog = Catalog::default();
+        let start = Utc::now();
+        track(&mut history, &ops, &catalog, start);
+        search(&mut ops, "z");
+        track(&mut history, &ops, &catalog, start);
+        search(&mut ops, "zi");
+        track(&mut history, &ops, &catalog, start + Duration::seconds(1));
+        search(&mut ops, "zip");
+        track(&mut history, &ops, &catalog, start + Duration::seconds(2));
+        assert_eq!(history.undo.len(), 1);
+        // No snapshot is taken while the changes merge.
+        assert!(history.snapshot().is_none());
+        track(&mut history, &ops, &catalog, start + Duration::seconds(5));
+        assert_eq!(
+            history
+                .snapshot()
+                .map(|v| v.validation.as_ref().unwrap().search.as_str()),
+            Some("zip")
+        );
+        search(&mut ops, "");
+        track(&mut history, &ops, &catalog, start + Duration::seconds(10));
+        assert_eq!(history.undo.len(), 2);
+        assert_eq!(
+            history.undo[1]
+                .snapshot
+                .validation
+                .as_ref()
+                .map(|v| v.search.as_str()),
+            Some("zip")
+        );
+    }
+
+    #[test]
+    fn undo_and_redo_merge_into_the_live_state() {
+        let mut history = History::default();
+        let mut ops = operations();
+        let mut catalog = Catalog::default();
+        let start = Utc::now();
+        track(&mut history, &ops, &catalog, start);
+        search(&mut ops, "zip");
+        track(&mut history, &ops, &catalog, start + Duration::seconds(10));
+        // Settings outside the marker are not reverted by undo.
+        let excluded = uuid::Uuid::new_v4();
+        ops.matrix.excluded.insert(excluded);
+        let selected = ops.validation.table.as_ref().unwrap().row_ids()[1];
+        ops.validation.table.as_mut().unwrap().row_select = Some(selected);
+
+        assert_eq!(
+            undo(&mut history, &mut ops, &mut catalog).as_deref(),
+            Some("Search Validation")
+        );
+        assert_eq!(searched(&ops), "");
+        assert!(ops.matrix.excluded.contains(&excluded));
+        // The table holds the same issues, so it keeps its selection.
+        assert_eq!(
+            ops.validation.table.as_ref().unwrap().current_row(),
+            Some(selected)
+        );
+        assert!(!history.can_undo());
+        assert!(history.can_redo());
+        // The restored state is marked on the next frame, not recorded as a change.
+        track(&mut history, &ops, &catalog, start + Duration::seconds(11));
+        assert!(!history.can_undo());
+
+        assert_eq!(
+            redo(&mut history, &mut ops, &mut catalog).as_deref(),
+            Some("Search Validation")
+        );
+        assert_eq!(searched(&ops), "zip");
+        assert!(ops.matrix.excluded.contains(&excluded));
+        assert!(history.can_undo());
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn undo_replaces_tables_with_other_results() {
+        let mut history = History::default();
+        let mut ops = operations();
+        let mut catalog = Catalog::default();
+        let start = Utc::now();
+        track(&mut history, &ops, &catalog, start);
+        ops.validation.table = Some(TableView::new(issues()));
+        track(&mut history, &ops, &catalog, start + Duration::seconds(10));
+        assert_eq!(history.undo[0].label, "Run Validation");
+        let before = history.undo[0].snapshot.validation.clone();
+        undo(&mut history, &mut ops, &mut catalog);
+        assert_eq!(
+            ops.validation.table.as_ref().map(|v| v.row_ids()),
+            before.as_ref().map(|v| v.row_ids())
+        );
+        assert_eq!(
+            ops.validation.package,
+            before.as_ref().map(|v| v.view.clone())
+        );
+    }
+
+    #[test]
+    fn undo_restores_the_editor_lifecycle() {
+        let mut history = History::default();
+        let mut ops = operations();
+        let mut catalog = Catalog::default();
+        let start = Utc::now();
+        track(&mut history, &ops, &catalog, start);
+        ops.editor.lifecycle.revision += 1;
+        track(&mut history, &ops, &catalog, start + Duration::seconds(10));
+        assert_eq!(history.undo[0].label, EDIT_LABEL);
+        undo(&mut history, &mut ops, &mut catalog);
+        assert_eq!(ops.editor.lifecycle.revision, 0);
+        redo(&mut history, &mut ops, &mut catalog);
+        assert_eq!(ops.editor.lifecycle.revision, 1);
+    }
+}
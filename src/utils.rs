@@ -43,3 +43,38 @@ pub fn toggle_select(select: &mut HashSet<usize>, row: usize, response: &egui::R
         }
     }
 }
+
+/// Great-circle distance in meters between two points in decimal degrees, using the haversine
+/// formula.
+pub fn distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let radius = 6_371_008.8;
+    let d_lat = (lat2 - lat1).to_radians();
+    let d_lon = (lon2 - lon1).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2)
+        + lat1.to_radians().cos() * lat2.to_radians().cos() * (d_lon / 2.0).sin().powi(2);
+    2.0 * radius * a.sqrt().asin()
+}
+
+/// The name of the user logged in to the machine, used to attribute edits.
+pub fn user_name() -> String {
+    std::env::var("USERNAME")
+        .or_else(|_| std::env::var("USER"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// The length in web mercator units of one meter on the ground at `latitude`.  Web mercator
+/// stretches lengths by the secant of the latitude, about 1.35 at 42 degrees north.
+pub fn mercator_scale(latitude: f64) -> f64 {
+    1.0 / latitude.to_radians().cos()
+}
+
+/// Projects a point in decimal degrees to web mercator (EPSG:3857) coordinates in meters.
+pub fn web_mercator(latitude: f64, longitude: f64) -> (f64, f64) {
+    let radius = 6_378_137.0;
+    let x = radius * longitude.to_radians();
+    let y = radius
+        * (std::f64::consts::FRAC_PI_4 + latitude.to_radians() / 2.0)
+            .tan()
+            .ln();
+    (x, y)
+}